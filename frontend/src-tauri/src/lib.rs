@@ -5,6 +5,9 @@ use tauri::{
 use tauri::menu::{MenuBuilder, MenuItemBuilder};
 
 mod markdown;
+mod settings;
+mod window;
+mod workspace;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -13,22 +16,171 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_sql::Builder::default().build())
+        .plugin(tauri_plugin_store::Builder::default().build())
         .invoke_handler(tauri::generate_handler![
             markdown::notes::markdown_get_file_tree,
+            markdown::notes::markdown_list_all_paths,
+            markdown::notes::markdown_create_folder,
+            markdown::notes::markdown_next_untitled_name,
             markdown::search::markdown_init_index,
             markdown::search::markdown_index_note,
             markdown::search::markdown_remove_from_index,
             markdown::search::markdown_list_notes,
             markdown::search::markdown_search_notes,
+            markdown::search::markdown_export_search_results,
+            markdown::search::markdown_search_suggest,
+            markdown::search::markdown_tag_cooccurrence,
+            markdown::search::markdown_search_by_tag,
+            markdown::search::markdown_tag_tree,
+            markdown::search::markdown_find_by_filename,
+            markdown::search::markdown_record_open,
+            markdown::search::markdown_list_recently_opened,
+            markdown::search::markdown_list_by_size,
+            markdown::search::markdown_list_empty_notes,
+            markdown::search::markdown_list_missing_frontmatter,
+            markdown::frontmatter::markdown_add_default_frontmatter,
+            markdown::frontmatter::markdown_validate_frontmatter,
+            markdown::frontmatter::markdown_validate_vault_frontmatter,
+            markdown::search::markdown_sibling_notes,
+            markdown::search::markdown_folder_stats,
+            markdown::search::markdown_pool_stats,
+            markdown::sync::markdown_list_modified_since,
+            markdown::sync::markdown_list_tombstones,
+            markdown::sync::markdown_purge_tombstones,
             markdown::watcher::markdown_watch_vault,
+            markdown::watcher::markdown_unwatch_vault,
+            markdown::watcher::markdown_watch_and_index,
+            markdown::watcher::markdown_unwatch_and_index,
+            markdown::watcher::markdown_watch_file,
+            markdown::watcher::markdown_unwatch_file,
+            markdown::blocks::markdown_resolve_block,
+            markdown::aliases::markdown_find_alias_collisions,
+            markdown::indexer::markdown_compute_checksum,
+            markdown::indexer::markdown_reindex_note,
+            markdown::indexer::markdown_check_large_notes,
+            markdown::manifest::markdown_vault_manifest,
+            markdown::manifest::markdown_reconcile_index,
+            markdown::dates::markdown_find_stale_frontmatter,
+            markdown::dates::markdown_sync_frontmatter_dates,
+            markdown::dates::markdown_writing_streak,
+            markdown::links::markdown_link_counts,
+            markdown::links::markdown_deletion_impact,
+            markdown::links::markdown_get_backlinks,
+            markdown::links::markdown_find_cycles,
+            markdown::links::markdown_link_path,
+            markdown::links::markdown_compute_importance,
+            markdown::links::markdown_get_importance,
+            markdown::links::markdown_get_local_graph,
+            markdown::links::markdown_export_graph,
+            markdown::links::markdown_reading_sequence,
+            markdown::links::markdown_follow_link,
+            markdown::links::markdown_normalize_links,
+            markdown::links::markdown_broken_links_with_suggestions,
+            markdown::links::markdown_fix_broken_link,
+            markdown::links::markdown_update_backlink_sections,
+            markdown::links::markdown_list_external_urls,
+            markdown::urls::markdown_check_urls,
+            markdown::urls::markdown_cancel_url_check,
+            markdown::mentions::markdown_unlinked_mentions,
+            markdown::mentions::markdown_link_mention,
+            markdown::headings::markdown_list_all_headings,
+            markdown::render::markdown_render_html,
+            markdown::render::markdown_html_to_markdown,
+            markdown::embeds::markdown_render_with_embeds,
+            markdown::trash::markdown_delete_note,
+            markdown::trash::markdown_restore_from_trash,
+            markdown::trash::markdown_empty_trash,
+            markdown::diff::markdown_diff_note,
+            markdown::external::markdown_open_externally,
+            markdown::external::markdown_reveal_in_file_manager,
+            markdown::bundle::markdown_export_bundle,
+            markdown::import::markdown_import_folder,
+            markdown::localize::markdown_localize_images,
+            markdown::templates::markdown_list_template_details,
+            markdown::naming::markdown_check_naming,
+            markdown::convert::markdown_convert_wikilinks,
+            markdown::permalinks::markdown_get_permalink,
+            markdown::permalinks::markdown_list_permalinks,
+            markdown::site::markdown_export_site,
+            markdown::tags::markdown_bulk_add_tag,
+            markdown::tags::markdown_bulk_remove_tag,
+            markdown::tags::markdown_find_tag_variants,
+            markdown::tags::markdown_merge_tags,
+            markdown::lint::markdown_lint_note,
+            markdown::lint::markdown_line_ending_report,
+            markdown::lint::markdown_normalize_line_endings,
+            markdown::lint::markdown_autofix,
+            markdown::versions::markdown_snapshot_note,
+            markdown::versions::markdown_list_versions,
+            markdown::versions::markdown_restore_version,
+            markdown::versions::markdown_diff_versions,
+            markdown::git::markdown_git_init,
+            markdown::git::markdown_git_status,
+            markdown::git::markdown_git_commit,
+            markdown::git::markdown_git_log,
+            markdown::git::markdown_git_pull,
+            markdown::git::markdown_git_push,
+            markdown::git::markdown_find_conflict_markers,
+            markdown::git::markdown_resolve_conflict,
+            markdown::encryption::markdown_encrypt_note,
+            markdown::encryption::markdown_decrypt_note,
+            markdown::encryption::markdown_read_encrypted_note,
+            markdown::language::markdown_detect_language,
+            markdown::cjk::markdown_search_cjk,
+            markdown::embeddings::markdown_store_embedding,
+            markdown::embeddings::markdown_semantic_search,
+            markdown::embeddings::markdown_hybrid_search,
+            markdown::analytics::markdown_analytics,
+            markdown::analytics::markdown_index_breakdown,
+            markdown::analytics::markdown_vault_health,
+            markdown::analytics::markdown_word_count_histogram,
+            markdown::rename::markdown_bulk_rename,
+            markdown::tasks::markdown_export_tasks_ics,
+            markdown::grep::markdown_grep_vault,
+            markdown::grep::markdown_cancel_grep,
+            markdown::windows::markdown_open_note_window,
+            markdown::windows::markdown_list_note_windows,
+            window::window_set_dock_visible,
+            window::window_get_close_behavior,
+            window::window_set_close_behavior,
+            window::window_quit_app,
+            window::set_status_window_click_through,
+            window::set_main_always_on_top,
+            window::get_main_always_on_top,
+            window::set_window_zoom,
+            window::get_pinned_notes,
+            window::set_pinned_notes,
+            window::open_pinned_note,
+            workspace::save_workspace,
+            workspace::restore_workspace,
+            workspace::list_workspaces,
+            workspace::delete_workspace,
         ])
         .setup(setup_app)
         .on_window_event(handle_window_event)
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // Closing the last window must not quit; the tray's Quit exits explicitly
+            if let tauri::RunEvent::ExitRequested { code: None, api, .. } = event {
+                api.prevent_exit();
+            }
+        });
 }
 
 fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    // Run as a tray app on macOS: no dock icon until the user asks for one
+    #[cfg(target_os = "macos")]
+    app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+
+    let window_state = window::WindowState::load(app.handle());
+    app.manage(window_state);
+
+    if let Some(main_window) = app.get_webview_window("main") {
+        window::restore_main_always_on_top(&main_window);
+        window::restore_window_zoom(&main_window);
+    }
+
     // Create system tray menu
     let show_main_item = MenuItemBuilder::with_id("show_main", "Show Main Window").build(app)?;
     let reset_indicator_item = MenuItemBuilder::with_id("reset_indicator", "Reset Status Indicator").build(app)?;
@@ -64,6 +216,8 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                         #[cfg(debug_assertions)]
                         eprintln!("Failed to emit reset event: {:?}", e);
                     }
+                    window::reset_status_click_through(&window);
+                    // Shown without focusing so the widget doesn't steal focus from the active app
                     if let Err(e) = window.show() {
                         #[cfg(debug_assertions)]
                         eprintln!("Failed to show status indicator: {:?}", e);
@@ -72,10 +226,6 @@ fn setup_app(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
                         #[cfg(debug_assertions)]
                         eprintln!("Failed to unminimize status indicator: {:?}", e);
                     }
-                    if let Err(e) = window.set_focus() {
-                        #[cfg(debug_assertions)]
-                        eprintln!("Failed to focus status indicator: {:?}", e);
-                    }
                 }
             }
             "quit" => {
@@ -123,7 +273,7 @@ fn create_status_window(app: &AppHandle) -> Result<(), Box<dyn std::error::Error
         .items(&[&show_dashboard_item])
         .build()?;
     
-    let status_window = WebviewWindowBuilder::new(
+    let status_builder = WebviewWindowBuilder::new(
         app,
         "status_indicator",
         WebviewUrl::App("status-indicator".into())
@@ -135,9 +285,16 @@ fn create_status_window(app: &AppHandle) -> Result<(), Box<dyn std::error::Error
     .always_on_top(true)
     .skip_taskbar(true)
     .resizable(true)
-    .visible(true)
-    .hidden_title(true)
-    .title_bar_style(tauri::TitleBarStyle::Overlay)
+    .focused(false)
+    .visible(true);
+
+    // Title bar styling only exists on macOS
+    #[cfg(target_os = "macos")]
+    let status_builder = status_builder
+        .hidden_title(true)
+        .title_bar_style(tauri::TitleBarStyle::Overlay);
+
+    let status_window = status_builder
     .menu(widget_menu)
     .on_menu_event(move |window, event| {
         match event.id().as_ref() {
@@ -179,13 +336,40 @@ fn create_status_window(app: &AppHandle) -> Result<(), Box<dyn std::error::Error
         eprintln!("Failed to show status window after creation: {:?}", e);
     }
 
+    apply_status_window_transparency(&status_window);
+    window::restore_status_click_through(&status_window);
+    window::restore_window_zoom(&status_window);
+
+    Ok(())
+}
+
+/// Best-effort transparency for the status indicator. Failures are logged and
+/// the widget keeps the plain `.transparent(true)` look.
+///
+/// - macOS: clear NSWindow background and visible on all Spaces. Vibrancy is
+///   not applied.
+/// - Windows: Mica on Windows 11, acrylic on Windows 10 (1809+), otherwise
+///   plain transparency.
+/// - Linux: plain transparency only, and only under a compositing window
+///   manager. Blur and vibrancy are unsupported.
+fn apply_status_window_transparency(status_window: &tauri::WebviewWindow) {
     // Set the webview background to transparent and make visible on all Spaces (macOS only)
     #[cfg(target_os = "macos")]
     {
         use cocoa::appkit::{NSColor, NSWindow, NSWindowCollectionBehavior};
         use cocoa::base::{id, nil};
-        
-        let ns_window = status_window.ns_window().unwrap() as id;
+
+        let ns_window = match status_window.ns_window() {
+            Ok(handle) if !handle.is_null() => handle as id,
+            Ok(_) => {
+                log::warn!("Status window has no NSWindow; skipping transparency");
+                return;
+            }
+            Err(e) => {
+                log::warn!("Failed to get NSWindow for status window: {:?}", e);
+                return;
+            }
+        };
         unsafe {
             let clear_color: id = NSColor::clearColor(nil);
             ns_window.setBackgroundColor_(clear_color);
@@ -200,18 +384,49 @@ fn create_status_window(app: &AppHandle) -> Result<(), Box<dyn std::error::Error
         }
     }
 
-    Ok(())
+    // Mica needs Windows 11; fall back to acrylic, then to plain transparency
+    #[cfg(target_os = "windows")]
+    {
+        if window_vibrancy::apply_mica(status_window, None).is_err() {
+            if let Err(e) = window_vibrancy::apply_acrylic(status_window, Some((18, 18, 18, 125))) {
+                log::warn!("Window backdrop effects unavailable for status window: {:?}", e);
+            }
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let _ = status_window;
 }
 
 fn handle_window_event(window: &tauri::Window, event: &WindowEvent) {
     if window.label() == "main" {
         if let WindowEvent::CloseRequested { api, .. } = event {
-            // Prevent app quit, just hide the main window
-            if let Err(e) = window.hide() {
-                #[cfg(debug_assertions)]
-                eprintln!("Failed to hide main window: {:?}", e);
+            let behavior = window
+                .try_state::<window::WindowState>()
+                .map(|state| state.close_behavior())
+                .unwrap_or_default();
+
+            match behavior {
+                window::CloseBehavior::Hide => {
+                    // Prevent app quit, just hide the main window
+                    if let Err(e) = window.hide() {
+                        #[cfg(debug_assertions)]
+                        eprintln!("Failed to hide main window: {:?}", e);
+                    }
+                    api.prevent_close();
+                }
+                window::CloseBehavior::Quit => {
+                    window.app_handle().exit(0);
+                }
+                window::CloseBehavior::Ask => {
+                    // The frontend answers with window_quit_app or by hiding the window
+                    api.prevent_close();
+                    if let Err(e) = window.emit("confirm-quit", ()) {
+                        #[cfg(debug_assertions)]
+                        eprintln!("Failed to emit confirm-quit event: {:?}", e);
+                    }
+                }
             }
-            api.prevent_close();
         }
     }
 }
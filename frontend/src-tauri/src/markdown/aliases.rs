@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use tauri::command;
+
+use super::search::get_or_create_pool;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AliasOwner {
+    pub path: String,
+    pub title: String,
+    /// Whether the colliding name is this note's title rather than one of its aliases.
+    pub is_title: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AliasCollision {
+    pub alias: String,
+    pub notes: Vec<AliasOwner>,
+}
+
+#[command]
+pub async fn markdown_find_alias_collisions(index_path: String) -> Result<Vec<AliasCollision>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query("SELECT path, title, aliases FROM notes ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Query failed: {}", e))?;
+
+    // Keyed by the lowercased name; the first spelling seen is reported.
+    let mut names: BTreeMap<String, (String, Vec<AliasOwner>)> = BTreeMap::new();
+
+    for row in &rows {
+        let path = row.get::<String, _>("path");
+        let title = row.get::<String, _>("title");
+        let aliases: Vec<String> = row
+            .get::<Option<String>, _>("aliases")
+            .and_then(|a| serde_json::from_str(&a).ok())
+            .unwrap_or_default();
+
+        let mut candidates = vec![(title.clone(), true)];
+        candidates.extend(aliases.into_iter().map(|a| (a, false)));
+
+        for (name, is_title) in candidates {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                continue;
+            }
+            let entry = names
+                .entry(name.to_lowercase())
+                .or_insert_with(|| (name.clone(), Vec::new()));
+            if entry.1.iter().any(|owner| owner.path == path) {
+                continue;
+            }
+            entry.1.push(AliasOwner {
+                path: path.clone(),
+                title: title.clone(),
+                is_title,
+            });
+        }
+    }
+
+    Ok(names
+        .into_values()
+        .filter(|(_, notes)| notes.len() > 1)
+        .map(|(alias, notes)| AliasCollision { alias, notes })
+        .collect())
+}
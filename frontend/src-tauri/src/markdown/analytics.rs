@@ -0,0 +1,401 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use tauri::command;
+
+use super::search::get_or_create_pool;
+
+const DEFAULT_TOP: i64 = 10;
+const DEFAULT_STALE_MONTHS: u32 = 6;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HubNote {
+    pub path: String,
+    pub title: String,
+    /// Distinct notes linking here, self-links excluded.
+    pub backlink_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagUsage {
+    pub tag: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultAnalytics {
+    pub note_count: i64,
+    pub word_count: i64,
+    /// Links between notes; attachment links aren't counted.
+    pub link_count: i64,
+    pub broken_link_count: i64,
+    /// Distinct tags, ignoring case and a leading `#`.
+    pub tag_count: i64,
+    pub top_hubs: Vec<HubNote>,
+    pub top_tags: Vec<TagUsage>,
+    /// Resolved links between distinct notes, per note.
+    pub average_backlinks: f64,
+    /// Notes with no links in or out, self-links aside.
+    pub orphan_count: i64,
+}
+
+/// Notes with no links in or out, self-links aside.
+async fn count_orphans(pool: &Pool<Sqlite>) -> Result<i64, String> {
+    sqlx::query_scalar(r#"
+        SELECT COUNT(*) FROM notes n
+        WHERE NOT EXISTS (
+            SELECT 1 FROM note_links
+            WHERE target_note_path = n.path AND source_note_path != n.path
+        )
+        AND NOT EXISTS (
+            SELECT 1 FROM note_links
+            WHERE source_note_path = n.path AND link_type != 'attachment'
+              AND target_note_path IS NOT NULL AND target_note_path != n.path
+        )
+    "#)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to count orphans: {}", e))
+}
+
+/// Vault-wide totals for a stats page in one call, each figure from a
+/// single aggregate query over the index. `top` caps the hub and tag
+/// lists, defaulting to 10.
+#[command]
+pub async fn markdown_analytics(index_path: String, top: Option<i64>) -> Result<VaultAnalytics, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let top = top.unwrap_or(DEFAULT_TOP).max(0);
+
+    let totals = sqlx::query("SELECT COUNT(*) AS notes, COALESCE(SUM(word_count), 0) AS words FROM notes")
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| format!("Failed to count notes: {}", e))?;
+    let note_count = totals.get::<i64, _>("notes");
+
+    let links = sqlx::query(r#"
+        SELECT COUNT(*) AS links,
+               COALESCE(SUM(target_note_path IS NULL), 0) AS broken,
+               COALESCE(SUM(target_note_path IS NOT NULL AND target_note_path != source_note_path), 0) AS between_notes
+        FROM note_links
+        WHERE link_type != 'attachment'
+    "#)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to count links: {}", e))?;
+
+    let top_hubs = sqlx::query(r#"
+        SELECT l.target_note_path AS path, n.title, COUNT(DISTINCT l.source_note_path) AS backlinks
+        FROM note_links l
+        JOIN notes n ON n.path = l.target_note_path
+        WHERE l.link_type != 'attachment' AND l.source_note_path != l.target_note_path
+        GROUP BY l.target_note_path
+        ORDER BY backlinks DESC, l.target_note_path
+        LIMIT ?
+    "#)
+    .bind(top)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to find hub notes: {}", e))?
+    .iter()
+    .map(|row| HubNote {
+        path: row.get::<String, _>("path"),
+        title: row.get::<String, _>("title"),
+        backlink_count: row.get::<i64, _>("backlinks"),
+    })
+    .collect();
+
+    // `COUNT(*) OVER ()` gives the number of distinct tags before LIMIT.
+    let tag_rows = sqlx::query(r#"
+        SELECT ltrim(MIN(tag.value), '#') AS tag,
+               COUNT(DISTINCT notes.path) AS uses,
+               COUNT(*) OVER () AS distinct_tags
+        FROM notes, json_each(CASE WHEN json_valid(notes.tags) THEN notes.tags ELSE '[]' END) AS tag
+        WHERE tag.type = 'text' AND ltrim(tag.value, '#') != ''
+        GROUP BY lower(ltrim(tag.value, '#'))
+        ORDER BY uses DESC, lower(ltrim(tag.value, '#'))
+        LIMIT ?
+    "#)
+    .bind(top.max(1))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to count tags: {}", e))?;
+    let tag_count = tag_rows.first().map(|row| row.get::<i64, _>("distinct_tags")).unwrap_or(0);
+    let top_tags = tag_rows
+        .iter()
+        .take(top as usize)
+        .map(|row| TagUsage {
+            tag: row.get::<String, _>("tag"),
+            count: row.get::<i64, _>("uses"),
+        })
+        .collect();
+
+    let orphan_count = count_orphans(&pool).await?;
+
+    let between_notes = links.get::<i64, _>("between_notes");
+    Ok(VaultAnalytics {
+        note_count,
+        word_count: totals.get::<i64, _>("words"),
+        link_count: links.get::<i64, _>("links"),
+        broken_link_count: links.get::<i64, _>("broken"),
+        tag_count,
+        top_hubs,
+        top_tags,
+        average_backlinks: if note_count > 0 { between_notes as f64 / note_count as f64 } else { 0.0 },
+        orphan_count,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexContribution {
+    pub path: String,
+    pub title: String,
+    pub word_count: i64,
+    /// Indexed body size in bytes, which drives the full-text index size.
+    pub content_bytes: i64,
+    /// Fraction of all indexed content this note accounts for.
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IndexBreakdown {
+    /// The database file plus its write-ahead log.
+    pub total_bytes: u64,
+    pub database_bytes: u64,
+    pub wal_bytes: u64,
+    pub note_count: i64,
+    pub content_bytes: i64,
+    /// Largest notes by content first.
+    pub largest: Vec<IndexContribution>,
+}
+
+/// How big the index is and which notes take up the most of it, to track
+/// down large generated files. `top_n` defaults to 10.
+#[command]
+pub async fn markdown_index_breakdown(index_path: String, top_n: Option<i64>) -> Result<IndexBreakdown, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let top = top_n.unwrap_or(DEFAULT_TOP).max(0);
+
+    let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| format!("Failed to read index size: {}", e))?;
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| format!("Failed to read index size: {}", e))?;
+    let database_bytes = (page_count * page_size).max(0) as u64;
+    let wal_bytes = std::fs::metadata(format!("{}-wal", index_path)).map(|m| m.len()).unwrap_or(0);
+
+    // The window totals are computed before LIMIT, so they cover every note.
+    let rows = sqlx::query(r#"
+        SELECT path, title, COALESCE(word_count, 0) AS words,
+               length(CAST(content AS BLOB)) AS bytes,
+               SUM(length(CAST(content AS BLOB))) OVER () AS total_bytes,
+               COUNT(*) OVER () AS notes
+        FROM notes
+        ORDER BY bytes DESC, path
+        LIMIT ?
+    "#)
+    .bind(top.max(1))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to measure notes: {}", e))?;
+
+    let note_count = rows.first().map(|row| row.get::<i64, _>("notes")).unwrap_or(0);
+    let content_bytes = rows.first().map(|row| row.get::<i64, _>("total_bytes")).unwrap_or(0);
+    let largest = rows
+        .iter()
+        .take(top as usize)
+        .map(|row| {
+            let bytes = row.get::<i64, _>("bytes");
+            IndexContribution {
+                path: row.get::<String, _>("path"),
+                title: row.get::<String, _>("title"),
+                word_count: row.get::<i64, _>("words"),
+                content_bytes: bytes,
+                share: if content_bytes > 0 { bytes as f64 / content_bytes as f64 } else { 0.0 },
+            }
+        })
+        .collect();
+
+    Ok(IndexBreakdown {
+        total_bytes: database_bytes + wal_bytes,
+        database_bytes,
+        wal_bytes,
+        note_count,
+        content_bytes,
+        largest,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthFactor {
+    /// `broken_links`, `orphans`, `untagged`, `stale` or `duplicates`.
+    pub name: String,
+    /// Notes affected.
+    pub count: i64,
+    /// `count` as a fraction of all notes.
+    pub ratio: f64,
+    /// Share of the score this factor can take away; weights sum to 1.
+    pub weight: f64,
+    /// Points taken off the score.
+    pub penalty: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultHealth {
+    /// 0 to 100; 100 when no note is affected by any factor.
+    pub score: u32,
+    pub note_count: i64,
+    pub factors: Vec<HealthFactor>,
+}
+
+/// One score for a dashboard widget, from 100 down by each factor's weight
+/// times the fraction of notes it affects: notes with broken links, orphans,
+/// untagged notes, notes not updated in `stale_months` (default 6) and
+/// notes whose body repeats another's. The factors are returned too, so the
+/// UI can point at what to fix.
+#[command]
+pub async fn markdown_vault_health(index_path: String, stale_months: Option<u32>) -> Result<VaultHealth, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let stale_months = stale_months.unwrap_or(DEFAULT_STALE_MONTHS);
+
+    let counts = sqlx::query(r#"
+        SELECT COUNT(*) AS notes,
+               COALESCE(SUM(json_array_length(CASE WHEN json_valid(tags) THEN tags ELSE '[]' END) = 0), 0) AS untagged,
+               COALESCE(SUM(julianday(updated) < julianday('now', ?)), 0) AS stale
+        FROM notes
+    "#)
+    .bind(format!("-{} months", stale_months))
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to count notes: {}", e))?;
+    let note_count = counts.get::<i64, _>("notes");
+
+    let broken: i64 = sqlx::query_scalar(r#"
+        SELECT COUNT(DISTINCT l.source_note_path)
+        FROM note_links l
+        JOIN notes n ON n.path = l.source_note_path
+        WHERE l.target_note_path IS NULL AND l.link_type != 'attachment'
+    "#)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to count broken links: {}", e))?;
+
+    // Every copy after the first in each group of identical bodies.
+    let duplicates: i64 = sqlx::query_scalar(r#"
+        SELECT COALESCE(SUM(copies - 1), 0) FROM (
+            SELECT COUNT(*) AS copies FROM notes
+            WHERE trim(content) != ''
+            GROUP BY content
+            HAVING copies > 1
+        )
+    "#)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to count duplicates: {}", e))?;
+
+    let orphans = count_orphans(&pool).await?;
+
+    let factors: Vec<HealthFactor> = [
+        ("broken_links", broken, 0.3),
+        ("orphans", orphans, 0.2),
+        ("untagged", counts.get::<i64, _>("untagged"), 0.15),
+        ("stale", counts.get::<i64, _>("stale"), 0.15),
+        ("duplicates", duplicates, 0.2),
+    ]
+    .into_iter()
+    .map(|(name, count, weight)| {
+        let ratio = if note_count > 0 { (count as f64 / note_count as f64).min(1.0) } else { 0.0 };
+        HealthFactor {
+            name: name.to_string(),
+            count,
+            ratio,
+            weight,
+            penalty: ratio * weight * 100.0,
+        }
+    })
+    .collect();
+
+    let penalty: f64 = factors.iter().map(|f| f.penalty).sum();
+    Ok(VaultHealth {
+        score: (100.0 - penalty).round().clamp(0.0, 100.0) as u32,
+        note_count,
+        factors,
+    })
+}
+
+/// Bucket boundaries when none are given: 0-100, 100-500, 500-1000,
+/// 1000-2500 and 2500 or more words.
+const DEFAULT_WORD_BUCKETS: [i64; 4] = [100, 500, 1000, 2500];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WordCountBucket {
+    /// Inclusive.
+    pub min: i64,
+    /// Exclusive; `None` for the last, open-ended bucket.
+    pub max: Option<i64>,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WordCountHistogram {
+    pub buckets: Vec<WordCountBucket>,
+    pub note_count: i64,
+    pub median: f64,
+    pub p90: f64,
+}
+
+/// Percentile of sorted values by linear interpolation between ranks.
+fn percentile(sorted: &[i64], fraction: f64) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        len => {
+            let rank = fraction * (len - 1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+            sorted[low] as f64 + (sorted[high] - sorted[low]) as f64 * (rank - low as f64)
+        }
+    }
+}
+
+/// How notes spread over word-count ranges, with the median and 90th
+/// percentile, from one fetch of every note's word count. `buckets` are
+/// the boundaries between ranges, e.g. `[100, 500]` for 0-100, 100-500 and
+/// 500 or more; they are sorted and deduplicated, and default to
+/// 100, 500, 1000 and 2500.
+#[command]
+pub async fn markdown_word_count_histogram(
+    index_path: String,
+    buckets: Option<Vec<i64>>,
+) -> Result<WordCountHistogram, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let mut bounds: Vec<i64> = buckets
+        .unwrap_or_else(|| DEFAULT_WORD_BUCKETS.to_vec())
+        .into_iter()
+        .filter(|&bound| bound > 0)
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let counts: Vec<i64> = sqlx::query_scalar("SELECT COALESCE(word_count, 0) FROM notes ORDER BY 1")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load word counts: {}", e))?;
+
+    let mut histogram: Vec<WordCountBucket> = std::iter::once(0)
+        .chain(bounds.iter().copied())
+        .zip(bounds.iter().copied().map(Some).chain(std::iter::once(None)))
+        .map(|(min, max)| WordCountBucket { min, max, count: 0 })
+        .collect();
+    for &words in &counts {
+        let index = bounds.partition_point(|&bound| bound <= words);
+        histogram[index].count += 1;
+    }
+
+    Ok(WordCountHistogram {
+        buckets: histogram,
+        note_count: counts.len() as i64,
+        median: percentile(&counts, 0.5),
+        p90: percentile(&counts, 0.9),
+    })
+}
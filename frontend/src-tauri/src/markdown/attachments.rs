@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::vault::{is_markdown_path, resolve_in_vault, walk_vault, VaultConfig};
+
+/// Collapses `.`/`..` segments without touching the extension.
+pub fn collapse(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// Path of `to` relative to the folder containing `from`, both vault-relative.
+pub fn relative_to(from: &str, to: &str) -> String {
+    let mut from_dir: Vec<&str> = from.split('/').collect();
+    from_dir.pop();
+    let to_parts: Vec<&str> = to.split('/').collect();
+    let common = from_dir
+        .iter()
+        .zip(&to_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<&str> = vec![".."; from_dir.len() - common];
+    parts.extend(&to_parts[common..]);
+    parts.join("/")
+}
+
+/// Non-markdown files in the vault, keyed by lowercased path and file name.
+struct FileIndex {
+    paths: HashMap<String, String>,
+    by_name: HashMap<String, Vec<String>>,
+}
+
+impl FileIndex {
+    fn load(vault_path: &str) -> FileIndex {
+        let mut index = FileIndex {
+            paths: HashMap::new(),
+            by_name: HashMap::new(),
+        };
+        for (rel_path, entry) in walk_vault(vault_path) {
+            if !entry.file_type().is_file() || is_markdown_path(&rel_path) {
+                continue;
+            }
+            let key = rel_path.to_lowercase();
+            let name = key.rsplit('/').next().unwrap_or(&key).to_string();
+            index.by_name.entry(name).or_default().push(rel_path.clone());
+            index.paths.insert(key, rel_path);
+        }
+        index
+    }
+}
+
+/// Resolves attachment targets like `![[image.png]]` to vault-relative
+/// paths. Candidates are tried in order:
+///
+/// 1. relative to the folder of the note holding the link;
+/// 2. inside the `attachmentFolder` vault setting, if set. A folder starting
+///    with `./` is taken relative to the note, as Obsidian does;
+/// 3. vault-wide: the target as a vault-absolute path, then the shallowest
+///    file with the same name anywhere in the vault.
+///
+/// Steps 1 and 2 and the first half of 3 only stat the candidates. The name
+/// search walks the vault once, on first use.
+pub struct AttachmentResolver {
+    vault_path: String,
+    folder: Option<String>,
+    files: OnceLock<FileIndex>,
+}
+
+impl AttachmentResolver {
+    pub fn load(vault_path: &str) -> AttachmentResolver {
+        let folder = VaultConfig::load(vault_path)
+            .and_then(|config| {
+                config
+                    .settings
+                    .get("attachmentFolder")
+                    .and_then(|v| v.as_str())
+                    .map(|f| f.trim().trim_end_matches('/').to_string())
+            })
+            .filter(|f| !f.is_empty() && f != "." && f != "/");
+
+        AttachmentResolver {
+            vault_path: vault_path.to_string(),
+            folder,
+            files: OnceLock::new(),
+        }
+    }
+
+    fn exists(&self, rel_path: &str) -> bool {
+        !rel_path.is_empty()
+            && resolve_in_vault(&self.vault_path, rel_path)
+                .map(|p| p.is_file())
+                .unwrap_or(false)
+    }
+
+    fn candidates(&self, source_path: &str, target: &str) -> Vec<String> {
+        let parent = source_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        let name = target.rsplit('/').next().unwrap_or(target);
+
+        let mut candidates = vec![collapse(&format!("{}/{}", parent, target))];
+        if let Some(folder) = &self.folder {
+            let folder = match folder.strip_prefix("./") {
+                Some(relative) => collapse(&format!("{}/{}", parent, relative)),
+                None => collapse(folder),
+            };
+            candidates.push(collapse(&format!("{}/{}", folder, target)));
+            candidates.push(collapse(&format!("{}/{}", folder, name)));
+        }
+        candidates.push(collapse(target));
+        candidates
+    }
+
+    /// Resolves a target by stat-ing the candidate paths only, for callers
+    /// that can't afford a vault walk per note.
+    pub fn resolve_nearby(&self, source_path: &str, target: &str) -> Option<String> {
+        let target = target.trim().replace('\\', "/");
+        self.candidates(source_path, &target).into_iter().find(|c| self.exists(c))
+    }
+
+    /// Full resolution, falling back to a case-insensitive search of the
+    /// whole vault by path and then by file name.
+    pub fn resolve(&self, source_path: &str, target: &str) -> Option<String> {
+        if let Some(path) = self.resolve_nearby(source_path, target) {
+            return Some(path);
+        }
+
+        let target = target.trim().replace('\\', "/");
+        let files = self.files.get_or_init(|| FileIndex::load(&self.vault_path));
+        for candidate in self.candidates(source_path, &target) {
+            if let Some(path) = files.paths.get(&candidate.to_lowercase()) {
+                return Some(path.clone());
+            }
+        }
+
+        let name = target.rsplit('/').next().unwrap_or(&target).to_lowercase();
+        files
+            .by_name
+            .get(&name)
+            .and_then(|paths| paths.iter().min_by_key(|p| (p.matches('/').count(), (*p).clone())))
+            .cloned()
+    }
+}
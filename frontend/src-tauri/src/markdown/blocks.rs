@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, Sqlite, Transaction};
+use std::collections::HashSet;
+use tauri::command;
+
+use super::search::get_or_create_pool;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockResult {
+    pub note_path: String,
+    pub block_id: String,
+    pub line: i32,
+    pub text: String,
+    pub is_duplicate: bool,
+}
+
+pub struct ParsedBlock {
+    pub block_id: String,
+    pub line: i32,
+    pub text: String,
+    pub is_duplicate: bool,
+}
+
+/// Finds `^blockid` markers at the end of lines, skipping fenced code blocks.
+/// Lines are zero-based within the indexed content.
+pub fn parse_blocks(content: &str) -> Vec<ParsedBlock> {
+    let mut blocks: Vec<ParsedBlock> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut in_fence = false;
+
+    for (line_index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_end();
+        if trimmed.trim_start().starts_with("```") || trimmed.trim_start().starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let Some(caret) = trimmed.rfind('^') else {
+            continue;
+        };
+        let id = &trimmed[caret + 1..];
+        let valid_id = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        let at_boundary = trimmed[..caret]
+            .chars()
+            .last()
+            .map(|c| c.is_whitespace())
+            .unwrap_or(true);
+        if !valid_id || !at_boundary {
+            continue;
+        }
+
+        let is_duplicate = !seen.insert(id.to_string());
+        if is_duplicate {
+            for block in blocks.iter_mut().filter(|b| b.block_id == id) {
+                block.is_duplicate = true;
+            }
+        }
+
+        blocks.push(ParsedBlock {
+            block_id: id.to_string(),
+            line: line_index as i32,
+            text: trimmed[..caret].trim_end().to_string(),
+            is_duplicate,
+        });
+    }
+
+    blocks
+}
+
+pub async fn index_blocks(
+    tx: &mut Transaction<'_, Sqlite>,
+    note_path: &str,
+    content: &str,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM note_blocks WHERE note_path = ?")
+        .bind(note_path)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to clear blocks: {}", e))?;
+
+    for block in parse_blocks(content) {
+        sqlx::query(r#"
+            INSERT INTO note_blocks (note_path, block_id, line, text, is_duplicate)
+            VALUES (?, ?, ?, ?, ?)
+        "#)
+        .bind(note_path)
+        .bind(&block.block_id)
+        .bind(block.line)
+        .bind(&block.text)
+        .bind(block.is_duplicate as i32)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to index block: {}", e))?;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub async fn markdown_resolve_block(
+    index_path: String,
+    note_path: String,
+    block_id: String,
+) -> Result<Option<BlockResult>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let row = sqlx::query(r#"
+        SELECT note_path, block_id, line, text, is_duplicate
+        FROM note_blocks
+        WHERE note_path = ? AND block_id = ?
+        ORDER BY line ASC
+        LIMIT 1
+    "#)
+    .bind(&note_path)
+    .bind(&block_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| format!("Failed to resolve block: {}", e))?;
+
+    Ok(row.map(|row| BlockResult {
+        note_path: row.get::<String, _>("note_path"),
+        block_id: row.get::<String, _>("block_id"),
+        line: row.get::<i32, _>("line"),
+        text: row.get::<String, _>("text"),
+        is_duplicate: row.get::<i32, _>("is_duplicate") != 0,
+    }))
+}
@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::io::Write;
+use tauri::command;
+
+use super::attachments::{relative_to, AttachmentResolver};
+use super::embeds::is_attachment_target;
+use super::links::{find_markdown_links, find_wikilinks, is_external_url, percent_decode, LinkResolver};
+use super::search::get_or_create_pool;
+use super::vault::resolve_in_vault;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExportBundleOptions {
+    /// Where to write the `.zip`, as an absolute path.
+    pub output_path: String,
+    /// Also bundle notes reached through ordinary links, not just embeds.
+    #[serde(default)]
+    pub include_linked: bool,
+    /// How many link hops to follow when `include_linked` is set. Defaults to 1.
+    pub link_depth: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MissingDependency {
+    pub source_path: String,
+    pub target: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportBundleResult {
+    pub zip_path: String,
+    pub notes: Vec<String>,
+    pub attachments: Vec<String>,
+    pub missing: Vec<MissingDependency>,
+}
+
+fn encode_link_path(path: &str) -> String {
+    path.replace('%', "%25").replace(' ', "%20")
+}
+
+struct Dependency {
+    path: String,
+    is_note: bool,
+    /// Embedded notes are always bundled; linked ones only within the depth.
+    is_embed: bool,
+}
+
+struct BundleContext<'a> {
+    resolver: &'a LinkResolver,
+    attachments: &'a AttachmentResolver,
+    missing: Vec<MissingDependency>,
+}
+
+impl BundleContext<'_> {
+    fn miss(&mut self, source_path: &str, target: &str, reason: &str) {
+        self.missing.push(MissingDependency {
+            source_path: source_path.to_string(),
+            target: target.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    fn resolve(&mut self, source_path: &str, target: &str) -> Option<(String, bool)> {
+        if is_attachment_target(target) {
+            match self.attachments.resolve(source_path, target) {
+                Some(path) => Some((path, false)),
+                None => {
+                    self.miss(source_path, target, "Attachment not found");
+                    None
+                }
+            }
+        } else {
+            match self.resolver.resolve(source_path, target) {
+                Some(path) => Some((path, true)),
+                None => {
+                    self.miss(source_path, target, "Note not found");
+                    None
+                }
+            }
+        }
+    }
+
+    /// Rewrites a note's links to point at bundle-relative paths and returns
+    /// the dependencies it references.
+    fn rewrite(&mut self, source_path: &str, raw: &str) -> (String, Vec<Dependency>) {
+        let mut dependencies = Vec::new();
+        let mut output = Vec::new();
+        let mut in_fence = false;
+
+        for line in raw.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            if in_fence {
+                output.push(line.to_string());
+                continue;
+            }
+
+            // Replacements as (start, end, text), applied left to right.
+            let mut edits: Vec<(usize, usize, String)> = Vec::new();
+
+            for link in find_wikilinks(line) {
+                if link.target.is_empty() {
+                    continue;
+                }
+                let Some((path, is_note)) = self.resolve(source_path, &link.target) else {
+                    continue;
+                };
+                let relative = relative_to(source_path, &path);
+                let target = match relative.strip_suffix(".md") {
+                    Some(stem) if is_note => stem.to_string(),
+                    _ => relative,
+                };
+
+                let mut text = String::new();
+                if link.is_embed {
+                    text.push('!');
+                }
+                text.push_str("[[");
+                text.push_str(&target);
+                if let Some(heading) = &link.heading {
+                    text.push('#');
+                    text.push_str(heading);
+                } else if let Some(block) = &link.block_ref {
+                    text.push_str("#^");
+                    text.push_str(block);
+                }
+                // Keep the visible text stable when the target was rewritten.
+                let display = link.display_text.clone().or_else(|| {
+                    (!link.is_embed && target != link.target).then(|| link.target.clone())
+                });
+                if let Some(display) = display {
+                    text.push('|');
+                    text.push_str(&display);
+                }
+                text.push_str("]]");
+
+                edits.push((link.start, link.end, text));
+                dependencies.push(Dependency { path, is_note, is_embed: link.is_embed });
+            }
+
+            for link in find_markdown_links(line) {
+                if is_external_url(&link.target) || link.target.starts_with('#') || link.target.is_empty() {
+                    continue;
+                }
+                if edits.iter().any(|(start, end, _)| link.start < *end && *start < link.end) {
+                    continue;
+                }
+                let (target, anchor) = match link.target.split_once('#') {
+                    Some((target, anchor)) => (target, Some(anchor)),
+                    None => (link.target.as_str(), None),
+                };
+                let decoded = percent_decode(target);
+                let Some((path, is_note)) = self.resolve(source_path, &decoded) else {
+                    continue;
+                };
+
+                let mut href = encode_link_path(&relative_to(source_path, &path));
+                if let Some(anchor) = anchor {
+                    href.push('#');
+                    href.push_str(anchor);
+                }
+                let text = format!("{}[{}]({})", if link.is_image { "!" } else { "" }, link.text, href);
+
+                edits.push((link.start, link.end, text));
+                dependencies.push(Dependency { path, is_note, is_embed: link.is_image });
+            }
+
+            edits.sort_by_key(|(start, _, _)| *start);
+            let mut rendered = String::new();
+            let mut cursor = 0;
+            for (start, end, text) in edits {
+                rendered.push_str(&line[cursor..start]);
+                rendered.push_str(&text);
+                cursor = end;
+            }
+            rendered.push_str(&line[cursor..]);
+            output.push(rendered);
+        }
+
+        let mut content = output.join("\n");
+        if raw.ends_with('\n') {
+            content.push('\n');
+        }
+        (content, dependencies)
+    }
+}
+
+/// Zips a note with its embedded notes and attachments, and optionally the
+/// notes it links to up to `link_depth` hops. Files keep their vault-relative
+/// layout and links are rewritten relative to each note, so the bundle opens
+/// as a small vault of its own.
+#[command]
+pub async fn markdown_export_bundle(
+    vault_path: String,
+    index_path: String,
+    rel_path: String,
+    options: ExportBundleOptions,
+) -> Result<ExportBundleResult, String> {
+    if options.output_path.is_empty() {
+        return Err("No output path given for the bundle".to_string());
+    }
+
+    let pool = get_or_create_pool(&index_path).await?;
+    let resolver = LinkResolver::load(&pool).await?;
+    let attachments = AttachmentResolver::load(&vault_path);
+    let link_depth = if options.include_linked { options.link_depth.unwrap_or(1) } else { 0 };
+
+    let mut context = BundleContext {
+        resolver: &resolver,
+        attachments: &attachments,
+        missing: Vec::new(),
+    };
+
+    let mut notes: Vec<(String, String)> = Vec::new();
+    let mut attachment_paths: BTreeSet<String> = BTreeSet::new();
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut queue: VecDeque<(String, u32)> = VecDeque::new();
+    queue.push_back((rel_path.clone(), 0));
+    visited.insert(rel_path.clone());
+
+    while let Some((path, depth)) = queue.pop_front() {
+        let raw = match resolve_in_vault(&vault_path, &path)
+            .and_then(|p| std::fs::read_to_string(p).map_err(|e| format!("Failed to read note: {}", e)))
+        {
+            Ok(raw) => raw,
+            Err(e) if path == rel_path => return Err(e),
+            Err(e) => {
+                context.miss(&path, &path, &e);
+                continue;
+            }
+        };
+
+        let (content, dependencies) = context.rewrite(&path, &raw);
+        notes.push((path.clone(), content));
+
+        for dependency in dependencies {
+            if !dependency.is_note {
+                attachment_paths.insert(dependency.path);
+                continue;
+            }
+            if visited.contains(&dependency.path) {
+                continue;
+            }
+            if dependency.is_embed {
+                // Embeds are needed to render the note, whatever the depth.
+                visited.insert(dependency.path.clone());
+                queue.push_back((dependency.path, depth));
+            } else if depth < link_depth {
+                visited.insert(dependency.path.clone());
+                queue.push_back((dependency.path, depth + 1));
+            } else if options.include_linked {
+                context.miss(&path, &dependency.path, "Beyond link depth");
+            }
+        }
+    }
+
+    let file = std::fs::File::create(&options.output_path)
+        .map_err(|e| format!("Failed to create bundle: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let file_options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for (path, content) in &notes {
+        zip.start_file(path.as_str(), file_options)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", path, e))?;
+        zip.write_all(content.as_bytes())
+            .map_err(|e| format!("Failed to add {} to bundle: {}", path, e))?;
+    }
+
+    let mut bundled_attachments = Vec::new();
+    for path in attachment_paths {
+        let bytes = match resolve_in_vault(&vault_path, &path).and_then(|p| std::fs::read(p).map_err(|e| e.to_string())) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                context.miss(&rel_path, &path, &format!("Failed to read attachment: {}", e));
+                continue;
+            }
+        };
+        zip.start_file(path.as_str(), file_options)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", path, e))?;
+        zip.write_all(&bytes)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", path, e))?;
+        bundled_attachments.push(path);
+    }
+
+    zip.finish().map_err(|e| format!("Failed to write bundle: {}", e))?;
+
+    Ok(ExportBundleResult {
+        zip_path: options.output_path,
+        notes: notes.into_iter().map(|(path, _)| path).collect(),
+        attachments: bundled_attachments,
+        missing: context.missing,
+    })
+}
@@ -0,0 +1,285 @@
+use sqlx::{Pool, Row, Sqlite, Transaction};
+use tauri::command;
+
+use super::search::get_or_create_pool;
+
+/// Characters either side of the match in `markdown_search_cjk` snippets.
+const SNIPPET_CONTEXT_CHARS: usize = 32;
+
+/// Han, kana and Hangul: scripts written without spaces between words,
+/// which `unicode61` would otherwise index as one token per run.
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF     // Hiragana, Katakana
+        | 0x31F0..=0x31FF   // Katakana phonetic extensions
+        | 0x3400..=0x4DBF   // CJK extension A
+        | 0x4E00..=0x9FFF   // CJK unified ideographs
+        | 0xAC00..=0xD7AF   // Hangul syllables
+        | 0xF900..=0xFAFF   // CJK compatibility ideographs
+        | 0xFF66..=0xFF9F   // Half-width katakana
+        | 0x20000..=0x2FA1F // CJK extensions B onwards
+    )
+}
+
+pub fn has_cjk(text: &str) -> bool {
+    text.chars().any(is_cjk)
+}
+
+/// Rewrites each run of CJK characters as space-separated overlapping
+/// bigrams (`東京都` becomes `東京 京都`), leaving other text as it is, so
+/// `unicode61` can match words inside a run.
+pub fn bigram_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut run: Vec<char> = Vec::new();
+
+    fn flush(run: &mut Vec<char>, out: &mut String) {
+        match run.len() {
+            0 => {}
+            1 => out.push(run[0]),
+            _ => {
+                let bigrams: Vec<String> = run.windows(2).map(|pair| pair.iter().collect()).collect();
+                out.push_str(&bigrams.join(" "));
+            }
+        }
+        if !run.is_empty() {
+            out.push(' ');
+        }
+        run.clear();
+    }
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            run.push(c);
+        } else {
+            flush(&mut run, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut run, &mut out);
+    out
+}
+
+/// FTS5 query over the bigram table: a CJK term becomes the phrase of its
+/// bigrams, a single CJK character a prefix match on them, and other terms
+/// prefix matches as in `markdown_search_notes`. Terms are OR-ed.
+pub fn cjk_fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| {
+            if !has_cjk(&term) {
+                return format!("\"{}\"*", term);
+            }
+            let tokens = bigram_text(&term);
+            let tokens = tokens.split_whitespace().collect::<Vec<_>>();
+            if tokens.len() == 1 && tokens[0].chars().count() == 1 {
+                format!("\"{}\"*", tokens[0])
+            } else {
+                format!("\"{}\"", tokens.join(" "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+/// Mirrors a note into `notes_fts_cjk` when its title or content contains
+/// CJK text, and drops it from there otherwise. Rows share the note's
+/// rowid, so the delete trigger on `notes` keeps the table in step.
+pub async fn index_cjk(tx: &mut Transaction<'_, Sqlite>, note_path: &str, title: &str, content: &str) -> Result<(), String> {
+    let Some(rowid) = sqlx::query_scalar::<_, i64>("SELECT rowid FROM notes WHERE path = ?")
+        .bind(note_path)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to look up note: {}", e))?
+    else {
+        return Ok(());
+    };
+
+    sqlx::query("DELETE FROM notes_fts_cjk WHERE rowid = ?")
+        .bind(rowid)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to clear CJK index: {}", e))?;
+
+    if has_cjk(title) || has_cjk(content) {
+        sqlx::query("INSERT INTO notes_fts_cjk (rowid, title, content) VALUES (?, ?, ?)")
+            .bind(rowid)
+            .bind(bigram_text(title))
+            .bind(bigram_text(content))
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| format!("Failed to index CJK text: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Fills `notes_fts_cjk` for indexes created before it existed.
+pub(crate) async fn backfill_cjk(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let rows = sqlx::query("SELECT path, title, content FROM notes")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    for row in &rows {
+        let title = row.get::<String, _>("title");
+        let content = row.get::<Option<String>, _>("content").unwrap_or_default();
+        if has_cjk(&title) || has_cjk(&content) {
+            index_cjk(&mut tx, &row.get::<String, _>("path"), &title, &content).await?;
+        }
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit CJK index: {}", e))
+}
+
+/// A snippet around the first query term found in `content`, with the
+/// match wrapped in `<mark>` like FTS5's `snippet()`.
+fn snippet(content: &str, query: &str) -> String {
+    let lower = content.to_lowercase();
+    let found = query
+        .split_whitespace()
+        .filter_map(|term| {
+            let term = term.to_lowercase();
+            // Offsets into `lower` only line up with `content` when
+            // lowercasing kept every byte length.
+            lower.find(&term).filter(|_| lower.len() == content.len()).map(|start| (start, term.len()))
+        })
+        .min_by_key(|(start, _)| *start);
+
+    let Some((start, len)) = found else {
+        return content.chars().take(SNIPPET_CONTEXT_CHARS * 2).collect();
+    };
+    let before: String = content[..start]
+        .chars()
+        .rev()
+        .take(SNIPPET_CONTEXT_CHARS)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let after: String = content[start + len..].chars().take(SNIPPET_CONTEXT_CHARS).collect();
+    format!(
+        "{}{}<mark>{}</mark>{}{}",
+        if before.len() < start { "..." } else { "" },
+        before,
+        &content[start..start + len],
+        after,
+        if start + len + after.len() < content.len() { "..." } else { "" },
+    )
+}
+
+/// Searches notes containing CJK text through the bigram index. Results
+/// have the same shape as `markdown_search_notes`, which routes here when
+/// the query contains CJK characters.
+pub(crate) async fn search_cjk(
+    pool: &Pool<Sqlite>,
+    query: &str,
+    title_weight: f64,
+    content_weight: f64,
+    limit: i32,
+) -> Result<Vec<serde_json::Value>, String> {
+    let fts_query = cjk_fts_query(query);
+    if fts_query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let rows = sqlx::query(r#"
+        SELECT n.id, n.path, n.title, n.content, -bm25(notes_fts_cjk, ?, ?) as score
+        FROM notes_fts_cjk
+        JOIN notes n ON notes_fts_cjk.rowid = n.rowid
+        WHERE notes_fts_cjk MATCH ?
+        ORDER BY score DESC
+        LIMIT ?
+    "#)
+    .bind(title_weight)
+    .bind(content_weight)
+    .bind(&fts_query)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Search failed: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let content = row.get::<String, _>("content");
+            serde_json::json!({
+                "id": row.get::<String, _>("id"),
+                "path": row.get::<String, _>("path"),
+                "title": row.get::<String, _>("title"),
+                "score": row.get::<f64, _>("score"),
+                "snippet": snippet(&content, query),
+            })
+        })
+        .collect())
+}
+
+#[command]
+pub async fn markdown_search_cjk(
+    index_path: String,
+    query: String,
+    limit: Option<i32>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    search_cjk(&pool, &query, 10.0, 1.0, limit.unwrap_or(50)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::search::{markdown_index_note, markdown_init_index, markdown_search_notes};
+
+    #[test]
+    fn bigrams_cjk_runs_only() {
+        assert_eq!(bigram_text("東京都"), "東京 京都 ");
+        assert_eq!(bigram_text("visit 東京 today"), "visit 東京  today");
+        assert_eq!(bigram_text("京"), "京 ");
+        assert_eq!(bigram_text("plain text"), "plain text");
+    }
+
+    #[test]
+    fn cjk_query_terms() {
+        assert_eq!(cjk_fts_query("京都"), "\"京都\"");
+        assert_eq!(cjk_fts_query("東京都"), "\"東京 京都\"");
+        assert_eq!(cjk_fts_query("京"), "\"京\"*");
+        assert_eq!(cjk_fts_query("京都 trip\""), "\"京都\" OR \"trip\"*");
+        assert_eq!(cjk_fts_query("  "), "");
+    }
+
+    #[tokio::test]
+    async fn finds_japanese_substring_the_default_index_misses() {
+        let dir = tempfile::tempdir().unwrap();
+        let index_path = dir.path().join("index.db").to_string_lossy().to_string();
+        markdown_init_index(index_path.clone(), None).await.unwrap();
+        markdown_index_note(
+            index_path.clone(),
+            "trip.md".to_string(),
+            "旅行".to_string(),
+            "昨日は東京都に行きました".to_string(),
+            "{}".to_string(),
+            "[]".to_string(),
+            "[]".to_string(),
+            1,
+            "x".to_string(),
+        )
+        .await
+        .unwrap();
+
+        let pool = get_or_create_pool(&index_path).await.unwrap();
+        let plain: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notes_fts WHERE notes_fts MATCH ?")
+            .bind("\"京都\"*")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(plain, 0);
+
+        let results = markdown_search_notes(index_path, "京都".to_string(), None, None, None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["path"], "trip.md");
+        assert!(results[0]["snippet"].as_str().unwrap().contains("<mark>京都</mark>"));
+    }
+}
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use super::attachments::{relative_to, AttachmentResolver};
+use super::embeds::is_attachment_target;
+use super::headings::heading_anchor;
+use super::links::{find_wikilinks, percent_encode_path, LinkResolver, WikiLink};
+use super::search::get_or_create_pool;
+
+/// What to do with a wikilink that doesn't resolve to a note or attachment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnresolvedLinkMode {
+    /// Leave the `[[wikilink]]` untouched.
+    #[default]
+    Keep,
+    /// Replace it with its display text.
+    Plain,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConvertWikilinksOptions {
+    /// Note the content belongs to; links are made relative to its folder.
+    /// Without one they are relative to the vault root.
+    pub source_path: Option<String>,
+    /// Percent-encode link paths. Otherwise paths with spaces or parentheses
+    /// are wrapped in `<...>`, which CommonMark accepts.
+    #[serde(default)]
+    pub url_encode: bool,
+    /// Keep the `.md` extension on links to notes.
+    #[serde(default)]
+    pub add_extension: bool,
+    #[serde(default)]
+    pub unresolved: UnresolvedLinkMode,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConvertWikilinksResult {
+    pub content: String,
+    pub converted: usize,
+    /// Targets that didn't resolve, in order of appearance.
+    pub unresolved: Vec<String>,
+}
+
+fn link_label(link: &WikiLink) -> String {
+    link.display_text.clone().unwrap_or_else(|| match &link.heading {
+        Some(heading) if link.target.is_empty() => heading.clone(),
+        Some(heading) => format!("{} > {}", link.target, heading),
+        None => link.target.clone(),
+    })
+}
+
+fn format_destination(path: &str, url_encode: bool) -> String {
+    if url_encode {
+        percent_encode_path(path)
+    } else if path.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", path)
+    } else {
+        path.to_string()
+    }
+}
+
+/// Rewrites `[[wikilinks]]` in `content` as `[text](path)` links, with
+/// paths relative to the source note.
+pub fn convert_wikilinks(
+    content: &str,
+    resolver: &LinkResolver,
+    attachments: &AttachmentResolver,
+    options: &ConvertWikilinksOptions,
+) -> ConvertWikilinksResult {
+    let source_path = options.source_path.as_deref().unwrap_or("");
+    convert_wikilinks_with(content, resolver, attachments, options, |path, is_note| {
+        let destination = relative_to(source_path, path);
+        match destination.strip_suffix(".md") {
+            Some(stem) if is_note && !options.add_extension => Some(stem.to_string()),
+            _ => Some(destination),
+        }
+    })
+}
+
+/// Like `convert_wikilinks`, but `destination` maps each resolved vault path
+/// (and whether it is a note) to the link destination. Returning `None`
+/// treats the link as unresolved.
+pub fn convert_wikilinks_with(
+    content: &str,
+    resolver: &LinkResolver,
+    attachments: &AttachmentResolver,
+    options: &ConvertWikilinksOptions,
+    mut destination: impl FnMut(&str, bool) -> Option<String>,
+) -> ConvertWikilinksResult {
+    let source_path = options.source_path.as_deref().unwrap_or("");
+    let mut converted = 0;
+    let mut unresolved = Vec::new();
+    let mut output = Vec::new();
+    let mut in_fence = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            output.push(line.to_string());
+            continue;
+        }
+
+        let mut rendered = String::new();
+        let mut cursor = 0;
+        for link in find_wikilinks(line) {
+            rendered.push_str(&line[cursor..link.start]);
+            cursor = link.end;
+
+            let label = link_label(&link);
+            let anchor = link.heading.as_deref().map(heading_anchor);
+
+            // `[[#Heading]]` points into the same note.
+            if link.target.is_empty() {
+                match &anchor {
+                    Some(anchor) => {
+                        rendered.push_str(&format!("[{}](#{})", label, anchor));
+                        converted += 1;
+                    }
+                    None => rendered.push_str(&line[link.start..link.end]),
+                }
+                continue;
+            }
+
+            let resolved = if is_attachment_target(&link.target) {
+                attachments.resolve(source_path, &link.target).map(|path| (path, false))
+            } else {
+                resolver.resolve(source_path, &link.target).map(|path| (path, true))
+            };
+
+            let mapped = resolved.and_then(|(path, is_note)| destination(&path, is_note).map(|d| (d, is_note)));
+            let Some((mapped, is_note)) = mapped else {
+                unresolved.push(link.target.clone());
+                match options.unresolved {
+                    UnresolvedLinkMode::Keep => rendered.push_str(&line[link.start..link.end]),
+                    UnresolvedLinkMode::Plain => rendered.push_str(&label),
+                }
+                continue;
+            };
+
+            let mut mapped = format_destination(&mapped, options.url_encode);
+            if let (Some(anchor), true) = (&anchor, is_note) {
+                mapped = match mapped.strip_suffix('>') {
+                    Some(inner) => format!("{}#{}>", inner, anchor),
+                    None => format!("{}#{}", mapped, anchor),
+                };
+            }
+
+            // Embedded attachments become images; embedded notes become links,
+            // since plain markdown has no transclusion.
+            let bang = if link.is_embed && !is_note { "!" } else { "" };
+            rendered.push_str(&format!("{}[{}]({})", bang, label, mapped));
+            converted += 1;
+        }
+        rendered.push_str(&line[cursor..]);
+        output.push(rendered);
+    }
+
+    let mut content_out = output.join("\n");
+    if content.ends_with('\n') {
+        content_out.push('\n');
+    }
+
+    ConvertWikilinksResult {
+        content: content_out,
+        converted,
+        unresolved,
+    }
+}
+
+/// Converts wikilinks to standard markdown links, for export to renderers
+/// that don't understand `[[...]]`, or to convert a note in place.
+#[command]
+pub async fn markdown_convert_wikilinks(
+    content: String,
+    vault_path: String,
+    index_path: String,
+    options: Option<ConvertWikilinksOptions>,
+) -> Result<ConvertWikilinksResult, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let resolver = LinkResolver::load(&pool).await?;
+    let attachments = AttachmentResolver::load(&vault_path);
+
+    Ok(convert_wikilinks(&content, &resolver, &attachments, &options.unwrap_or_default()))
+}
@@ -0,0 +1,281 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::BTreeSet;
+use std::time::SystemTime;
+use tauri::command;
+
+use super::frontmatter::replace_frontmatter_value;
+use super::indexer::reindex_file_with;
+use super::links::LinkResolver;
+use super::search::get_or_create_pool;
+use super::vault::{resolve_in_vault, write_atomic};
+
+const DEFAULT_STALE_THRESHOLD_SECS: i64 = 60;
+
+/// A frontmatter `updated` value, which may be a full timestamp or just a date.
+struct DeclaredDate {
+    time: DateTime<Utc>,
+    date_only: bool,
+}
+
+/// Accepts RFC3339, `YYYY-MM-DD HH:MM[:SS]` in local time, or `YYYY-MM-DD`.
+fn parse_declared_date(value: &str) -> Option<DeclaredDate> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(DeclaredDate { time: time.with_timezone(&Utc), date_only: false });
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, format) {
+            let time = Local.from_local_datetime(&naive).earliest()?;
+            return Some(DeclaredDate { time: time.with_timezone(&Utc), date_only: false });
+        }
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    let time = Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?;
+    Some(DeclaredDate { time: time.with_timezone(&Utc), date_only: true })
+}
+
+/// Whether `declared` disagrees with `actual` by more than the threshold. A
+/// date-only value agrees with any time on that local day.
+fn is_stale(declared: &DeclaredDate, actual: DateTime<Utc>, threshold_secs: i64) -> bool {
+    if declared.date_only {
+        return actual.with_timezone(&Local).date_naive() != declared.time.with_timezone(&Local).date_naive();
+    }
+    (actual - declared.time).num_seconds().abs() > threshold_secs
+}
+
+fn file_modified(vault_path: &str, rel_path: &str) -> Result<DateTime<Utc>, String> {
+    let full_path = resolve_in_vault(vault_path, rel_path)?;
+    std::fs::metadata(&full_path)
+        .and_then(|metadata| metadata.modified())
+        .map(DateTime::<Utc>::from)
+        .map_err(|e| format!("Failed to read modification time: {}", e))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StaleFrontmatter {
+    pub path: String,
+    /// The frontmatter `updated` value as written.
+    pub declared: String,
+    /// File modification time, or the index's `updated` without a vault path.
+    pub actual: String,
+    /// `actual` minus `declared`; `None` when `declared` couldn't be parsed.
+    pub drift_seconds: Option<i64>,
+}
+
+async fn find_stale(
+    index_path: &str,
+    vault_path: Option<&str>,
+    threshold_secs: i64,
+) -> Result<Vec<StaleFrontmatter>, String> {
+    let pool = get_or_create_pool(index_path).await?;
+
+    let rows = sqlx::query("SELECT path, frontmatter, updated FROM notes ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+
+    let mut stale = Vec::new();
+    for row in &rows {
+        let path = row.get::<String, _>("path");
+        let frontmatter: serde_json::Value = row
+            .get::<Option<String>, _>("frontmatter")
+            .and_then(|f| serde_json::from_str(&f).ok())
+            .unwrap_or_default();
+        let Some(declared) = frontmatter.get("updated").and_then(|v| v.as_str()) else {
+            continue;
+        };
+
+        let actual = match vault_path {
+            Some(vault_path) => match file_modified(vault_path, &path) {
+                Ok(time) => time,
+                Err(e) => {
+                    log::warn!("Skipping {}: {}", path, e);
+                    continue;
+                }
+            },
+            None => match DateTime::parse_from_rfc3339(&row.get::<String, _>("updated")) {
+                Ok(time) => time.with_timezone(&Utc),
+                Err(_) => continue,
+            },
+        };
+
+        let drift_seconds = match parse_declared_date(declared) {
+            Some(parsed) if !is_stale(&parsed, actual, threshold_secs) => continue,
+            Some(parsed) => Some((actual - parsed.time).num_seconds()),
+            None => None,
+        };
+
+        stale.push(StaleFrontmatter {
+            path,
+            declared: declared.to_string(),
+            actual: actual.to_rfc3339(),
+            drift_seconds,
+        });
+    }
+
+    Ok(stale)
+}
+
+/// Notes whose frontmatter `updated` disagrees with when they were actually
+/// changed by more than `threshold_seconds` (default 60). With `vault_path`
+/// the file's modification time is used, otherwise the time it was last
+/// indexed. Unparseable dates are always reported.
+#[command]
+pub async fn markdown_find_stale_frontmatter(
+    index_path: String,
+    vault_path: Option<String>,
+    threshold_seconds: Option<i64>,
+) -> Result<Vec<StaleFrontmatter>, String> {
+    let threshold = threshold_seconds.unwrap_or(DEFAULT_STALE_THRESHOLD_SECS).max(0);
+    find_stale(&index_path, vault_path.as_deref(), threshold).await
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncFrontmatterDatesResult {
+    pub updated: Vec<String>,
+    /// Notes that couldn't be rewritten, as `path: error`.
+    pub errors: Vec<String>,
+}
+
+/// Rewrites each stale frontmatter `updated` to the file's modification
+/// time, keeping date-only values date-only. The original modification time
+/// is restored afterwards so the rewrite doesn't make the note stale again.
+#[command]
+pub async fn markdown_sync_frontmatter_dates(
+    vault_path: String,
+    index_path: String,
+    threshold_seconds: Option<i64>,
+) -> Result<SyncFrontmatterDatesResult, String> {
+    let threshold = threshold_seconds.unwrap_or(DEFAULT_STALE_THRESHOLD_SECS).max(0);
+    let stale = find_stale(&index_path, Some(&vault_path), threshold).await?;
+    let pool = get_or_create_pool(&index_path).await?;
+    let mut resolver = LinkResolver::load(&pool).await?;
+
+    let mut result = SyncFrontmatterDatesResult::default();
+    for note in stale {
+        match sync_note_date(&vault_path, &note) {
+            Ok(()) => {
+                if let Err(e) = reindex_file_with(&pool, &vault_path, &note.path, &mut resolver).await {
+                    result.errors.push(format!("{}: {}", note.path, e));
+                } else {
+                    result.updated.push(note.path);
+                }
+            }
+            Err(e) => result.errors.push(format!("{}: {}", note.path, e)),
+        }
+    }
+
+    Ok(result)
+}
+
+fn sync_note_date(vault_path: &str, note: &StaleFrontmatter) -> Result<(), String> {
+    let full_path = resolve_in_vault(vault_path, &note.path)?;
+    let raw = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read note: {}", e))?;
+    let modified: SystemTime = std::fs::metadata(&full_path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| format!("Failed to read modification time: {}", e))?;
+
+    let local = DateTime::<Local>::from(modified);
+    let value = match parse_declared_date(&note.declared) {
+        Some(declared) if declared.date_only => local.format("%Y-%m-%d").to_string(),
+        _ => local.to_rfc3339_opts(SecondsFormat::Secs, false),
+    };
+
+    let updated = replace_frontmatter_value(&raw, "updated", &value)
+        .ok_or_else(|| "No updated field in frontmatter".to_string())?;
+
+    write_atomic(&full_path, updated)?;
+    std::fs::File::options()
+        .write(true)
+        .open(&full_path)
+        .and_then(|file| file.set_modified(modified))
+        .map_err(|e| format!("Failed to restore modification time: {}", e))
+}
+
+/// Which timestamp a writing streak counts.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreakField {
+    #[default]
+    Created,
+    Updated,
+}
+
+/// Consecutive local days, both ends included, as `YYYY-MM-DD`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayRange {
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WritingStreak {
+    /// Days in the run ending today, or yesterday while today has no note
+    /// yet; 0 once a day has been missed.
+    pub current: usize,
+    pub longest: usize,
+    pub current_range: Option<DayRange>,
+    /// The most recent run when several tie.
+    pub longest_range: Option<DayRange>,
+    /// Days with at least one note.
+    pub active_days: usize,
+}
+
+/// Streaks over a set of active days, relative to `today`.
+pub fn writing_streak(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> WritingStreak {
+    let range = |start: NaiveDate, end: NaiveDate| DayRange {
+        start: start.format("%Y-%m-%d").to_string(),
+        end: end.format("%Y-%m-%d").to_string(),
+    };
+
+    let mut runs: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for &day in days {
+        match runs.last_mut() {
+            Some((_, end)) if end.succ_opt() == Some(day) => *end = day,
+            _ => runs.push((day, day)),
+        }
+    }
+    let length = |(start, end): (NaiveDate, NaiveDate)| (end - start).num_days() as usize + 1;
+
+    // `max_by_key` keeps the last of equal runs, which is the latest.
+    let longest = runs.iter().copied().max_by_key(|&run| length(run));
+    let current = runs
+        .last()
+        .copied()
+        .filter(|&(_, end)| end == today || end.succ_opt() == Some(today));
+
+    WritingStreak {
+        current: current.map(length).unwrap_or(0),
+        longest: longest.map(length).unwrap_or(0),
+        current_range: current.map(|(start, end)| range(start, end)),
+        longest_range: longest.map(|(start, end)| range(start, end)),
+        active_days: days.len(),
+    }
+}
+
+/// Current and longest runs of consecutive days on which a note was
+/// created, or last updated with `field: "updated"`, bucketed by local
+/// day from the index's timestamps.
+#[command]
+pub async fn markdown_writing_streak(index_path: String, field: Option<StreakField>) -> Result<WritingStreak, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let sql = match field.unwrap_or_default() {
+        StreakField::Created => "SELECT created AS stamp FROM notes",
+        StreakField::Updated => "SELECT updated AS stamp FROM notes",
+    };
+    let rows = sqlx::query(sql)
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load note dates: {}", e))?;
+
+    let days = rows
+        .iter()
+        .filter_map(|row| row.get::<Option<String>, _>("stamp"))
+        .filter_map(|stamp| parse_declared_date(&stamp))
+        .map(|date| date.time.with_timezone(&Local).date_naive())
+        .collect();
+
+    Ok(writing_streak(&days, Local::now().date_naive()))
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use tauri::command;
+
+use super::indexer::normalize_line_endings;
+use super::vault::resolve_in_vault;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiffLine {
+    /// `equal`, `insert` or `delete`.
+    pub kind: String,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+    pub text: String,
+}
+
+/// A group of changes with surrounding context. Line numbers are zero-based.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteDiff {
+    pub disk_exists: bool,
+    pub identical: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// Line diff between two texts, ignoring CRLF/LF differences.
+pub fn diff_texts(old: &str, new: &str) -> Vec<DiffHunk> {
+    let old = normalize_line_endings(old);
+    let new = normalize_line_endings(new);
+    let diff = TextDiff::from_lines(&old, &new);
+
+    diff.grouped_ops(3)
+        .iter()
+        .map(|group| {
+            let old_start = group.first().map(|op| op.old_range().start).unwrap_or(0);
+            let old_end = group.last().map(|op| op.old_range().end).unwrap_or(0);
+            let new_start = group.first().map(|op| op.new_range().start).unwrap_or(0);
+            let new_end = group.last().map(|op| op.new_range().end).unwrap_or(0);
+
+            let lines = group
+                .iter()
+                .flat_map(|op| diff.iter_changes(op))
+                .map(|change| DiffLine {
+                    kind: match change.tag() {
+                        ChangeTag::Equal => "equal",
+                        ChangeTag::Insert => "insert",
+                        ChangeTag::Delete => "delete",
+                    }
+                    .to_string(),
+                    old_line: change.old_index(),
+                    new_line: change.new_index(),
+                    text: change.value().trim_end_matches('\n').to_string(),
+                })
+                .collect();
+
+            DiffHunk {
+                old_start,
+                old_len: old_end - old_start,
+                new_start,
+                new_len: new_end - new_start,
+                lines,
+            }
+        })
+        .collect()
+}
+
+/// Diffs the on-disk note (old side) against the editor buffer (new side).
+/// A note deleted on disk is treated as empty, so every buffer line shows as
+/// an insertion.
+#[command]
+pub async fn markdown_diff_note(vault_path: String, rel_path: String, buffer: String) -> Result<NoteDiff, String> {
+    let full_path = resolve_in_vault(&vault_path, &rel_path)?;
+
+    let (disk_exists, disk) = match std::fs::read_to_string(&full_path) {
+        Ok(content) => (true, content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (false, String::new()),
+        Err(e) => return Err(format!("Failed to read note: {}", e)),
+    };
+
+    let hunks = diff_texts(&disk, &buffer);
+    Ok(NoteDiff {
+        disk_exists,
+        identical: disk_exists && hunks.is_empty(),
+        hunks,
+    })
+}
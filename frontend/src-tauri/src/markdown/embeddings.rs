@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashMap;
+use tauri::command;
+
+use super::search::{get_or_create_pool, markdown_search_notes};
+
+/// `markdown_hybrid_search` blends this many times `limit` candidates from
+/// each side, and at least `HYBRID_MIN_CANDIDATES`.
+const HYBRID_CANDIDATE_FACTOR: usize = 4;
+const HYBRID_MIN_CANDIDATES: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SemanticMatch {
+    pub path: String,
+    pub title: String,
+    /// Cosine similarity, -1 to 1.
+    pub score: f32,
+    /// The note changed since its embedding was stored.
+    pub stale: bool,
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+fn norm(vector: &[f32]) -> f32 {
+    vector.iter().map(|v| v * v).sum::<f32>().sqrt()
+}
+
+fn validate(vector: &[f32]) -> Result<(), String> {
+    if vector.is_empty() {
+        return Err("Embedding is empty".to_string());
+    }
+    if vector.iter().any(|v| !v.is_finite()) {
+        return Err("Embedding contains NaN or infinite values".to_string());
+    }
+    if norm(vector) == 0.0 {
+        return Err("Embedding is all zeros".to_string());
+    }
+    Ok(())
+}
+
+/// Dimensions of the stored embeddings, ignoring `except_path`, or `None`
+/// when there are none yet.
+async fn stored_dimensions(pool: &sqlx::Pool<sqlx::Sqlite>, except_path: &str) -> Result<Option<i64>, String> {
+    sqlx::query_scalar("SELECT dimensions FROM note_embeddings WHERE note_path != ? LIMIT 1")
+        .bind(except_path)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to read embeddings: {}", e))
+}
+
+/// Stores the embedding for an indexed note, replacing any previous one.
+/// Every embedding in an index must have the same dimensions; the vectors
+/// come from the app's model, so they are stored as given.
+#[command]
+pub async fn markdown_store_embedding(index_path: String, path: String, vector: Vec<f32>) -> Result<(), String> {
+    validate(&vector)?;
+    let pool = get_or_create_pool(&index_path).await?;
+
+    if let Some(dimensions) = stored_dimensions(&pool, &path).await? {
+        if dimensions != vector.len() as i64 {
+            return Err(format!(
+                "Embedding has {} dimensions but the index uses {}",
+                vector.len(),
+                dimensions
+            ));
+        }
+    }
+
+    let checksum: Option<Option<String>> = sqlx::query_scalar("SELECT checksum FROM notes WHERE path = ?")
+        .bind(&path)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to look up note: {}", e))?;
+    let Some(checksum) = checksum else {
+        return Err(format!("Note not indexed: {}", path));
+    };
+
+    sqlx::query(r#"
+        INSERT INTO note_embeddings (note_path, dimensions, vector, checksum, updated)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(note_path) DO UPDATE SET
+            dimensions = excluded.dimensions,
+            vector = excluded.vector,
+            checksum = excluded.checksum,
+            updated = excluded.updated
+    "#)
+    .bind(&path)
+    .bind(vector.len() as i64)
+    .bind(encode_vector(&vector))
+    .bind(checksum)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to store embedding: {}", e))?;
+
+    Ok(())
+}
+
+/// Every embedded note scored against `query_vector`, best first.
+///
+/// This scans every stored embedding, which is fine for a personal vault;
+/// an approximate index (e.g. HNSW) would be the next step for large ones.
+pub(crate) async fn semantic_scores(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    query_vector: &[f32],
+) -> Result<Vec<SemanticMatch>, String> {
+    validate(query_vector)?;
+
+    let rows = sqlx::query(r#"
+        SELECT e.note_path, e.dimensions, e.vector, e.checksum IS n.checksum AS fresh, n.title
+        FROM note_embeddings e
+        JOIN notes n ON n.path = e.note_path
+    "#)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load embeddings: {}", e))?;
+
+    if let Some(row) = rows.first() {
+        let dimensions = row.get::<i64, _>("dimensions");
+        if dimensions != query_vector.len() as i64 {
+            return Err(format!(
+                "Query has {} dimensions but the index uses {}",
+                query_vector.len(),
+                dimensions
+            ));
+        }
+    }
+
+    let query_norm = norm(query_vector);
+    let mut matches: Vec<SemanticMatch> = rows
+        .iter()
+        .filter_map(|row| {
+            let vector = decode_vector(&row.get::<Vec<u8>, _>("vector"));
+            if vector.len() != query_vector.len() {
+                return None;
+            }
+            let dot: f32 = vector.iter().zip(query_vector).map(|(a, b)| a * b).sum();
+            Some(SemanticMatch {
+                path: row.get::<String, _>("note_path"),
+                title: row.get::<String, _>("title"),
+                score: dot / (norm(&vector) * query_norm),
+                stale: !row.get::<bool, _>("fresh"),
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    Ok(matches)
+}
+
+/// Notes nearest to `query_vector` by cosine similarity, best first.
+#[command]
+pub async fn markdown_semantic_search(
+    index_path: String,
+    query_vector: Vec<f32>,
+    limit: Option<usize>,
+) -> Result<Vec<SemanticMatch>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let mut matches = semantic_scores(&pool, &query_vector).await?;
+    matches.truncate(limit.unwrap_or(20));
+    Ok(matches)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HybridMatch {
+    pub path: String,
+    pub title: String,
+    /// The blended score, 0 to 1.
+    pub score: f64,
+    /// bm25, min-max normalised over the keyword hits; `None` when the note
+    /// didn't match the query text.
+    pub keyword_score: Option<f64>,
+    /// Cosine similarity, min-max normalised over the embedded notes;
+    /// `None` when the note has no embedding.
+    pub semantic_score: Option<f64>,
+    pub snippet: Option<String>,
+}
+
+/// Maps scores onto 0..1, best at 1. All-equal scores all become 1.
+fn normalise(scores: &[f64]) -> impl Fn(f64) -> f64 {
+    let min = scores.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    move |score| if max > min { (score - min) / (max - min) } else { 1.0 }
+}
+
+/// Keyword hits from `markdown_search_notes` blended with cosine similarity:
+/// `alpha * semantic + (1 - alpha) * keyword`, with `alpha` defaulting to
+/// 0.5. A note without an embedding is ranked on its keyword score alone,
+/// and one that only matches semantically gets no keyword share.
+#[command]
+pub async fn markdown_hybrid_search(
+    index_path: String,
+    query: String,
+    query_vector: Vec<f32>,
+    alpha: Option<f64>,
+    limit: Option<usize>,
+) -> Result<Vec<HybridMatch>, String> {
+    let alpha = alpha.unwrap_or(0.5).clamp(0.0, 1.0);
+    let limit = limit.unwrap_or(20);
+    let pool = get_or_create_pool(&index_path).await?;
+
+    // Rank a wider pool than requested so the blend can reorder it.
+    let candidates = (limit * HYBRID_CANDIDATE_FACTOR).max(HYBRID_MIN_CANDIDATES);
+    let keyword = if query.trim().is_empty() {
+        Vec::new()
+    } else {
+        markdown_search_notes(index_path.clone(), query, Some(candidates as i32), None, None).await?
+    };
+    let mut semantic = semantic_scores(&pool, &query_vector).await?;
+
+    let keyword_norm = normalise(&keyword.iter().filter_map(|hit| hit["score"].as_f64()).collect::<Vec<_>>());
+    let semantic_norm = normalise(&semantic.iter().map(|m| m.score as f64).collect::<Vec<_>>());
+
+    let mut merged: HashMap<String, HybridMatch> = HashMap::new();
+    for hit in &keyword {
+        let (Some(path), Some(score)) = (hit["path"].as_str(), hit["score"].as_f64()) else {
+            continue;
+        };
+        merged.insert(path.to_string(), HybridMatch {
+            path: path.to_string(),
+            title: hit["title"].as_str().unwrap_or_default().to_string(),
+            score: 0.0,
+            keyword_score: Some(keyword_norm(score)),
+            semantic_score: None,
+            snippet: hit["snippet"].as_str().map(|s| s.to_string()),
+        });
+    }
+    let semantic_scores: HashMap<String, f64> = semantic
+        .iter()
+        .map(|m| (m.path.clone(), semantic_norm(m.score as f64)))
+        .collect();
+    semantic.truncate(candidates);
+    for m in semantic {
+        merged.entry(m.path.clone()).or_insert(HybridMatch {
+            path: m.path,
+            title: m.title,
+            score: 0.0,
+            keyword_score: None,
+            semantic_score: None,
+            snippet: None,
+        });
+    }
+
+    let mut results: Vec<HybridMatch> = merged
+        .into_values()
+        .map(|mut hit| {
+            hit.semantic_score = semantic_scores.get(&hit.path).copied();
+            hit.score = match (hit.keyword_score, hit.semantic_score) {
+                (Some(k), Some(s)) => alpha * s + (1.0 - alpha) * k,
+                (Some(k), None) => k,
+                (None, Some(s)) => alpha * s,
+                (None, None) => 0.0,
+            };
+            hit
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    results.truncate(limit);
+    Ok(results)
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::command;
+
+use super::attachments::AttachmentResolver;
+use super::blocks::parse_blocks;
+use super::frontmatter::split_frontmatter;
+use super::headings::extract_section;
+use super::links::{find_wikilinks, LinkResolver, WikiLink};
+use super::render::{render_html, RenderOptions};
+use super::search::get_or_create_pool;
+use super::vault::resolve_in_vault;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnresolvedEmbed {
+    pub source_path: String,
+    pub target: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmbedRenderResult {
+    pub content: String,
+    pub html: String,
+    pub unresolved: Vec<UnresolvedEmbed>,
+}
+
+struct EmbedContext<'a> {
+    vault_path: &'a str,
+    resolver: &'a LinkResolver,
+    attachments: &'a AttachmentResolver,
+    attachment_paths: HashMap<String, String>,
+    max_depth: u32,
+    unresolved: Vec<UnresolvedEmbed>,
+}
+
+/// Embeds of non-markdown files (`![[diagram.png]]`) are left for the
+/// renderer, with their resolved paths; only note embeds are inlined.
+pub fn is_attachment_target(target: &str) -> bool {
+    let name = target.rsplit('/').next().unwrap_or(target);
+    match name.rsplit_once('.') {
+        Some((_, ext)) => !ext.eq_ignore_ascii_case("md") && !ext.contains(' '),
+        None => false,
+    }
+}
+
+impl EmbedContext<'_> {
+    fn fail(&mut self, source_path: &str, link: &WikiLink, reason: &str) {
+        self.unresolved.push(UnresolvedEmbed {
+            source_path: source_path.to_string(),
+            target: link.target.clone(),
+            reason: reason.to_string(),
+        });
+    }
+
+    fn resolve_attachment(&mut self, source_path: &str, link: &WikiLink) {
+        if self.attachment_paths.contains_key(&link.target) {
+            return;
+        }
+        match self.attachments.resolve(source_path, &link.target) {
+            Some(path) => {
+                self.attachment_paths.insert(link.target.clone(), path);
+            }
+            None => self.fail(source_path, link, "Attachment not found"),
+        }
+    }
+
+    fn embed_content(&mut self, source_path: &str, link: &WikiLink, stack: &mut Vec<String>, depth: u32) -> Option<String> {
+        let target_path = if link.target.is_empty() {
+            source_path.to_string()
+        } else {
+            match self.resolver.resolve(source_path, &link.target) {
+                Some(path) => path,
+                None => {
+                    self.fail(source_path, link, "Note not found");
+                    return None;
+                }
+            }
+        };
+
+        let anchor = link.heading.clone().or_else(|| link.block_ref.as_ref().map(|b| format!("^{}", b)));
+        let key = match &anchor {
+            Some(anchor) => format!("{}#{}", target_path, anchor),
+            None => target_path.clone(),
+        };
+        // A section may be embedded from a note that is already being expanded;
+        // only re-entering the same section, or the whole note, is a cycle.
+        let section_prefix = format!("{}#", target_path);
+        let whole_note_cycle = anchor.is_none()
+            && stack.iter().any(|k| k == &target_path || k.starts_with(&section_prefix));
+        if stack.contains(&key) || whole_note_cycle {
+            self.fail(source_path, link, "Embed cycle");
+            return None;
+        }
+        if depth >= self.max_depth {
+            self.fail(source_path, link, "Maximum embed depth reached");
+            return None;
+        }
+
+        let raw = match resolve_in_vault(self.vault_path, &target_path).and_then(|p| {
+            std::fs::read_to_string(p).map_err(|e| format!("Failed to read note: {}", e))
+        }) {
+            Ok(raw) => raw,
+            Err(e) => {
+                self.fail(source_path, link, &e);
+                return None;
+            }
+        };
+        let (_, body) = split_frontmatter(&raw);
+
+        let fragment = if let Some(heading) = &link.heading {
+            match extract_section(body, heading) {
+                Some(section) => section,
+                None => {
+                    self.fail(source_path, link, "Heading not found");
+                    return None;
+                }
+            }
+        } else if let Some(block_id) = &link.block_ref {
+            match parse_blocks(body).into_iter().find(|b| &b.block_id == block_id) {
+                Some(block) => block.text,
+                None => {
+                    self.fail(source_path, link, "Block not found");
+                    return None;
+                }
+            }
+        } else {
+            body.to_string()
+        };
+
+        stack.push(key);
+        let expanded = self.expand(&target_path, &fragment, stack, depth + 1);
+        stack.pop();
+        Some(expanded)
+    }
+
+    fn expand(&mut self, source_path: &str, body: &str, stack: &mut Vec<String>, depth: u32) -> String {
+        let mut output = Vec::new();
+        let mut in_fence = false;
+
+        for line in body.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            if in_fence {
+                output.push(line.to_string());
+                continue;
+            }
+
+            let mut rendered = String::new();
+            let mut cursor = 0;
+            for link in find_wikilinks(line) {
+                if !link.is_embed {
+                    continue;
+                }
+                if is_attachment_target(&link.target) {
+                    self.resolve_attachment(source_path, &link);
+                    continue;
+                }
+                rendered.push_str(&line[cursor..link.start]);
+                match self.embed_content(source_path, &link, stack, depth) {
+                    Some(content) => rendered.push_str(&content),
+                    None => rendered.push_str(&line[link.start..link.end]),
+                }
+                cursor = link.end;
+            }
+            rendered.push_str(&line[cursor..]);
+            output.push(rendered);
+        }
+
+        output.join("\n")
+    }
+}
+
+#[command]
+pub async fn markdown_render_with_embeds(
+    vault_path: String,
+    index_path: String,
+    rel_path: String,
+    max_depth: Option<u32>,
+) -> Result<EmbedRenderResult, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let resolver = LinkResolver::load(&pool).await?;
+    let attachments = AttachmentResolver::load(&vault_path);
+
+    let raw = std::fs::read_to_string(resolve_in_vault(&vault_path, &rel_path)?)
+        .map_err(|e| format!("Failed to read note: {}", e))?;
+    let (_, body) = split_frontmatter(&raw);
+
+    let mut context = EmbedContext {
+        vault_path: &vault_path,
+        resolver: &resolver,
+        attachments: &attachments,
+        attachment_paths: HashMap::new(),
+        max_depth: max_depth.unwrap_or(5),
+        unresolved: Vec::new(),
+    };
+    let mut stack = vec![rel_path.clone()];
+    let content = context.expand(&rel_path, body, &mut stack, 0);
+    let html = render_html(&content, &RenderOptions {
+        attachment_paths: context.attachment_paths,
+        ..RenderOptions::default()
+    });
+
+    Ok(EmbedRenderResult {
+        content,
+        html,
+        unresolved: context.unresolved,
+    })
+}
@@ -0,0 +1,151 @@
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use tauri::command;
+
+use super::indexer::reindex_file;
+use super::search::{get_or_create_pool, remove_note};
+use super::vault::{is_markdown_path, normalize_rel_path, resolve_in_vault, write_atomic};
+use super::versions::remove_versions;
+
+/// Appended to the note's file name, so `Secret.md` becomes `Secret.md.enc`.
+/// Scans only pick up `.md` files, so encrypted notes stay out of the index.
+pub const ENCRYPTED_EXTENSION: &str = ".enc";
+/// File header, authenticated along with the salt and nonce that follow it.
+const MAGIC: &[u8] = b"nenspace-enc-v1\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<chacha20poly1305::Key, String> {
+    let mut key = chacha20poly1305::Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+/// `MAGIC | salt | nonce | ciphertext`, with the key derived from the
+/// passphrase by Argon2id and sealed with XChaCha20-Poly1305.
+pub fn encrypt_bytes(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut out = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + plaintext.len() + 16);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad: &out })
+        .map_err(|_| "Failed to encrypt note".to_string())?;
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Reverses `encrypt_bytes`. A wrong passphrase and a tampered file fail
+/// the same way, since the tag can't tell them apart.
+pub fn decrypt_bytes(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let header_len = MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if data.len() < header_len || !data.starts_with(MAGIC) {
+        return Err("Not an encrypted note".to_string());
+    }
+    let (header, ciphertext) = data.split_at(header_len);
+    let salt = &header[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let nonce = XNonce::from_slice(&header[MAGIC.len() + SALT_LEN..]);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(nonce, Payload { msg: ciphertext, aad: header })
+        .map_err(|_| "Wrong passphrase or corrupted file".to_string())
+}
+
+/// Encrypts a note to `<rel_path>.enc` and deletes the plaintext, its stored
+/// versions and its index entry. The encrypted file is read back and
+/// decrypted before anything is deleted. Returns the encrypted file's path.
+#[command]
+pub async fn markdown_encrypt_note(
+    vault_path: String,
+    index_path: String,
+    rel_path: String,
+    passphrase: String,
+) -> Result<String, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase is empty".to_string());
+    }
+    let rel_path = normalize_rel_path(&rel_path)?;
+    if !is_markdown_path(&rel_path) {
+        return Err(format!("Not a markdown note: {}", rel_path));
+    }
+    let source = resolve_in_vault(&vault_path, &rel_path)?;
+    let encrypted_rel = format!("{}{}", rel_path, ENCRYPTED_EXTENSION);
+    let destination = resolve_in_vault(&vault_path, &encrypted_rel)?;
+    if destination.exists() {
+        return Err(format!("{} already exists", encrypted_rel));
+    }
+
+    let plaintext = std::fs::read(&source).map_err(|e| format!("Failed to read note: {}", e))?;
+    let encrypted = encrypt_bytes(&plaintext, &passphrase)?;
+    write_atomic(&destination, &encrypted)?;
+
+    let written = std::fs::read(&destination).map_err(|e| format!("Failed to verify encrypted note: {}", e))?;
+    if decrypt_bytes(&written, &passphrase).ok().as_deref() != Some(plaintext.as_slice()) {
+        let _ = std::fs::remove_file(&destination);
+        return Err("Encrypted note failed verification; the original was kept".to_string());
+    }
+
+    std::fs::remove_file(&source).map_err(|e| format!("Failed to remove plaintext note: {}", e))?;
+    if let Err(e) = remove_versions(&vault_path, &rel_path) {
+        log::warn!("Failed to remove versions of {}: {}", rel_path, e);
+    }
+    let pool = get_or_create_pool(&index_path).await?;
+    remove_note(&pool, &rel_path).await?;
+
+    Ok(encrypted_rel)
+}
+
+/// Decrypts `<note>.md.enc` back to `<note>.md`, removes the encrypted file
+/// and indexes the note again. A wrong passphrase leaves both untouched.
+/// Returns the note's path.
+#[command]
+pub async fn markdown_decrypt_note(
+    vault_path: String,
+    index_path: String,
+    rel_path: String,
+    passphrase: String,
+) -> Result<String, String> {
+    let encrypted_rel = normalize_rel_path(&rel_path)?;
+    let note_rel = match encrypted_rel.strip_suffix(ENCRYPTED_EXTENSION) {
+        Some(note) if is_markdown_path(note) => note.to_string(),
+        _ => return Err(format!("Not an encrypted note: {}", encrypted_rel)),
+    };
+    let source = resolve_in_vault(&vault_path, &encrypted_rel)?;
+    let destination = resolve_in_vault(&vault_path, &note_rel)?;
+    if destination.exists() {
+        return Err(format!("{} already exists", note_rel));
+    }
+
+    let data = std::fs::read(&source).map_err(|e| format!("Failed to read encrypted note: {}", e))?;
+    let plaintext = decrypt_bytes(&data, &passphrase)?;
+    write_atomic(&destination, &plaintext)?;
+    std::fs::remove_file(&source).map_err(|e| format!("Failed to remove encrypted note: {}", e))?;
+
+    let pool = get_or_create_pool(&index_path).await?;
+    reindex_file(&pool, &vault_path, &note_rel).await?;
+
+    Ok(note_rel)
+}
+
+/// Decrypts a note for viewing without writing the plaintext to disk.
+#[command]
+pub async fn markdown_read_encrypted_note(
+    vault_path: String,
+    rel_path: String,
+    passphrase: String,
+) -> Result<String, String> {
+    let source = resolve_in_vault(&vault_path, &rel_path)?;
+    let data = std::fs::read(&source).map_err(|e| format!("Failed to read encrypted note: {}", e))?;
+    String::from_utf8(decrypt_bytes(&data, &passphrase)?).map_err(|_| "Decrypted note is not valid UTF-8".to_string())
+}
@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+use tauri::command;
+use tokio::process::Command;
+
+use super::vault::resolve_in_vault;
+
+fn existing_vault_path(vault_path: &str, rel_path: &str) -> Result<PathBuf, String> {
+    let full_path = resolve_in_vault(vault_path, rel_path)?;
+    if !full_path.exists() {
+        return Err(format!("Path not found: {}", rel_path));
+    }
+    Ok(full_path)
+}
+
+#[cfg(target_os = "macos")]
+async fn run_opener(mut command: Command, path: &Path) -> Result<(), String> {
+    let status = command
+        .status()
+        .await
+        .map_err(|e| format!("Failed to launch system opener: {}", e))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("No application is available to open {}", path.display()))
+    }
+}
+
+/// Opens `path` with its default application. This goes through the OS
+/// (`ShellExecuteW` on Windows) rather than a shell, so names like `R&D.md`
+/// can't be read as commands, and a missing file association is an error.
+async fn open_with_default_app(path: &Path) -> Result<(), String> {
+    let target = path.to_path_buf();
+    tokio::task::spawn_blocking(move || tauri_plugin_opener::open_path(&target, None::<&str>))
+        .await
+        .map_err(|e| format!("Failed to launch system opener: {}", e))?
+        .map_err(|e| format!("No application is available to open {}: {}", path.display(), e))
+}
+
+/// Opens a note or attachment with the OS default application.
+#[command]
+pub async fn markdown_open_externally(vault_path: String, rel_path: String) -> Result<(), String> {
+    let full_path = existing_vault_path(&vault_path, &rel_path)?;
+    open_with_default_app(&full_path).await
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Shows a note in Finder/Explorer with it selected. On Linux selection goes
+/// through the FileManager1 D-Bus interface, falling back to opening the
+/// containing folder when no file manager implements it.
+#[command]
+pub async fn markdown_reveal_in_file_manager(vault_path: String, rel_path: String) -> Result<(), String> {
+    let full_path = existing_vault_path(&vault_path, &rel_path)?;
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(&full_path);
+        run_opener(command, &full_path).await
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // explorer.exe exits non-zero even on success, so only spawn errors count.
+        let mut select_arg = std::ffi::OsString::from("/select,");
+        select_arg.push(full_path.as_os_str());
+        Command::new("explorer")
+            .arg(select_arg)
+            .status()
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to launch Explorer: {}", e))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let selected = Command::new("dbus-send")
+            .args([
+                "--session",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            .arg(format!("array:string:{}", file_uri(&full_path)))
+            .arg("string:")
+            .status()
+            .await
+            .map(|status| status.success())
+            .unwrap_or(false);
+
+        if selected {
+            return Ok(());
+        }
+
+        let folder = if full_path.is_dir() {
+            full_path.clone()
+        } else {
+            full_path.parent().map(|p| p.to_path_buf()).unwrap_or_else(|| full_path.clone())
+        };
+        open_with_default_app(&folder).await
+    }
+}
@@ -0,0 +1,492 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::Row;
+use tauri::command;
+
+use super::indexer::reindex_file_with;
+use super::links::LinkResolver;
+use super::search::get_or_create_pool;
+use super::vault::{resolve_in_vault, vault_for_index, write_atomic, VaultConfig};
+
+/// Used by `markdown_add_default_frontmatter` when no template is given.
+const DEFAULT_FRONTMATTER: &str = "created: {{date}}\ntags: []";
+
+/// Splits a note into its raw frontmatter block and body. Mirrors the frontend's
+/// `parseFrontmatter`: the note must open with `---` and the block ends at the
+/// next line that is exactly `---`.
+pub fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content.strip_prefix("---") else {
+        return (None, content);
+    };
+    let Some(first_newline) = rest.find('\n') else {
+        return (None, content);
+    };
+    if !rest[..first_newline].trim().is_empty() {
+        return (None, content);
+    }
+
+    let yaml_start = 3 + first_newline + 1;
+    let mut offset = yaml_start;
+    for line in content[yaml_start..].split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let yaml = content[yaml_start..offset].trim_end_matches(['\r', '\n']);
+            return (Some(yaml), &content[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+
+    (None, content)
+}
+
+/// Parses the simple `key: value` frontmatter the editor writes. Inline
+/// `[a, b]` arrays, quoted strings, booleans and numbers are recognised;
+/// anything else is kept as a string.
+pub fn parse_frontmatter(content: &str) -> (Map<String, Value>, &str) {
+    match split_frontmatter(content) {
+        (Some(yaml), body) => (parse_yaml(yaml), body),
+        (None, body) => (Map::new(), body),
+    }
+}
+
+fn parse_yaml(text: &str) -> Map<String, Value> {
+    let mut result = Map::new();
+
+    for line in text.lines() {
+        let Some(colon) = line.find(':') else {
+            continue;
+        };
+        let key = line[..colon].trim().to_string();
+        let value = line[colon + 1..].trim();
+
+        let parsed = if value.starts_with('[') && value.ends_with(']') && value.len() >= 2 {
+            Value::Array(
+                value[1..value.len() - 1]
+                    .split(',')
+                    .map(|v| Value::String(strip_quotes(v.trim()).to_string()))
+                    .filter(|v| v.as_str() != Some(""))
+                    .collect(),
+            )
+        } else if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+            Value::String(value[1..value.len() - 1].to_string())
+        } else if value == "true" {
+            Value::Bool(true)
+        } else if value == "false" {
+            Value::Bool(false)
+        } else if let Ok(number) = value.parse::<i64>() {
+            Value::Number(number.into())
+        } else if let Some(number) = value.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+            Value::Number(number)
+        } else {
+            Value::String(value.to_string())
+        };
+
+        result.insert(key, parsed);
+    }
+
+    result
+}
+
+fn strip_quotes(value: &str) -> &str {
+    let value = value.strip_prefix(['"', '\'']).unwrap_or(value);
+    value.strip_suffix(['"', '\'']).unwrap_or(value)
+}
+
+/// Reads a frontmatter value as a list of strings, accepting either an array
+/// or a single string.
+pub fn string_list(frontmatter: &Map<String, Value>, key: &str) -> Vec<String> {
+    match frontmatter.get(key) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        Some(Value::String(s)) if !s.is_empty() => vec![s.clone()],
+        _ => Vec::new(),
+    }
+}
+
+/// Replaces the value of an existing top-level `key:` line in the note's
+/// frontmatter, keeping the rest of the file byte-for-byte. Quotes around
+/// the old value are kept. Returns `None` when the key isn't present.
+pub fn replace_frontmatter_value(content: &str, key: &str, value: &str) -> Option<String> {
+    let (Some(yaml), _) = split_frontmatter(content) else {
+        return None;
+    };
+    let yaml_start = yaml.as_ptr() as usize - content.as_ptr() as usize;
+
+    let mut offset = yaml_start;
+    for line in yaml.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\r', '\n']);
+        if let Some(colon) = text.find(':') {
+            if !text.starts_with([' ', '\t']) && text[..colon].trim() == key {
+                let old = text[colon + 1..].trim();
+                let quote = ['"', '\''].into_iter().find(|q| old.len() >= 2 && old.starts_with(*q) && old.ends_with(*q));
+                let new_value = match quote {
+                    Some(q) => format!("{}{}{}", q, value, q),
+                    None => value.to_string(),
+                };
+                let value_start = offset + colon + 1;
+                let value_end = offset + text.len();
+                return Some(format!("{} {}{}", &content[..value_start], new_value, &content[value_end..]));
+            }
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Edits a list-valued frontmatter key in place with `update`, creating the
+/// key, or the whole frontmatter block, when missing. Inline `[a, b]` lists
+/// and scalars are written back inline; `- item` block lists keep their
+/// layout. Returns `None` when `update` leaves the list unchanged.
+pub fn update_frontmatter_list(
+    content: &str,
+    key: &str,
+    update: impl FnOnce(&mut Vec<String>),
+) -> Option<String> {
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let inline = |items: &[String]| format!("[{}]", items.join(", "));
+
+    let (Some(yaml), _) = split_frontmatter(content) else {
+        let mut items = Vec::new();
+        update(&mut items);
+        if items.is_empty() {
+            return None;
+        }
+        return Some(format!("---{nl}{}: {}{nl}---{nl}{}", key, inline(&items), content, nl = newline));
+    };
+    let yaml_start = yaml.as_ptr() as usize - content.as_ptr() as usize;
+    let yaml_end = yaml_start + yaml.len();
+
+    let lines: Vec<(usize, &str)> = yaml
+        .split_inclusive('\n')
+        .scan(yaml_start, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line.trim_end_matches(['\r', '\n'])))
+        })
+        .collect();
+
+    let found = lines.iter().enumerate().find_map(|(i, (offset, text))| {
+        let colon = text.find(':')?;
+        (!text.starts_with([' ', '\t', '-']) && text[..colon].trim() == key).then_some((i, offset + colon + 1))
+    });
+
+    let Some((index, value_start)) = found else {
+        let mut items = Vec::new();
+        update(&mut items);
+        if items.is_empty() {
+            return None;
+        }
+        let line = format!("{}: {}", key, inline(&items));
+        let insert = if yaml.is_empty() {
+            format!("{}{}", line, newline)
+        } else {
+            format!("{}{}", newline, line)
+        };
+        return Some(format!("{}{}{}", &content[..yaml_end], insert, &content[yaml_end..]));
+    };
+
+    let (offset, text) = lines[index];
+    let value = text[value_start - offset..].trim();
+    let block: Vec<(usize, &str)> = if value.is_empty() {
+        lines[index + 1..]
+            .iter()
+            .take_while(|(_, line)| {
+                let trimmed = line.trim_start();
+                trimmed == "-" || trimmed.starts_with("- ")
+            })
+            .copied()
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let original: Vec<String> = if !block.is_empty() {
+        block
+            .iter()
+            .map(|(_, line)| strip_quotes(line.trim_start()[1..].trim()).to_string())
+            .collect()
+    } else if value.starts_with('[') && value.ends_with(']') {
+        value[1..value.len() - 1]
+            .split(',')
+            .map(|v| strip_quotes(v.trim()).to_string())
+            .filter(|v| !v.is_empty())
+            .collect()
+    } else if value.is_empty() {
+        Vec::new()
+    } else {
+        vec![strip_quotes(value).to_string()]
+    };
+
+    let mut items = original.clone();
+    update(&mut items);
+    if items == original {
+        return None;
+    }
+
+    match block.last() {
+        Some(&(last_offset, last_line)) if !items.is_empty() => {
+            let first = block[0].1;
+            let indent = &first[..first.len() - first.trim_start().len()];
+            let rendered: Vec<String> = items.iter().map(|item| format!("{}- {}", indent, item)).collect();
+            let block_start = block[0].0;
+            let block_end = last_offset + last_line.len();
+            Some(format!("{}{}{}", &content[..block_start], rendered.join(newline), &content[block_end..]))
+        }
+        last => {
+            let value_end = match last {
+                Some(&(last_offset, last_line)) => last_offset + last_line.len(),
+                None => offset + text.len(),
+            };
+            Some(format!("{} {}{}", &content[..value_start], inline(&items), &content[value_end..]))
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AddFrontmatterResult {
+    pub changed: usize,
+    /// Notes that already had frontmatter.
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
+
+/// Fills in a frontmatter template for one note: `{{date}}` becomes today's
+/// local date and `{{title}}` the file name, quoted. Surrounding `---` lines
+/// are optional.
+fn render_frontmatter(template: &str, rel_path: &str) -> String {
+    let name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+    let title = name.strip_suffix(".md").unwrap_or(name);
+    let body = template.trim().trim_start_matches("---").trim_end_matches("---").trim_matches(['\r', '\n']);
+    body.replace("{{date}}", &chrono::Local::now().format("%Y-%m-%d").to_string())
+        .replace("{{title}}", &Value::String(title.to_string()).to_string())
+}
+
+/// Prepends a frontmatter block to each note that has none, from
+/// `template` or `created: {{date}}` plus empty `tags`, then reindexes it.
+/// The block uses the note's own line endings.
+#[command]
+pub async fn markdown_add_default_frontmatter(
+    vault_path: String,
+    index_path: String,
+    paths: Vec<String>,
+    template: Option<String>,
+) -> Result<AddFrontmatterResult, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let template = template.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| DEFAULT_FRONTMATTER.to_string());
+    let mut resolver = LinkResolver::load(&pool).await?;
+    let mut result = AddFrontmatterResult::default();
+
+    for path in paths {
+        let edited = resolve_in_vault(&vault_path, &path).and_then(|full_path| {
+            let raw = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read note: {}", e))?;
+            if split_frontmatter(&raw).0.is_some() {
+                return Ok(false);
+            }
+            let newline = if raw.contains("\r\n") { "\r\n" } else { "\n" };
+            let block = render_frontmatter(&template, &path).lines().collect::<Vec<_>>().join(newline);
+            let updated = format!("---{nl}{}{nl}---{nl}{}", block, raw, nl = newline);
+            write_atomic(&full_path, &updated).map(|_| true)
+        });
+
+        match edited {
+            Ok(true) => match reindex_file_with(&pool, &vault_path, &path, &mut resolver).await {
+                Ok(_) => result.changed += 1,
+                Err(e) => result.errors.push(format!("{}: {}", path, e)),
+            },
+            Ok(false) => result.skipped += 1,
+            Err(e) => result.errors.push(format!("{}: {}", path, e)),
+        }
+    }
+
+    Ok(result)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Number,
+    Boolean,
+    List,
+    /// `YYYY-MM-DD`, optionally followed by a time.
+    Date,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldRule {
+    pub key: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(rename = "type")]
+    pub field_type: Option<FieldType>,
+    /// Values the field may take; each item must be one of them for lists.
+    pub allowed: Option<Vec<Value>>,
+}
+
+/// Required frontmatter, stored as the `frontmatterSchema` vault setting.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontmatterSchema {
+    /// Folder the schema applies to in vault-wide checks; the whole vault
+    /// when unset.
+    pub folder: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<FieldRule>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FrontmatterError {
+    pub key: String,
+    /// `required`, `type` or `allowed`.
+    pub rule: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FrontmatterViolations {
+    pub path: String,
+    pub errors: Vec<FrontmatterError>,
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn has_type(value: &Value, field_type: FieldType) -> bool {
+    match field_type {
+        FieldType::String => value.is_string(),
+        FieldType::Number => value.is_number(),
+        FieldType::Boolean => value.is_boolean(),
+        FieldType::List => value.is_array(),
+        FieldType::Date => value
+            .as_str()
+            .and_then(|s| s.get(..10))
+            .is_some_and(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok()),
+    }
+}
+
+/// Whether `value` is among `allowed`, comparing strings with other scalars
+/// by their text, since frontmatter is untyped.
+fn is_allowed(value: &Value, allowed: &[Value]) -> bool {
+    let text = |v: &Value| match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    allowed.iter().any(|a| a == value || text(a) == text(value))
+}
+
+/// Checks parsed frontmatter against a schema. A blank value counts as
+/// missing; a missing optional field is not checked further.
+pub fn validate_fields(frontmatter: &Map<String, Value>, schema: &FrontmatterSchema) -> Vec<FrontmatterError> {
+    let mut errors = Vec::new();
+    let mut error = |key: &str, rule: &str, message: String| {
+        errors.push(FrontmatterError {
+            key: key.to_string(),
+            rule: rule.to_string(),
+            message,
+        })
+    };
+
+    for field in &schema.fields {
+        let key = field.key.as_str();
+        let Some(value) = frontmatter.get(key).filter(|v| !is_blank(v)) else {
+            if field.required {
+                error(key, "required", format!("{} is required", key));
+            }
+            continue;
+        };
+        if let Some(field_type) = field.field_type {
+            if !has_type(value, field_type) {
+                let expected = serde_json::to_value(field_type).ok().and_then(|t| t.as_str().map(|t| t.to_string()));
+                error(key, "type", format!("{} must be a {}", key, expected.unwrap_or_default()));
+                continue;
+            }
+        }
+        if let Some(allowed) = field.allowed.as_deref().filter(|a| !a.is_empty()) {
+            let items = match value {
+                Value::Array(items) => items.iter().collect::<Vec<_>>(),
+                value => vec![value],
+            };
+            if let Some(bad) = items.into_iter().find(|item| !is_allowed(item, allowed)) {
+                let choices: Vec<String> = allowed
+                    .iter()
+                    .map(|a| a.as_str().map(|s| s.to_string()).unwrap_or_else(|| a.to_string()))
+                    .collect();
+                let shown = bad.as_str().map(|s| s.to_string()).unwrap_or_else(|| bad.to_string());
+                error(key, "allowed", format!("{} is {}, expected one of {}", key, shown, choices.join(", ")));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Validates a note's frontmatter, e.g. from a save hook, against `schema`.
+#[command]
+pub async fn markdown_validate_frontmatter(
+    content: String,
+    schema: FrontmatterSchema,
+) -> Result<Vec<FrontmatterError>, String> {
+    let (frontmatter, _) = parse_frontmatter(&content);
+    Ok(validate_fields(&frontmatter, &schema))
+}
+
+/// Checks every indexed note in the schema's folder against `schema`, or
+/// the vault's `frontmatterSchema` setting when omitted, and lists the notes
+/// that break it, by path.
+#[command]
+pub async fn markdown_validate_vault_frontmatter(
+    index_path: String,
+    schema: Option<FrontmatterSchema>,
+) -> Result<Vec<FrontmatterViolations>, String> {
+    let schema = match schema {
+        Some(schema) => schema,
+        None => vault_for_index(&index_path)
+            .and_then(VaultConfig::load)
+            .and_then(|config| config.settings.get("frontmatterSchema").cloned())
+            .map(|value| serde_json::from_value(value).map_err(|e| format!("Invalid frontmatterSchema setting: {}", e)))
+            .transpose()?
+            .unwrap_or_default(),
+    };
+    if schema.fields.is_empty() {
+        return Ok(Vec::new());
+    }
+    let folder = schema.folder.as_deref().unwrap_or("").trim().trim_matches('/').to_string();
+
+    let pool = get_or_create_pool(&index_path).await?;
+    let rows = sqlx::query(r#"
+        SELECT path, frontmatter FROM notes
+        WHERE ? = '' OR substr(path, 1, length(?) + 1) = ? || '/'
+        ORDER BY path
+    "#)
+    .bind(&folder)
+    .bind(&folder)
+    .bind(&folder)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load frontmatter: {}", e))?;
+
+    let mut violations = Vec::new();
+    for row in &rows {
+        let frontmatter: Map<String, Value> = row
+            .get::<Option<String>, _>("frontmatter")
+            .and_then(|f| serde_json::from_str(&f).ok())
+            .unwrap_or_default();
+        let errors = validate_fields(&frontmatter, &schema);
+        if !errors.is_empty() {
+            violations.push(FrontmatterViolations {
+                path: row.get::<String, _>("path"),
+                errors,
+            });
+        }
+    }
+    Ok(violations)
+}
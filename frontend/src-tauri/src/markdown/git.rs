@@ -0,0 +1,644 @@
+use git2::build::CheckoutBuilder;
+use git2::{
+    Cred, CredentialType, FetchOptions, IndexAddOption, PushOptions, RemoteCallbacks, Repository, RepositoryState,
+    Signature, Status, StatusOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::command;
+
+use super::vault::{is_markdown_path, resolve_in_vault, walk_vault, write_atomic};
+
+/// Written by `markdown_git_init` when the vault has no `.gitignore`, so the
+/// index database, snapshots and trash stay out of history.
+const DEFAULT_GITIGNORE: &str = ".vault/index.db*\n.nenspace/\n.trash/\n";
+/// Used when the current branch has no upstream configured.
+const DEFAULT_REMOTE: &str = "origin";
+/// libgit2 asks again after a rejected credential; stop before it loops.
+const MAX_AUTH_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitFileStatus {
+    /// Vault-relative.
+    pub path: String,
+    /// `new`, `modified`, `deleted`, `renamed`, `typechange` or `conflicted`.
+    pub status: String,
+    /// Whether the change is already in the Git index.
+    pub staged: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitStatus {
+    /// `None` on a detached HEAD or before the first commit.
+    pub branch: Option<String>,
+    pub files: Vec<GitFileStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitCommitInfo {
+    pub id: String,
+    pub summary: String,
+    pub message: String,
+    pub author: String,
+    pub email: String,
+    /// RFC3339.
+    pub time: String,
+}
+
+/// The repository holding the vault, plus the vault's path inside its
+/// working tree (empty when the vault is the repository root). Commands only
+/// look at and stage files under that prefix.
+fn open_repo(vault_path: &str) -> Result<(Repository, String), String> {
+    let repo = Repository::discover(vault_path)
+        .map_err(|_| "Vault is not a Git repository; initialise one first".to_string())?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| "Git repository has no working tree".to_string())?;
+
+    let canonical = |p: &Path| p.canonicalize().map_err(|e| format!("Failed to resolve {}: {}", p.display(), e));
+    let vault = canonical(Path::new(vault_path))?;
+    let prefix = vault
+        .strip_prefix(canonical(workdir)?)
+        .map_err(|_| "Vault is outside the Git working tree".to_string())?
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    Ok((repo, prefix))
+}
+
+/// Converts a working-tree path to a vault-relative one, or `None` when it
+/// lies outside the vault.
+fn vault_relative(prefix: &str, path: &str) -> Option<String> {
+    if prefix.is_empty() {
+        return Some(path.to_string());
+    }
+    path.strip_prefix(prefix)?.strip_prefix('/').map(|p| p.to_string())
+}
+
+fn status_kind(status: Status) -> &'static str {
+    if status.is_conflicted() {
+        "conflicted"
+    } else if status.intersects(Status::INDEX_NEW | Status::WT_NEW) {
+        "new"
+    } else if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+        "deleted"
+    } else if status.intersects(Status::INDEX_RENAMED | Status::WT_RENAMED) {
+        "renamed"
+    } else if status.intersects(Status::INDEX_TYPECHANGE | Status::WT_TYPECHANGE) {
+        "typechange"
+    } else {
+        "modified"
+    }
+}
+
+fn commit_info(commit: &git2::Commit) -> GitCommitInfo {
+    let author = commit.author();
+    let time = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_default();
+    GitCommitInfo {
+        id: commit.id().to_string(),
+        summary: commit.summary().unwrap_or("").to_string(),
+        message: commit.message().unwrap_or("").to_string(),
+        author: author.name().unwrap_or("").to_string(),
+        email: author.email().unwrap_or("").to_string(),
+        time,
+    }
+}
+
+/// Creates a repository at the vault root, with a default `.gitignore` if
+/// there isn't one. Fails if the vault is already inside a repository.
+#[command]
+pub async fn markdown_git_init(vault_path: String) -> Result<(), String> {
+    if Repository::discover(&vault_path).is_ok() {
+        return Err("Vault is already in a Git repository".to_string());
+    }
+    Repository::init(&vault_path).map_err(|e| format!("Failed to initialise repository: {}", e))?;
+
+    let gitignore = Path::new(&vault_path).join(".gitignore");
+    if !gitignore.exists() {
+        std::fs::write(&gitignore, DEFAULT_GITIGNORE).map_err(|e| format!("Failed to write .gitignore: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Changed files in the vault, untracked ones included and `.gitignore`
+/// respected.
+#[command]
+pub async fn markdown_git_status(vault_path: String) -> Result<GitStatus, String> {
+    let (repo, prefix) = open_repo(&vault_path)?;
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true).include_ignored(false);
+    if !prefix.is_empty() {
+        options.pathspec(&prefix);
+    }
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| format!("Failed to read Git status: {}", e))?;
+
+    let staged_mask = Status::INDEX_NEW
+        | Status::INDEX_MODIFIED
+        | Status::INDEX_DELETED
+        | Status::INDEX_RENAMED
+        | Status::INDEX_TYPECHANGE;
+    let mut files: Vec<GitFileStatus> = statuses
+        .iter()
+        .filter_map(|entry| {
+            let path = vault_relative(&prefix, entry.path()?)?;
+            let status = entry.status();
+            Some(GitFileStatus {
+                path,
+                status: status_kind(status).to_string(),
+                staged: status.intersects(staged_mask),
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let branch = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(|s| s.to_string()));
+
+    Ok(GitStatus { branch, files })
+}
+
+/// Stages every change under the vault, deletions included, and commits it
+/// on the current branch. The author comes from Git config, falling back to
+/// a generic identity when none is set.
+#[command]
+pub async fn markdown_git_commit(vault_path: String, message: String) -> Result<GitCommitInfo, String> {
+    if message.trim().is_empty() {
+        return Err("Commit message is empty".to_string());
+    }
+    let (mut repo, prefix) = open_repo(&vault_path)?;
+
+    // Committing after a conflicted pull concludes the merge.
+    let mut merge_heads = Vec::new();
+    if repo.state() == RepositoryState::Merge {
+        repo.mergehead_foreach(|id| {
+            merge_heads.push(*id);
+            true
+        })
+        .map_err(|e| format!("Failed to read merge heads: {}", e))?;
+    }
+
+    let pathspec = if prefix.is_empty() { "*".to_string() } else { format!("{}/*", prefix) };
+    let mut index = repo.index().map_err(|e| format!("Failed to open Git index: {}", e))?;
+    index
+        .add_all([&pathspec], IndexAddOption::DEFAULT, None)
+        .map_err(|e| format!("Failed to stage changes: {}", e))?;
+    index
+        .update_all([&pathspec], None)
+        .map_err(|e| format!("Failed to stage deletions: {}", e))?;
+    index.write().map_err(|e| format!("Failed to write Git index: {}", e))?;
+
+    let tree_id = index.write_tree().map_err(|e| format!("Failed to write tree: {}", e))?;
+    let tree = repo.find_tree(tree_id).map_err(|e| format!("Failed to read tree: {}", e))?;
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    if merge_heads.is_empty() && parent.as_ref().is_some_and(|p| p.tree_id() == tree_id) {
+        return Err("Nothing to commit".to_string());
+    }
+
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("Nenspace", "nenspace@localhost"))
+        .map_err(|e| format!("Failed to create signature: {}", e))?;
+    let merge_parents = merge_heads
+        .iter()
+        .map(|id| repo.find_commit(*id))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read merge head: {}", e))?;
+    let parents: Vec<&git2::Commit> = parent.iter().chain(merge_parents.iter()).collect();
+    let id = repo
+        .commit(Some("HEAD"), &signature, &signature, message.trim(), &tree, &parents)
+        .map_err(|e| format!("Failed to commit: {}", e))?;
+
+    if !merge_heads.is_empty() {
+        repo.cleanup_state().map_err(|e| format!("Failed to finish merge: {}", e))?;
+    }
+
+    let commit = repo.find_commit(id).map_err(|e| format!("Failed to read commit: {}", e))?;
+    Ok(commit_info(&commit))
+}
+
+/// Commits reachable from HEAD, newest first. Empty before the first commit.
+#[command]
+pub async fn markdown_git_log(vault_path: String, limit: Option<usize>) -> Result<Vec<GitCommitInfo>, String> {
+    let (repo, _) = open_repo(&vault_path)?;
+    if repo.head().is_err() {
+        return Ok(Vec::new());
+    }
+
+    let mut walk = repo.revwalk().map_err(|e| format!("Failed to read history: {}", e))?;
+    walk.push_head().map_err(|e| format!("Failed to read history: {}", e))?;
+    walk.set_sorting(git2::Sort::TIME).map_err(|e| format!("Failed to read history: {}", e))?;
+
+    walk.take(limit.unwrap_or(50).max(1))
+        .map(|id| {
+            let id = id.map_err(|e| format!("Failed to read history: {}", e))?;
+            let commit = repo.find_commit(id).map_err(|e| format!("Failed to read commit: {}", e))?;
+            Ok(commit_info(&commit))
+        })
+        .collect()
+}
+
+/// Credentials for the vault's remote, supplied by the caller (which keeps
+/// them in the OS keychain). Without any, the SSH agent and Git's configured
+/// credential helpers are tried.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GitCredentials {
+    /// Defaults to the user in the remote URL, then `git`.
+    pub username: Option<String>,
+    /// HTTPS personal access token or password.
+    pub token: Option<String>,
+    pub ssh_key_path: Option<String>,
+    pub ssh_passphrase: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitPullOutcome {
+    UpToDate,
+    FastForward,
+    Merged,
+    /// The merge stopped with conflicts; the repository is left mid-merge
+    /// and `markdown_git_commit` concludes it once they are resolved.
+    Conflicts,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitConflict {
+    /// Vault-relative when inside the vault, otherwise relative to the
+    /// repository root.
+    pub path: String,
+    /// False when that side deleted the file.
+    pub ours: bool,
+    pub theirs: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitPullResult {
+    pub outcome: GitPullOutcome,
+    /// HEAD after the pull; `None` while conflicts are pending.
+    pub head: Option<GitCommitInfo>,
+    pub conflicts: Vec<GitConflict>,
+}
+
+fn remote_callbacks(credentials: &GitCredentials) -> RemoteCallbacks<'_> {
+    let mut callbacks = RemoteCallbacks::new();
+    let mut attempts = 0;
+    callbacks.credentials(move |url, username_from_url, allowed| {
+        attempts += 1;
+        if attempts > MAX_AUTH_ATTEMPTS {
+            return Err(git2::Error::from_str("Authentication failed"));
+        }
+        let username = credentials.username.as_deref().or(username_from_url).unwrap_or("git");
+
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username);
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return match &credentials.ssh_key_path {
+                Some(key) => Cred::ssh_key(username, None, Path::new(key), credentials.ssh_passphrase.as_deref()),
+                None => Cred::ssh_key_from_agent(username),
+            };
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Some(token) = &credentials.token {
+                return Cred::userpass_plaintext(username, token);
+            }
+            let config = git2::Config::open_default()?;
+            return Cred::credential_helper(&config, url, username_from_url);
+        }
+        Err(git2::Error::from_str("No credentials available for this remote"))
+    });
+    callbacks
+}
+
+/// The checked-out branch and the remote it tracks.
+fn current_branch(repo: &Repository) -> Result<(String, String), String> {
+    let head = repo.head().map_err(|_| "Repository has no commits yet".to_string())?;
+    if !head.is_branch() {
+        return Err("HEAD is detached; check out a branch first".to_string());
+    }
+    let branch = head.shorthand().unwrap_or_default().to_string();
+    let refname = head.name().unwrap_or_default().to_string();
+    let remote = repo
+        .branch_upstream_remote(&refname)
+        .ok()
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| DEFAULT_REMOTE.to_string());
+    Ok((branch, remote))
+}
+
+fn head_info(repo: &Repository) -> Option<GitCommitInfo> {
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit_info(&commit))
+}
+
+/// Fetches the current branch from its remote and merges it: a
+/// fast-forward when possible, otherwise a merge commit. Conflicts are
+/// returned rather than treated as an error so the UI can list them.
+/// Refuses to run over uncommitted changes to tracked files.
+#[command]
+pub async fn markdown_git_pull(
+    vault_path: String,
+    credentials: Option<GitCredentials>,
+) -> Result<GitPullResult, String> {
+    let credentials = credentials.unwrap_or_default();
+    let (repo, prefix) = open_repo(&vault_path)?;
+    if repo.state() != RepositoryState::Clean {
+        return Err("A merge is in progress; resolve it and commit first".to_string());
+    }
+    let (branch, remote_name) = current_branch(&repo)?;
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(false);
+    let dirty = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| format!("Failed to read Git status: {}", e))?;
+    if !dirty.is_empty() {
+        return Err("Commit or discard local changes before pulling".to_string());
+    }
+
+    let mut remote = repo
+        .find_remote(&remote_name)
+        .map_err(|_| format!("Remote {} is not configured", remote_name))?;
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(remote_callbacks(&credentials));
+    remote
+        .fetch(&[&branch], Some(&mut fetch_options), None)
+        .map_err(|e| format!("Failed to fetch from {}: {}", remote_name, e))?;
+
+    let fetch_head = repo
+        .find_reference("FETCH_HEAD")
+        .map_err(|e| format!("Failed to read fetched branch: {}", e))?;
+    let incoming = repo
+        .reference_to_annotated_commit(&fetch_head)
+        .map_err(|e| format!("Failed to read fetched branch: {}", e))?;
+    let (analysis, _) = repo
+        .merge_analysis(&[&incoming])
+        .map_err(|e| format!("Failed to analyse merge: {}", e))?;
+
+    if analysis.is_up_to_date() {
+        return Ok(GitPullResult {
+            outcome: GitPullOutcome::UpToDate,
+            head: head_info(&repo),
+            conflicts: Vec::new(),
+        });
+    }
+
+    if analysis.is_fast_forward() {
+        let refname = format!("refs/heads/{}", branch);
+        let mut reference = repo
+            .find_reference(&refname)
+            .map_err(|e| format!("Failed to read {}: {}", refname, e))?;
+        reference
+            .set_target(incoming.id(), "pull: fast-forward")
+            .map_err(|e| format!("Failed to fast-forward: {}", e))?;
+        repo.checkout_head(Some(CheckoutBuilder::new().force()))
+            .map_err(|e| format!("Failed to check out {}: {}", branch, e))?;
+        return Ok(GitPullResult {
+            outcome: GitPullOutcome::FastForward,
+            head: head_info(&repo),
+            conflicts: Vec::new(),
+        });
+    }
+
+    repo.merge(&[&incoming], None, Some(CheckoutBuilder::new().allow_conflicts(true).conflict_style_merge(true)))
+        .map_err(|e| format!("Failed to merge: {}", e))?;
+    let mut index = repo.index().map_err(|e| format!("Failed to open Git index: {}", e))?;
+
+    if index.has_conflicts() {
+        let conflicts = index
+            .conflicts()
+            .map_err(|e| format!("Failed to read conflicts: {}", e))?
+            .flatten()
+            .filter_map(|conflict| {
+                let entry = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref())?;
+                let path = String::from_utf8_lossy(&entry.path).to_string();
+                Some(GitConflict {
+                    path: vault_relative(&prefix, &path).unwrap_or(path),
+                    ours: conflict.our.is_some(),
+                    theirs: conflict.their.is_some(),
+                })
+            })
+            .collect();
+        return Ok(GitPullResult {
+            outcome: GitPullOutcome::Conflicts,
+            head: None,
+            conflicts,
+        });
+    }
+
+    let tree_id = index.write_tree().map_err(|e| format!("Failed to write tree: {}", e))?;
+    let tree = repo.find_tree(tree_id).map_err(|e| format!("Failed to read tree: {}", e))?;
+    let ours = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|e| format!("Failed to read HEAD: {}", e))?;
+    let theirs = repo
+        .find_commit(incoming.id())
+        .map_err(|e| format!("Failed to read fetched commit: {}", e))?;
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("Nenspace", "nenspace@localhost"))
+        .map_err(|e| format!("Failed to create signature: {}", e))?;
+    let message = format!("Merge branch '{}' of {}", branch, remote_name);
+    repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &[&ours, &theirs])
+        .map_err(|e| format!("Failed to commit merge: {}", e))?;
+    repo.cleanup_state().map_err(|e| format!("Failed to finish merge: {}", e))?;
+
+    Ok(GitPullResult {
+        outcome: GitPullOutcome::Merged,
+        head: head_info(&repo),
+        conflicts: Vec::new(),
+    })
+}
+
+/// Pushes the current branch to its remote. A rejected update, e.g. when
+/// the remote has commits that haven't been pulled, is an error.
+#[command]
+pub async fn markdown_git_push(vault_path: String, credentials: Option<GitCredentials>) -> Result<(), String> {
+    let credentials = credentials.unwrap_or_default();
+    let (repo, _) = open_repo(&vault_path)?;
+    let (branch, remote_name) = current_branch(&repo)?;
+    let mut remote = repo
+        .find_remote(&remote_name)
+        .map_err(|_| format!("Remote {} is not configured", remote_name))?;
+
+    let mut rejected: Option<String> = None;
+    let mut callbacks = remote_callbacks(&credentials);
+    callbacks.push_update_reference(|refname, status| {
+        if let Some(status) = status {
+            rejected = Some(format!("{}: {}", refname, status));
+        }
+        Ok(())
+    });
+    let mut push_options = PushOptions::new();
+    push_options.remote_callbacks(callbacks);
+
+    let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+    remote.push(&[&refspec], Some(&mut push_options)).map_err(|e| {
+        if e.code() == git2::ErrorCode::NotFastForward {
+            "Push rejected: the remote has commits that haven't been pulled; pull first".to_string()
+        } else {
+            format!("Failed to push to {}: {}", remote_name, e)
+        }
+    })?;
+    drop(push_options);
+
+    match rejected {
+        Some(reason) => Err(format!("Push rejected: {}; pull first", reason)),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConflictHunk {
+    /// Position within the file, as passed to `markdown_resolve_conflict`.
+    pub index: usize,
+    /// One-based lines of the `<<<<<<<`, `=======` and `>>>>>>>` markers.
+    pub start_line: usize,
+    pub separator_line: usize,
+    pub end_line: usize,
+    /// Text after the markers, e.g. `HEAD` and the merged commit.
+    pub ours_label: String,
+    pub theirs_label: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConflictedNote {
+    pub path: String,
+    pub hunks: Vec<ConflictHunk>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictChoice {
+    Ours,
+    Theirs,
+    /// Ours followed by theirs.
+    Both,
+}
+
+/// Zero-based line indexes of one hunk's markers. `base` is the `|||||||`
+/// line written by the diff3 conflict style.
+struct HunkLines {
+    start: usize,
+    base: Option<usize>,
+    separator: usize,
+    end: usize,
+}
+
+/// The label after a conflict marker made of seven `marker` characters at
+/// the start of a line, or `None` if the line isn't one.
+fn marker_label(line: &str, marker: char) -> Option<&str> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let rest = line.strip_prefix(&marker.to_string().repeat(7))?;
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+/// Complete hunks only; stray or unbalanced markers are left alone.
+fn conflict_hunks(lines: &[&str]) -> Vec<HunkLines> {
+    let mut hunks = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut base = None;
+    let mut separator = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        if marker_label(line, '<').is_some() {
+            (start, base, separator) = (Some(i), None, None);
+        } else if let Some(s) = start {
+            if separator.is_none() && base.is_none() && marker_label(line, '|').is_some() {
+                base = Some(i);
+            } else if separator.is_none() && marker_label(line, '=').is_some_and(|label| label.is_empty()) {
+                separator = Some(i);
+            } else if let (Some(sep), Some(_)) = (separator, marker_label(line, '>')) {
+                hunks.push(HunkLines { start: s, base, separator: sep, end: i });
+                start = None;
+            }
+        }
+    }
+    hunks
+}
+
+/// Finds the Git conflict hunks in a note's content.
+pub fn find_conflicts(content: &str) -> Vec<ConflictHunk> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    conflict_hunks(&lines)
+        .into_iter()
+        .enumerate()
+        .map(|(index, hunk)| ConflictHunk {
+            index,
+            start_line: hunk.start + 1,
+            separator_line: hunk.separator + 1,
+            end_line: hunk.end + 1,
+            ours_label: marker_label(lines[hunk.start], '<').unwrap_or("").to_string(),
+            theirs_label: marker_label(lines[hunk.end], '>').unwrap_or("").to_string(),
+        })
+        .collect()
+}
+
+/// Replaces hunk `index` with the chosen side, keeping everything else
+/// (other hunks included) byte for byte. `None` if there is no such hunk.
+pub fn resolve_conflict(content: &str, index: usize, choice: ConflictChoice) -> Option<String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let hunk = conflict_hunks(&lines).into_iter().nth(index)?;
+
+    let ours = &lines[hunk.start + 1..hunk.base.unwrap_or(hunk.separator)];
+    let theirs = &lines[hunk.separator + 1..hunk.end];
+    let kept: Vec<&str> = match choice {
+        ConflictChoice::Ours => ours.to_vec(),
+        ConflictChoice::Theirs => theirs.to_vec(),
+        ConflictChoice::Both => ours.iter().chain(theirs).copied().collect(),
+    };
+
+    let mut resolved: String = lines[..hunk.start].concat();
+    resolved.push_str(&kept.concat());
+    resolved.push_str(&lines[hunk.end + 1..].concat());
+    Some(resolved)
+}
+
+/// Notes under the vault that still contain conflict markers, e.g. after a
+/// pull stopped with conflicts.
+#[command]
+pub async fn markdown_find_conflict_markers(vault_path: String) -> Result<Vec<ConflictedNote>, String> {
+    let mut notes: Vec<ConflictedNote> = walk_vault(&vault_path)
+        .into_iter()
+        .filter(|(rel_path, entry)| entry.file_type().is_file() && is_markdown_path(rel_path))
+        .filter_map(|(path, entry)| {
+            let content = std::fs::read_to_string(entry.path()).ok()?;
+            let hunks = find_conflicts(&content);
+            (!hunks.is_empty()).then_some(ConflictedNote { path, hunks })
+        })
+        .collect();
+    notes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(notes)
+}
+
+/// Resolves one conflict hunk in a note and rewrites it. Returns the hunks
+/// still left, renumbered.
+#[command]
+pub async fn markdown_resolve_conflict(
+    vault_path: String,
+    rel_path: String,
+    hunk: usize,
+    choice: ConflictChoice,
+) -> Result<Vec<ConflictHunk>, String> {
+    let full_path = resolve_in_vault(&vault_path, &rel_path)?;
+    let content = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read note: {}", e))?;
+
+    let resolved = resolve_conflict(&content, hunk, choice)
+        .ok_or_else(|| format!("Conflict {} not found in {}", hunk, rel_path))?;
+    write_atomic(&full_path, &resolved)?;
+
+    Ok(find_conflicts(&resolved))
+}
@@ -0,0 +1,171 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{command, AppHandle, Emitter};
+
+use super::vault::{is_markdown_path, walk_vault};
+
+lazy_static::lazy_static! {
+    static ref GREP_CANCEL_FLAGS: Mutex<HashMap<String, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GrepOptions {
+    #[serde(default)]
+    pub case_insensitive: bool,
+    #[serde(default)]
+    pub whole_word: bool,
+    /// Identifies the search for `markdown_cancel_grep` and in emitted
+    /// matches. Generated when not given.
+    pub search_id: Option<String>,
+    pub max_results: Option<usize>,
+}
+
+/// Payload of the `grep-match` event. `line` is zero-based in the file and
+/// `start`/`end` are byte offsets within `text`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrepMatch {
+    pub search_id: String,
+    pub path: String,
+    pub line: usize,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrepSummary {
+    pub search_id: String,
+    pub files_searched: usize,
+    pub matches: usize,
+    pub cancelled: bool,
+}
+
+/// Compiles a user pattern. The regex engine matches in linear time, so the
+/// guard is on compile-time complexity: size and nesting limits reject
+/// patterns that would blow up the automaton.
+pub fn build_grep_regex(pattern: &str, case_insensitive: bool, whole_word: bool) -> Result<Regex, String> {
+    let pattern = if whole_word {
+        format!(r"\b(?:{})\b", pattern)
+    } else {
+        pattern.to_string()
+    };
+
+    RegexBuilder::new(&pattern)
+        .case_insensitive(case_insensitive)
+        .size_limit(1 << 20)
+        .dfa_size_limit(1 << 20)
+        .nest_limit(32)
+        .build()
+        .map_err(|e| format!("Invalid pattern: {}", e))
+}
+
+/// Scans every markdown file in the vault, calling `on_match` for each hit
+/// until `cancel` is set or `max_results` is reached. Returns the number of
+/// files searched and matches found.
+pub fn grep_vault(
+    vault_path: &str,
+    regex: &Regex,
+    search_id: &str,
+    max_results: Option<usize>,
+    cancel: &AtomicBool,
+    mut on_match: impl FnMut(GrepMatch),
+) -> (usize, usize) {
+    let mut files_searched = 0;
+    let mut matches = 0;
+
+    for (rel_path, entry) in walk_vault(vault_path) {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        if !entry.file_type().is_file() || !is_markdown_path(&rel_path) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        files_searched += 1;
+
+        for (line_number, line) in content.lines().enumerate() {
+            for found in regex.find_iter(line) {
+                on_match(GrepMatch {
+                    search_id: search_id.to_string(),
+                    path: rel_path.clone(),
+                    line: line_number,
+                    text: line.to_string(),
+                    start: found.start(),
+                    end: found.end(),
+                });
+                matches += 1;
+                if max_results.is_some_and(|max| matches >= max) {
+                    return (files_searched, matches);
+                }
+            }
+        }
+    }
+
+    (files_searched, matches)
+}
+
+/// Regex search over the files on disk, bypassing the index. Matches are
+/// streamed as `grep-match` events while the walk runs; the summary is
+/// returned once it finishes or is cancelled.
+#[command]
+pub async fn markdown_grep_vault(
+    app: AppHandle,
+    vault_path: String,
+    pattern: String,
+    options: Option<GrepOptions>,
+) -> Result<GrepSummary, String> {
+    let options = options.unwrap_or_default();
+    let regex = build_grep_regex(&pattern, options.case_insensitive, options.whole_word)?;
+    let search_id = options
+        .search_id
+        .unwrap_or_else(|| format!("grep_{}", chrono::Utc::now().timestamp_millis()));
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    GREP_CANCEL_FLAGS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(search_id.clone(), cancel.clone());
+
+    let max_results = options.max_results;
+    let worker_id = search_id.clone();
+    let worker_cancel = cancel.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        grep_vault(&vault_path, &regex, &worker_id, max_results, &worker_cancel, |found| {
+            if let Err(e) = app.emit("grep-match", found) {
+                log::error!("Failed to emit grep match: {}", e);
+            }
+        })
+    })
+    .await;
+
+    if let Ok(mut flags) = GREP_CANCEL_FLAGS.lock() {
+        flags.remove(&search_id);
+    }
+    let (files_searched, matches) = result.map_err(|e| format!("Grep failed: {}", e))?;
+
+    Ok(GrepSummary {
+        search_id,
+        files_searched,
+        matches,
+        cancelled: cancel.load(Ordering::Relaxed),
+    })
+}
+
+/// Stops a running `markdown_grep_vault`. Returns false when no search with
+/// that id is running.
+#[command]
+pub async fn markdown_cancel_grep(search_id: String) -> Result<bool, String> {
+    let flags = GREP_CANCEL_FLAGS.lock().map_err(|e| e.to_string())?;
+    match flags.get(&search_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite, Transaction};
+use tauri::command;
+
+use super::search::get_or_create_pool;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: i32,
+    pub text: String,
+    pub line: i32,
+}
+
+/// Parses an ATX heading (`## Text`), returning its level and text.
+pub fn parse_heading_line(line: &str) -> Option<(i32, String)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim_end().to_string();
+    Some((level as i32, text))
+}
+
+/// Lists ATX headings outside fenced code blocks. Lines are zero-based.
+pub fn parse_headings(content: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut in_fence = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some((level, text)) = parse_heading_line(line) {
+            headings.push(Heading { level, text, line: index as i32 });
+        }
+    }
+
+    headings
+}
+
+/// Returns the section under `heading` (matched case-insensitively), from the
+/// heading line up to the next heading of the same or a higher level.
+pub fn extract_section(content: &str, heading: &str) -> Option<String> {
+    let headings = parse_headings(content);
+    let wanted = heading.trim().to_lowercase();
+    let position = headings.iter().position(|h| h.text.to_lowercase() == wanted)?;
+    let start = &headings[position];
+
+    let end = headings[position + 1..]
+        .iter()
+        .find(|h| h.level <= start.level)
+        .map(|h| h.line as usize);
+
+    let lines: Vec<&str> = content.lines().collect();
+    let end = end.unwrap_or(lines.len());
+    Some(lines[start.line as usize..end].join("\n"))
+}
+
+/// GitHub-style anchor for a heading: lowercased, punctuation dropped and
+/// spaces turned into hyphens.
+pub fn heading_anchor(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+pub async fn index_headings(
+    tx: &mut Transaction<'_, Sqlite>,
+    note_path: &str,
+    content: &str,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM note_headings WHERE note_path = ?")
+        .bind(note_path)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to clear headings: {}", e))?;
+
+    for heading in parse_headings(content) {
+        sqlx::query("INSERT INTO note_headings (note_path, level, text, line) VALUES (?, ?, ?, ?)")
+            .bind(note_path)
+            .bind(heading.level)
+            .bind(&heading.text)
+            .bind(heading.line)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| format!("Failed to index heading: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Fills `note_headings` from already-indexed content, for indexes created
+/// before the table existed. Unchanged notes are never reindexed, so they
+/// would otherwise have no headings.
+pub(crate) async fn backfill_headings(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let rows = sqlx::query("SELECT path, content FROM notes")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    for row in &rows {
+        let content = row.get::<Option<String>, _>("content").unwrap_or_default();
+        index_headings(&mut tx, &row.get::<String, _>("path"), &content).await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit headings: {}", e))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultHeading {
+    pub path: String,
+    pub title: String,
+    pub text: String,
+    pub level: i32,
+    /// Zero-based, counted from the end of the frontmatter.
+    pub line: i32,
+}
+
+/// Headings across every note, for a vault-wide outline. Levels default to
+/// 1 through 6, and results are in path then line order.
+#[command]
+pub async fn markdown_list_all_headings(
+    index_path: String,
+    min_level: Option<i32>,
+    max_level: Option<i32>,
+) -> Result<Vec<VaultHeading>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query(r#"
+        SELECT h.note_path, n.title, h.text, h.level, h.line
+        FROM note_headings h
+        JOIN notes n ON n.path = h.note_path
+        WHERE h.level BETWEEN ? AND ?
+        ORDER BY h.note_path, h.line
+    "#)
+    .bind(min_level.unwrap_or(1))
+    .bind(max_level.unwrap_or(6))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to list headings: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| VaultHeading {
+            path: row.get::<String, _>("note_path"),
+            title: row.get::<String, _>("title"),
+            text: row.get::<String, _>("text"),
+            level: row.get::<i32, _>("level"),
+            line: row.get::<i32, _>("line"),
+        })
+        .collect())
+}
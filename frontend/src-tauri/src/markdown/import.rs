@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+use super::embeds::is_attachment_target;
+use super::indexer::reindex_file_with;
+use super::links::LinkResolver;
+use super::search::get_or_create_pool;
+use super::vault::{is_markdown_path, normalize_rel_path, resolve_in_vault, to_rel_path, write_atomic};
+
+/// What to do when an imported file's path is already taken in the vault.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportCollision {
+    /// Import as `name 1.md`, `name 2.md`, ...
+    #[default]
+    Rename,
+    Skip,
+    Overwrite,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportFolderOptions {
+    /// Vault folder to import into; the vault root when unset.
+    pub target_folder: Option<String>,
+    /// Also copy non-markdown files such as images and PDFs.
+    #[serde(default)]
+    pub include_attachments: bool,
+    #[serde(default)]
+    pub on_collision: ImportCollision,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportedFile {
+    /// Relative to the source folder or zip.
+    pub source: String,
+    /// Vault-relative destination.
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkippedImport {
+    pub source: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportFolderResult {
+    pub imported: Vec<ImportedFile>,
+    /// Files whose destination was taken, with where they ended up; for
+    /// `skip` they are listed under `skipped` instead.
+    pub conflicts: Vec<ImportedFile>,
+    pub skipped: Vec<SkippedImport>,
+    pub errors: Vec<String>,
+}
+
+/// `dir/name.md` -> `dir/name 1.md`, counting up until the path is free.
+pub(crate) fn free_path(vault_path: &str, rel_path: &str) -> Result<String, String> {
+    let (dir, name) = match rel_path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{}/", dir), name),
+        None => (String::new(), rel_path),
+    };
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    let mut n = 1;
+    loop {
+        let candidate = format!("{}{} {}{}", dir, stem, n, ext);
+        if !resolve_in_vault(vault_path, &candidate)?.exists() {
+            return Ok(candidate);
+        }
+        n += 1;
+    }
+}
+
+/// Dot-folders such as `.obsidian` or `.git`, and zip tooling leftovers.
+fn is_hidden(rel_path: &str) -> bool {
+    rel_path.split('/').any(|part| part.starts_with('.') || part == "__MACOSX")
+}
+
+/// Files under a folder, or entries of a `.zip`, listed as relative paths
+/// plus the key `read` takes for each.
+enum ImportSource {
+    Folder(PathBuf),
+    Zip(zip::ZipArchive<std::fs::File>),
+}
+
+impl ImportSource {
+    fn open(src_path: &str) -> Result<Self, String> {
+        let path = Path::new(src_path);
+        if path.is_dir() {
+            return Ok(ImportSource::Folder(path.to_path_buf()));
+        }
+        let is_zip = path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("zip"));
+        if !path.is_file() || !is_zip {
+            return Err(format!("Not a folder or .zip file: {}", src_path));
+        }
+        let file = std::fs::File::open(path).map_err(|e| format!("Failed to open zip: {}", e))?;
+        let archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip: {}", e))?;
+        Ok(ImportSource::Zip(archive))
+    }
+
+    fn files(&mut self) -> Vec<(String, String)> {
+        let mut files: Vec<(String, String)> = match self {
+            ImportSource::Folder(root) => walkdir::WalkDir::new(&*root)
+                .min_depth(1)
+                .into_iter()
+                .flatten()
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| {
+                    let rel = to_rel_path(root, entry.path());
+                    (rel.clone(), rel)
+                })
+                .collect(),
+            ImportSource::Zip(archive) => (0..archive.len())
+                .filter_map(|i| {
+                    let entry = archive.by_index(i).ok()?;
+                    let name = entry.enclosed_name()?;
+                    let rel = name.to_string_lossy().replace('\\', "/");
+                    (!entry.is_dir()).then(|| (rel, entry.name().to_string()))
+                })
+                .collect(),
+        };
+        files.sort();
+        files
+    }
+
+    fn read(&mut self, key: &str) -> Result<Vec<u8>, String> {
+        match self {
+            ImportSource::Folder(root) => {
+                std::fs::read(root.join(key)).map_err(|e| format!("Failed to read file: {}", e))
+            }
+            ImportSource::Zip(archive) => {
+                let mut entry = archive
+                    .by_name(key)
+                    .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+                let mut bytes = Vec::new();
+                entry
+                    .read_to_end(&mut bytes)
+                    .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// Copies one file to `wanted`, or to a free name next to it. Returns the
+/// destination and whether `wanted` was taken, or `None` when skipped.
+fn copy_into_vault(
+    source: &mut ImportSource,
+    vault_path: &str,
+    key: &str,
+    wanted: &str,
+    on_collision: ImportCollision,
+) -> Result<Option<(String, bool)>, String> {
+    let wanted = normalize_rel_path(wanted)?;
+    let collided = resolve_in_vault(vault_path, &wanted)?.exists();
+    let dest = match on_collision {
+        ImportCollision::Skip if collided => return Ok(None),
+        ImportCollision::Rename if collided => free_path(vault_path, &wanted)?,
+        _ => wanted,
+    };
+
+    let bytes = source.read(key)?;
+    let full_path = resolve_in_vault(vault_path, &dest)?;
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+    }
+    write_atomic(&full_path, bytes)?;
+    Ok(Some((dest, collided)))
+}
+
+/// Copies notes, and optionally attachments, from an external folder or a
+/// `.zip` into the vault, keeping their folder layout under
+/// `target_folder`, then indexes the imported notes. Dot-folders are left
+/// out. Renamed notes keep their content as is, so links to them by file
+/// name from other imported notes need fixing by hand.
+#[command]
+pub async fn markdown_import_folder(
+    src_path: String,
+    vault_path: String,
+    index_path: String,
+    options: Option<ImportFolderOptions>,
+) -> Result<ImportFolderResult, String> {
+    let options = options.unwrap_or_default();
+    let target_folder = normalize_rel_path(options.target_folder.as_deref().unwrap_or(""))?;
+    let vault_root = std::fs::canonicalize(&vault_path).map_err(|e| format!("Failed to open vault: {}", e))?;
+    if std::fs::canonicalize(&src_path).is_ok_and(|src| src.starts_with(&vault_root)) {
+        return Err("Cannot import from inside the vault".to_string());
+    }
+
+    let pool = get_or_create_pool(&index_path).await?;
+    let mut source = ImportSource::open(&src_path)?;
+    let mut result = ImportFolderResult::default();
+    let mut notes = Vec::new();
+
+    for (rel, key) in source.files() {
+        let is_note = is_markdown_path(&rel);
+        let reason = if is_hidden(&rel) {
+            Some("Hidden file")
+        } else if !is_note && !is_attachment_target(&rel) {
+            Some("Not a note or attachment")
+        } else if !is_note && !options.include_attachments {
+            Some("Attachments not included")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            result.skipped.push(SkippedImport {
+                source: rel,
+                reason: reason.to_string(),
+            });
+            continue;
+        }
+
+        let wanted = if target_folder.is_empty() { rel.clone() } else { format!("{}/{}", target_folder, rel) };
+        let copied = copy_into_vault(&mut source, &vault_path, &key, &wanted, options.on_collision);
+        match copied {
+            Ok(Some((path, collided))) => {
+                if collided {
+                    result.conflicts.push(ImportedFile {
+                        source: rel.clone(),
+                        path: path.clone(),
+                    });
+                }
+                if is_note {
+                    notes.push(path.clone());
+                }
+                result.imported.push(ImportedFile { source: rel, path });
+            }
+            Ok(None) => result.skipped.push(SkippedImport {
+                source: rel,
+                reason: "Already exists".to_string(),
+            }),
+            Err(e) => result.errors.push(format!("{}: {}", rel, e)),
+        }
+    }
+
+    let mut resolver = LinkResolver::load(&pool).await?;
+    for path in notes {
+        if let Err(e) = reindex_file_with(&pool, &vault_path, &path, &mut resolver).await {
+            result.errors.push(format!("{}: {}", path, e));
+        }
+    }
+
+    Ok(result)
+}
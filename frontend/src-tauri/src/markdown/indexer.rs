@@ -0,0 +1,322 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Sqlite};
+use tauri::command;
+
+use super::frontmatter::{parse_frontmatter, string_list};
+use super::headings::parse_heading_line;
+use super::links::LinkResolver;
+use super::search::{get_or_create_pool, upsert_note, upsert_note_with, NoteRecord};
+use super::vault::{is_markdown_path, resolve_in_vault, walk_vault, VaultConfig};
+
+/// Notes above this many bytes are flagged as large unless the vault sets
+/// `largeNoteThreshold`.
+pub const DEFAULT_LARGE_NOTE_BYTES: u64 = 1024 * 1024;
+
+/// Longest excerpt kept for list views, in characters.
+pub const EXCERPT_CHARS: usize = 160;
+
+/// Checksum used for change detection on every platform.
+///
+/// Line endings are normalised before hashing: `\r\n` and lone `\r` both
+/// become `\n`, so a note checked out with CRLF on Windows hashes the same
+/// as its LF copy elsewhere. The digest is SHA-256, hex-encoded in lowercase.
+pub fn compute_checksum(content: &str) -> String {
+    let normalized = normalize_line_endings(content);
+    let digest = Sha256::digest(normalized.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn normalize_line_endings(content: &str) -> String {
+    content.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Collects a note's tags in order of first appearance: the frontmatter
+/// `tags` list, then inline `#tags` in the body, matching the frontend's
+/// `extractTags`. Inline tags must follow whitespace or start a line, so URL
+/// fragments and `a#b` don't count, and fenced or inline code is skipped.
+/// Nested tags like `#area/subarea` are kept whole; all-digit tokens such as
+/// `#123` are not tags.
+pub fn extract_tags(content: &str) -> Vec<String> {
+    let (frontmatter, body) = parse_frontmatter(content);
+    let mut tags: Vec<String> = Vec::new();
+    let mut add = |tag: &str| {
+        let tag = tag.trim().trim_start_matches('#').trim_matches('/');
+        if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    };
+
+    for tag in string_list(&frontmatter, "tags") {
+        add(&tag);
+    }
+
+    let mut in_fence = false;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut previous: Option<char> = None;
+        for (index, c) in line.char_indices() {
+            if c == '`' {
+                in_code = !in_code;
+            } else if c == '#' && !in_code && previous.map_or(true, char::is_whitespace) {
+                let tag: String = line[index + 1..]
+                    .chars()
+                    .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '/'))
+                    .collect();
+                add(&tag);
+            }
+            previous = Some(c);
+        }
+    }
+
+    tags
+}
+
+/// `---`, `***` or `___`, possibly spaced out.
+fn is_thematic_break(line: &str) -> bool {
+    let marks: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 && ['-', '*', '_'].iter().any(|&m| marks.chars().all(|c| c == m))
+}
+
+/// The first paragraph of a note body for list previews, with whitespace
+/// collapsed and cut to `max_chars` at a word boundary. Headings, fenced
+/// code, rules and HTML comments are passed over, so a note that is only a
+/// heading or only frontmatter has no excerpt.
+pub fn note_excerpt(body: &str, max_chars: usize) -> Option<String> {
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+    for line in body.lines() {
+        let trimmed = line.trim();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if is_fence {
+            in_fence = !in_fence;
+        }
+        let skipped = is_fence
+            || in_fence
+            || trimmed.is_empty()
+            || parse_heading_line(trimmed).is_some()
+            || is_thematic_break(trimmed)
+            || trimmed.starts_with("<!--") && trimmed.ends_with("-->");
+        if !skipped {
+            paragraph.push(trimmed);
+        } else if !paragraph.is_empty() {
+            break;
+        }
+    }
+
+    let text = paragraph.join(" ").split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    if text.chars().count() <= max_chars {
+        return Some(text);
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > 0 && !text[cut.len()..].starts_with(' ') => &cut[..space],
+        _ => cut.as_str(),
+    };
+    Some(format!("{}…", cut.trim_end_matches([' ', ',', ';', ':', '.'])))
+}
+
+/// Derives the full index record for a note from its raw file content, the
+/// same way the frontend does before calling `markdown_index_note`.
+pub fn note_record_from_content(rel_path: &str, raw: &str) -> NoteRecord {
+    let (frontmatter, body) = parse_frontmatter(raw);
+
+    let title = frontmatter
+        .get("title")
+        .and_then(|t| t.as_str())
+        .map(|t| t.to_string())
+        .unwrap_or_else(|| {
+            let name = rel_path.rsplit('/').next().unwrap_or(rel_path);
+            name.strip_suffix(".md").unwrap_or(name).to_string()
+        });
+    let aliases = string_list(&frontmatter, "aliases");
+
+    NoteRecord {
+        path: rel_path.to_string(),
+        title,
+        content: body.to_string(),
+        frontmatter: serde_json::Value::Object(frontmatter.clone()).to_string(),
+        tags: serde_json::to_string(&extract_tags(raw)).unwrap_or_else(|_| "[]".to_string()),
+        aliases: serde_json::to_string(&aliases).unwrap_or_else(|_| "[]".to_string()),
+        word_count: body.split_whitespace().count() as i32,
+        checksum: compute_checksum(raw),
+    }
+}
+
+/// The vault's `largeNoteThreshold` in bytes (0 turns the check off) and
+/// `largeNoteMetadataOnly`, which indexes large notes without their body.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LargeNoteLimit {
+    pub threshold: u64,
+    pub metadata_only: bool,
+}
+
+impl LargeNoteLimit {
+    pub(crate) fn load(vault_path: &str) -> Self {
+        let settings = VaultConfig::load(vault_path).map(|config| config.settings);
+        let setting = |key: &str| settings.as_ref().and_then(|s| s.get(key).cloned());
+        LargeNoteLimit {
+            threshold: setting("largeNoteThreshold")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(DEFAULT_LARGE_NOTE_BYTES),
+            metadata_only: setting("largeNoteMetadataOnly")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        }
+    }
+
+    fn exceeded_by(&self, size: u64) -> bool {
+        self.threshold > 0 && size > self.threshold
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LargeNote {
+    pub path: String,
+    pub size: u64,
+    pub threshold: u64,
+    /// Indexed with its title, frontmatter and tags but no body.
+    pub metadata_only: bool,
+}
+
+pub(crate) async fn reindex_file(
+    pool: &Pool<Sqlite>,
+    vault_path: &str,
+    rel_path: &str,
+) -> Result<NoteRecord, String> {
+    reindex_file_checked(pool, vault_path, rel_path, None).await.map(|(record, _)| record)
+}
+
+/// `reindex_file` for batches, sharing one `LinkResolver` across notes.
+pub(crate) async fn reindex_file_with(
+    pool: &Pool<Sqlite>,
+    vault_path: &str,
+    rel_path: &str,
+    resolver: &mut LinkResolver,
+) -> Result<NoteRecord, String> {
+    reindex_file_checked(pool, vault_path, rel_path, Some(resolver)).await.map(|(record, _)| record)
+}
+
+/// Reindexes a note and reports it when it is over the vault's size limit.
+/// With `largeNoteMetadataOnly` such a note is stored without its body, so
+/// it stays out of full-text search and its links aren't indexed, but its
+/// word count is still the real one.
+pub(crate) async fn reindex_file_checked(
+    pool: &Pool<Sqlite>,
+    vault_path: &str,
+    rel_path: &str,
+    resolver: Option<&mut LinkResolver>,
+) -> Result<(NoteRecord, Option<LargeNote>), String> {
+    let full_path = resolve_in_vault(vault_path, rel_path)?;
+    let raw = std::fs::read_to_string(&full_path)
+        .map_err(|e| format!("Failed to read note: {}", e))?;
+
+    let mut record = note_record_from_content(rel_path, &raw);
+    let limit = LargeNoteLimit::load(vault_path);
+    let large = limit.exceeded_by(raw.len() as u64).then(|| LargeNote {
+        path: rel_path.to_string(),
+        size: raw.len() as u64,
+        threshold: limit.threshold,
+        metadata_only: limit.metadata_only,
+    });
+    if let Some(large) = &large {
+        log::warn!("{} is {} bytes, over the {} byte large note threshold", rel_path, large.size, large.threshold);
+        if large.metadata_only {
+            record.content = String::new();
+        }
+    }
+
+    match resolver {
+        Some(resolver) => upsert_note_with(pool, &record, resolver).await?,
+        None => upsert_note(pool, &record).await?,
+    }
+    Ok((record, large))
+}
+
+/// Notes above `threshold_bytes`, or the vault's `largeNoteThreshold`,
+/// largest first. Sizes come from the filesystem, so nothing is read.
+#[command]
+pub async fn markdown_check_large_notes(
+    vault_path: String,
+    threshold_bytes: Option<u64>,
+) -> Result<Vec<LargeNote>, String> {
+    let mut limit = LargeNoteLimit::load(&vault_path);
+    if let Some(threshold) = threshold_bytes {
+        limit.threshold = threshold;
+    }
+
+    let mut notes: Vec<LargeNote> = walk_vault(&vault_path)
+        .into_iter()
+        .filter(|(rel_path, entry)| entry.file_type().is_file() && is_markdown_path(rel_path))
+        .filter_map(|(path, entry)| {
+            let size = entry.metadata().ok()?.len();
+            limit.exceeded_by(size).then_some(LargeNote {
+                path,
+                size,
+                threshold: limit.threshold,
+                metadata_only: limit.metadata_only,
+            })
+        })
+        .collect();
+    notes.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    Ok(notes)
+}
+
+#[command]
+pub async fn markdown_compute_checksum(vault_path: String, rel_path: String) -> Result<String, String> {
+    let full_path = resolve_in_vault(&vault_path, &rel_path)?;
+    let raw = std::fs::read_to_string(&full_path)
+        .map_err(|e| format!("Failed to read note: {}", e))?;
+    Ok(compute_checksum(&raw))
+}
+
+#[command]
+pub async fn markdown_reindex_note(
+    vault_path: String,
+    index_path: String,
+    rel_path: String,
+) -> Result<String, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let record = reindex_file(&pool, &vault_path, &rel_path).await?;
+    Ok(record.checksum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_skip_fenced_and_inline_code() {
+        let content = "#real\n```\n#backtick_fence\n```\n~~~\n#tilde_fence\n~~~\nsee `#inline` and #after\n";
+        assert_eq!(extract_tags(content), vec!["real", "after"]);
+    }
+
+    #[test]
+    fn tags_need_a_word_boundary() {
+        let content = "a#b https://example.com/page#section #123 #2024-plan\n";
+        assert_eq!(extract_tags(content), vec!["2024-plan"]);
+    }
+
+    #[test]
+    fn nested_tags_are_kept_whole() {
+        assert_eq!(extract_tags("#area/sub and #area/sub/deeper/"), vec!["area/sub", "area/sub/deeper"]);
+    }
+
+    #[test]
+    fn frontmatter_tags_come_first_without_repeats() {
+        let content = "---\ntags: [beta, alpha]\n---\n#gamma #alpha\n";
+        assert_eq!(extract_tags(content), vec!["beta", "alpha", "gamma"]);
+    }
+}
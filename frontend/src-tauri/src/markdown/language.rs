@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use super::frontmatter::split_frontmatter;
+
+/// Below this many letters of prose, detection is reported as unreliable
+/// whatever the detector says.
+const MIN_LETTERS: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LanguageDetection {
+    /// ISO 639-3, e.g. `eng`. `None` when the note is too short or too
+    /// ambiguous to call.
+    pub language: Option<String>,
+    /// 0 to 1, reported even when `language` is withheld.
+    pub confidence: f64,
+}
+
+/// The note's prose: frontmatter, fenced and inline code and URLs are
+/// dropped so they don't skew detection towards English.
+fn prose(content: &str) -> String {
+    let (_, body) = split_frontmatter(content);
+    let mut text = String::new();
+    let mut in_fence = false;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        for (i, part) in line.split('`').enumerate() {
+            if i % 2 == 1 {
+                continue;
+            }
+            for word in part.split_whitespace().filter(|w| !w.contains("://")) {
+                text.push_str(word);
+                text.push(' ');
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+pub fn detect_language(content: &str) -> LanguageDetection {
+    let text = prose(content);
+    let Some(info) = whatlang::detect(&text) else {
+        return LanguageDetection { language: None, confidence: 0.0 };
+    };
+
+    let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+    let reliable = info.is_reliable() && letters >= MIN_LETTERS;
+    LanguageDetection {
+        language: reliable.then(|| info.lang().code().to_string()),
+        confidence: info.confidence(),
+    }
+}
+
+#[command]
+pub async fn markdown_detect_language(content: String) -> Result<LanguageDetection, String> {
+    Ok(detect_language(&content))
+}
@@ -0,0 +1,2063 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, Pool, Row, Sqlite, Transaction};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tauri::command;
+
+use super::attachments::AttachmentResolver;
+use super::embeds::is_attachment_target;
+use super::frontmatter::split_frontmatter;
+use super::indexer::{compute_checksum, reindex_file, reindex_file_with};
+use super::search::{escape_like, get_or_create_pool};
+use super::tasks::is_list_marker;
+use super::vault::{resolve_in_vault, write_atomic};
+
+/// A `[[target#anchor|display]]` link found in a line of text. `start`/`end`
+/// are byte offsets covering the whole link, including the `!` of an embed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WikiLink {
+    pub target: String,
+    pub heading: Option<String>,
+    pub block_ref: Option<String>,
+    pub display_text: Option<String>,
+    pub is_embed: bool,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A `[text](target)` or `![alt](target)` link. Offsets as for `WikiLink`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkdownLink {
+    pub text: String,
+    pub target: String,
+    pub is_image: bool,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Scans text for wikilinks and embeds, mirroring the frontend's `parseLinks`.
+pub fn find_wikilinks(text: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut cursor = 0;
+
+    while let Some(open) = text[cursor..].find("[[") {
+        let open = cursor + open;
+        let Some(close) = text[open + 2..].find("]]") else {
+            break;
+        };
+        let close = open + 2 + close;
+        let inner = &text[open + 2..close];
+
+        if inner.contains('[') || inner.contains('\n') {
+            cursor = open + 2;
+            continue;
+        }
+
+        let (reference, display_text) = match inner.split_once('|') {
+            Some((reference, display)) => (reference, Some(display.trim().to_string())),
+            None => (inner, None),
+        };
+        let (target, anchor) = match reference.split_once('#') {
+            Some((target, anchor)) => (target.trim(), Some(anchor.trim())),
+            None => (reference.trim(), None),
+        };
+
+        if !target.is_empty() || anchor.is_some() {
+            let is_embed = open > 0 && text.as_bytes()[open - 1] == b'!';
+            let block_ref = anchor.and_then(|a| a.strip_prefix('^')).map(|b| b.to_string());
+            let heading = anchor.filter(|a| !a.starts_with('^')).map(|h| h.to_string());
+
+            links.push(WikiLink {
+                target: target.to_string(),
+                heading,
+                block_ref,
+                display_text: display_text.filter(|d| !d.is_empty()),
+                is_embed,
+                start: if is_embed { open - 1 } else { open },
+                end: close + 2,
+            });
+        }
+
+        cursor = close + 2;
+    }
+
+    links
+}
+
+/// Normalises a link target for lookup: forward slashes, no `./` or leading
+/// `/`, `..` collapsed and no `.md` extension.
+pub fn normalize_target(target: &str) -> String {
+    let target = target.trim().replace('\\', "/");
+    let mut parts: Vec<&str> = Vec::new();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    let joined = parts.join("/");
+    joined.strip_suffix(".md").unwrap_or(&joined).to_string()
+}
+
+/// The spelling of a link target stored in `note_links`, so `./note`,
+/// `note.md` and `note` compare equal: forward slashes, no `.` or empty
+/// segments, `dir/..` collapsed and no `.md` extension. Leading `..`
+/// segments are kept, since they climb from the source's folder.
+/// Attachment targets keep their extension, as they resolve to files.
+pub fn canonical_link_target(target: &str, link_type: &str) -> String {
+    let target = target.trim().replace('\\', "/");
+    let mut parts: Vec<&str> = Vec::new();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    let joined = parts.join("/");
+    let has_md = link_type != "attachment"
+        && joined.len() > 3
+        && joined.get(joined.len() - 3..).is_some_and(|ext| ext.eq_ignore_ascii_case(".md"));
+    if has_md {
+        joined[..joined.len() - 3].to_string()
+    } else {
+        joined
+    }
+}
+
+/// A stored target's lookup key in `note_links.target_key`. Lowercased here
+/// rather than in SQL, whose `lower()` only folds ASCII.
+pub fn link_target_key(target: &str) -> String {
+    target.trim().to_lowercase()
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// Resolves link targets against every indexed note. Loaded once per command
+/// or indexing batch, then kept in step with `insert_note`/`remove_note`, so
+/// graph walks and bulk reindexes don't round-trip to SQLite per link.
+#[derive(Default)]
+pub struct LinkResolver {
+    paths: HashMap<String, String>,
+    by_stem: HashMap<String, Vec<String>>,
+    by_name: HashMap<String, Vec<String>>,
+    /// The `by_name` keys each path is filed under, for `remove_note`.
+    names: HashMap<String, Vec<String>>,
+}
+
+impl LinkResolver {
+    pub async fn load<'e, E>(executor: E) -> Result<Self, String>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let rows = sqlx::query("SELECT path, title, aliases FROM notes")
+            .fetch_all(executor)
+            .await
+            .map_err(|e| format!("Failed to load notes: {}", e))?;
+
+        let mut resolver = LinkResolver::default();
+
+        for row in &rows {
+            let aliases: Vec<String> = row
+                .get::<Option<String>, _>("aliases")
+                .and_then(|a| serde_json::from_str(&a).ok())
+                .unwrap_or_default();
+            resolver.insert_note(&row.get::<String, _>("path"), &row.get::<String, _>("title"), &aliases);
+        }
+
+        Ok(resolver)
+    }
+
+    fn path_keys(path: &str) -> (String, String) {
+        let key = normalize_target(path).to_lowercase();
+        let stem = key.rsplit('/').next().unwrap_or(&key).to_string();
+        (key, stem)
+    }
+
+    /// Adds a note, or refreshes its title and aliases if already present.
+    pub fn insert_note(&mut self, path: &str, title: &str, aliases: &[String]) {
+        self.remove_note(path);
+
+        let (key, stem) = Self::path_keys(path);
+        self.by_stem.entry(stem).or_default().push(path.to_string());
+        let mut names = Vec::new();
+        for name in std::iter::once(title).chain(aliases.iter().map(String::as_str)) {
+            let name = name.trim().to_lowercase();
+            if !name.is_empty() && !names.contains(&name) {
+                self.by_name.entry(name.clone()).or_default().push(path.to_string());
+                names.push(name);
+            }
+        }
+        self.names.insert(path.to_string(), names);
+        self.paths.insert(key, path.to_string());
+    }
+
+    pub fn remove_note(&mut self, path: &str) {
+        let Some(names) = self.names.remove(path) else {
+            return;
+        };
+        let (key, stem) = Self::path_keys(path);
+        if self.paths.get(&key).is_some_and(|p| p == path) {
+            self.paths.remove(&key);
+        }
+        if let Some(paths) = self.by_stem.get_mut(&stem) {
+            paths.retain(|p| p != path);
+        }
+        for name in names {
+            if let Some(paths) = self.by_name.get_mut(&name) {
+                paths.retain(|p| p != path);
+            }
+        }
+    }
+
+    /// Returns every note the target could refer to, trying in order: a path
+    /// relative to the source note's folder, a vault-absolute path, a unique
+    /// filename, then titles and aliases. An empty result is a broken link.
+    pub fn candidates(&self, source_path: &str, target: &str) -> Vec<String> {
+        let normalized = normalize_target(target);
+        if normalized.is_empty() {
+            return Vec::new();
+        }
+        let lower = normalized.to_lowercase();
+
+        // Joined before normalising, so `../` can climb out of the source's folder.
+        let relative = normalize_target(&format!("{}/{}", parent_dir(source_path), target.trim())).to_lowercase();
+        for key in [&relative, &lower] {
+            if let Some(path) = self.paths.get(key) {
+                return vec![path.clone()];
+            }
+        }
+
+        let stem = lower.rsplit('/').next().unwrap_or(&lower);
+        if let Some(paths) = self.by_stem.get(stem) {
+            let suffix = format!("/{}", lower);
+            let mut matches: Vec<String> = paths
+                .iter()
+                .filter(|p| {
+                    let key = normalize_target(p).to_lowercase();
+                    !lower.contains('/') || key == lower || key.ends_with(&suffix)
+                })
+                .cloned()
+                .collect();
+            if !matches.is_empty() {
+                matches.sort();
+                return matches;
+            }
+        }
+
+        let mut matches = self.by_name.get(lower.trim()).cloned().unwrap_or_default();
+        matches.sort();
+        matches.dedup();
+        matches
+    }
+
+    /// Resolves to a single note, preferring the shallowest path when the
+    /// target is ambiguous.
+    pub fn resolve(&self, source_path: &str, target: &str) -> Option<String> {
+        self.candidates(source_path, target)
+            .into_iter()
+            .min_by_key(|p| (p.matches('/').count(), p.clone()))
+    }
+}
+
+/// Scans a line for inline markdown links and images. Reference-style links
+/// are not resolved.
+pub fn find_markdown_links(text: &str) -> Vec<MarkdownLink> {
+    let mut links = Vec::new();
+    let mut cursor = 0;
+
+    while let Some(close) = text[cursor..].find("](") {
+        let close = cursor + close;
+        let Some(open) = text[..close].rfind('[') else {
+            cursor = close + 2;
+            continue;
+        };
+        let Some(end) = text[close + 2..].find(')') else {
+            break;
+        };
+        let end = close + 2 + end;
+
+        // `[[wiki]](...)` is not a markdown link.
+        if open > 0 && text.as_bytes()[open - 1] == b'[' {
+            cursor = end + 1;
+            continue;
+        }
+
+        let raw_target = text[close + 2..end].trim();
+        // Drop an optional `"title"` and angle brackets around the target.
+        let target = raw_target
+            .split_once(" \"")
+            .map(|(t, _)| t)
+            .unwrap_or(raw_target)
+            .trim_start_matches('<')
+            .trim_end_matches('>');
+
+        let is_image = open > 0 && text.as_bytes()[open - 1] == b'!';
+        links.push(MarkdownLink {
+            text: text[open + 1..close].to_string(),
+            target: target.to_string(),
+            is_image,
+            start: if is_image { open - 1 } else { open },
+            end: end + 1,
+        });
+        cursor = end + 1;
+    }
+
+    links
+}
+
+pub fn is_external_url(target: &str) -> bool {
+    target.contains("://") || target.starts_with("mailto:") || target.starts_with("tel:")
+}
+
+/// Decodes `%XX` escapes as used in markdown link targets (`My%20Note.md`).
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Percent-encodes a link path for renderers that want strict URLs, leaving
+/// `/` and unreserved characters alone.
+pub fn percent_encode_path(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// A link as stored in `note_links`, its target in canonical form. `line`
+/// is zero-based within the indexed content. `link_type` is `wiki`, `embed`, `markdown` or `attachment`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedLink {
+    pub target: String,
+    pub link_type: String,
+    pub line: i32,
+}
+
+/// Markers around the section `markdown_update_backlink_sections` writes.
+const BACKLINKS_START: &str = "<!-- backlinks:start -->";
+const BACKLINKS_END: &str = "<!-- backlinks:end -->";
+
+/// Collects outgoing links to notes and attachments, skipping fenced code,
+/// external URLs and generated backlink sections, whose links only mirror
+/// other notes' and would otherwise make every link look mutual.
+pub fn extract_links(content: &str) -> Vec<ExtractedLink> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+    let mut in_backlinks = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if !in_backlinks && line.trim() == BACKLINKS_START {
+            in_backlinks = true;
+        } else if in_backlinks {
+            in_backlinks = line.trim() != BACKLINKS_END;
+            continue;
+        }
+
+        for link in find_wikilinks(line) {
+            if link.target.is_empty() {
+                continue;
+            }
+            let link_type = match (link.is_embed, is_attachment_target(&link.target)) {
+                (_, true) => "attachment",
+                (true, false) => "embed",
+                (false, false) => "wiki",
+            };
+            links.push(ExtractedLink {
+                target: canonical_link_target(&link.target, link_type),
+                link_type: link_type.to_string(),
+                line: index as i32,
+            });
+        }
+
+        for link in find_markdown_links(line) {
+            if is_external_url(&link.target) || link.target.starts_with('#') || link.target.is_empty() {
+                continue;
+            }
+            let target = percent_decode(link.target.split('#').next().unwrap_or(""));
+            let link_type = if link.is_image || is_attachment_target(&target) {
+                "attachment"
+            } else {
+                "markdown"
+            };
+            links.push(ExtractedLink {
+                target: canonical_link_target(&target, link_type),
+                link_type: link_type.to_string(),
+                line: index as i32,
+            });
+        }
+    }
+
+    links
+}
+
+/// A `http(s)://` URL in a note. `line` is zero-based within the content.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtractedUrl {
+    pub url: String,
+    pub line: i32,
+}
+
+/// The end of a URL starting at `rest`: up to whitespace, quotes or angle
+/// brackets, less trailing punctuation and any `)` that closes the
+/// surrounding markdown link rather than belonging to the URL.
+fn url_len(rest: &str) -> usize {
+    let mut url = rest
+        .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '`'))
+        .map_or(rest, |end| &rest[..end]);
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '*', '_', ']']);
+        let unbalanced = trimmed.ends_with(')') && trimmed.matches(')').count() > trimmed.matches('(').count();
+        let trimmed = if unbalanced { &trimmed[..trimmed.len() - 1] } else { trimmed };
+        if trimmed.len() == url.len() {
+            return url.len();
+        }
+        url = trimmed;
+    }
+}
+
+/// Collects `http://` and `https://` URLs, bare or as link destinations,
+/// skipping fenced code.
+pub fn extract_urls(content: &str) -> Vec<ExtractedUrl> {
+    let mut urls = Vec::new();
+    let mut in_fence = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        for (start, _) in line.match_indices("http") {
+            let rest = &line[start..];
+            let scheme = if rest.starts_with("https://") { 8 } else if rest.starts_with("http://") { 7 } else { continue };
+            if line[..start].chars().last().is_some_and(|c| c.is_alphanumeric()) {
+                continue;
+            }
+            let len = url_len(rest);
+            if len > scheme {
+                urls.push(ExtractedUrl {
+                    url: rest[..len].to_string(),
+                    line: index as i32,
+                });
+            }
+        }
+    }
+
+    urls
+}
+
+pub async fn index_urls(
+    tx: &mut Transaction<'_, Sqlite>,
+    note_path: &str,
+    content: &str,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM note_urls WHERE note_path = ?")
+        .bind(note_path)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to clear URLs: {}", e))?;
+
+    for url in extract_urls(content) {
+        sqlx::query("INSERT INTO note_urls (note_path, url, line) VALUES (?, ?, ?)")
+            .bind(note_path)
+            .bind(&url.url)
+            .bind(url.line)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| format!("Failed to index URL: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Fills `note_links.target_key` for indexes created before the column.
+pub(crate) async fn backfill_link_keys(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let rows = sqlx::query("SELECT id, target_path FROM note_links")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load links: {}", e))?;
+
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    for row in &rows {
+        sqlx::query("UPDATE note_links SET target_key = ? WHERE id = ?")
+            .bind(link_target_key(&row.get::<String, _>("target_path")))
+            .bind(row.get::<String, _>("id"))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to update link: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit link keys: {}", e))
+}
+
+/// Fills `note_urls` from already-indexed content, for indexes created
+/// before the table existed.
+pub(crate) async fn backfill_urls(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let rows = sqlx::query("SELECT path, content FROM notes")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    for row in &rows {
+        let content = row.get::<Option<String>, _>("content").unwrap_or_default();
+        index_urls(&mut tx, &row.get::<String, _>("path"), &content).await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit URLs: {}", e))
+}
+
+/// Rewrites a note's rows in `note_links`, then resolves the dangling links
+/// across the vault that the (possibly new) note could now satisfy: those
+/// whose target is its file name, a path ending in it, its title or an
+/// alias. `resolver` must already include the note.
+pub async fn index_links(
+    tx: &mut Transaction<'_, Sqlite>,
+    source_path: &str,
+    content: &str,
+    attachments: Option<&AttachmentResolver>,
+    resolver: &LinkResolver,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM note_links WHERE source_note_path = ?")
+        .bind(source_path)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to clear links: {}", e))?;
+
+    for (position, link) in extract_links(content).into_iter().enumerate() {
+        // Attachments resolve to the file's path without a vault-wide search,
+        // which would be too slow to run for every note indexed.
+        let resolved = if link.link_type == "attachment" {
+            attachments.and_then(|a| a.resolve_nearby(source_path, &link.target))
+        } else {
+            resolver.resolve(source_path, &link.target)
+        };
+
+        sqlx::query(r#"
+            INSERT INTO note_links (id, source_note_path, target_note_path, target_path, link_type, position_line, target_key)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#)
+        .bind(format!("{}:{}", source_path, position))
+        .bind(source_path)
+        .bind(&resolved)
+        .bind(&link.target)
+        .bind(&link.link_type)
+        .bind(link.line)
+        .bind(link_target_key(&link.target))
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to index link: {}", e))?;
+    }
+
+    let (_, stem) = LinkResolver::path_keys(source_path);
+    let mut names = resolver.names.get(source_path).cloned().unwrap_or_default();
+    names.push(stem.clone());
+    let names = serde_json::to_string(&names).map_err(|e| e.to_string())?;
+
+    let dangling = sqlx::query(r#"
+        SELECT id, source_note_path, target_path FROM note_links
+        WHERE target_note_path IS NULL AND link_type != 'attachment'
+          AND (target_key IN (SELECT value FROM json_each(?)) OR target_key LIKE ? ESCAPE '\')
+    "#)
+    .bind(&names)
+    .bind(format!("%/{}", escape_like(&stem)))
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| format!("Failed to load unresolved links: {}", e))?;
+
+    for row in &dangling {
+        let source = row.get::<String, _>("source_note_path");
+        let target = row.get::<String, _>("target_path");
+        if let Some(resolved) = resolver.resolve(&source, &target) {
+            sqlx::query("UPDATE note_links SET target_note_path = ? WHERE id = ?")
+                .bind(&resolved)
+                .bind(row.get::<String, _>("id"))
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| format!("Failed to resolve link: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LinkCounts {
+    pub link_count: i64,
+    pub backlink_count: i64,
+}
+
+/// Outgoing and incoming link counts for many notes in one grouped query.
+/// Every requested path is present in the result, with zeros when it has no
+/// links.
+#[command]
+pub async fn markdown_link_counts(
+    index_path: String,
+    paths: Vec<String>,
+) -> Result<HashMap<String, LinkCounts>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let wanted = serde_json::to_string(&paths).map_err(|e| e.to_string())?;
+    let rows = sqlx::query(r#"
+        WITH wanted(path) AS (SELECT value FROM json_each(?)),
+        counted(path, outgoing, incoming) AS (
+            SELECT source_note_path, 1, 0 FROM note_links
+            WHERE source_note_path IN (SELECT path FROM wanted)
+            UNION ALL
+            SELECT target_note_path, 0, 1 FROM note_links
+            WHERE target_note_path IN (SELECT path FROM wanted)
+        )
+        SELECT path, SUM(outgoing) AS link_count, SUM(incoming) AS backlink_count
+        FROM counted
+        GROUP BY path
+    "#)
+    .bind(&wanted)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to count links: {}", e))?;
+
+    let mut counts: HashMap<String, LinkCounts> = paths
+        .into_iter()
+        .map(|path| (path, LinkCounts::default()))
+        .collect();
+    for row in &rows {
+        counts.insert(row.get::<String, _>("path"), LinkCounts {
+            link_count: row.get::<i64, _>("link_count"),
+            backlink_count: row.get::<i64, _>("backlink_count"),
+        });
+    }
+
+    Ok(counts)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeletionImpact {
+    /// Notes linking to the deleted note, with how many of their links break.
+    pub broken_links: Vec<BrokenLinkSource>,
+    pub broken_link_count: i64,
+    /// Notes whose only incoming links come from the deleted note.
+    pub orphaned: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrokenLinkSource {
+    pub path: String,
+    pub count: i64,
+}
+
+/// What deleting `path` would do to the link graph, without deleting it.
+#[command]
+pub async fn markdown_deletion_impact(index_path: String, path: String) -> Result<DeletionImpact, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query(r#"
+        SELECT source_note_path, COUNT(*) AS count
+        FROM note_links
+        WHERE target_note_path = ? AND source_note_path != ?
+        GROUP BY source_note_path
+        ORDER BY source_note_path
+    "#)
+    .bind(&path)
+    .bind(&path)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load backlinks: {}", e))?;
+
+    let broken_links: Vec<BrokenLinkSource> = rows
+        .iter()
+        .map(|row| BrokenLinkSource {
+            path: row.get::<String, _>("source_note_path"),
+            count: row.get::<i64, _>("count"),
+        })
+        .collect();
+    let broken_link_count = broken_links.iter().map(|source| source.count).sum();
+
+    // Self-links don't keep a note connected, so they are ignored on both
+    // sides.
+    let orphaned = sqlx::query(r#"
+        SELECT target_note_path
+        FROM note_links
+        WHERE target_note_path IS NOT NULL
+          AND target_note_path != ?
+          AND source_note_path != target_note_path
+        GROUP BY target_note_path
+        HAVING SUM(source_note_path != ?) = 0
+        ORDER BY target_note_path
+    "#)
+    .bind(&path)
+    .bind(&path)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to find orphaned notes: {}", e))?
+    .iter()
+    .map(|row| row.get::<String, _>("target_note_path"))
+    .collect();
+
+    Ok(DeletionImpact {
+        broken_links,
+        broken_link_count,
+        orphaned,
+    })
+}
+
+/// Longest backlink snippet, in characters, unless the caller asks otherwise.
+const DEFAULT_SNIPPET_CHARS: usize = 160;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Backlink {
+    pub source_path: String,
+    pub source_title: String,
+    pub link_type: String,
+    /// The target as written in the source note.
+    pub target_path: String,
+    /// Zero-based, counted from the end of the source's frontmatter.
+    pub line: Option<i32>,
+    /// The linking line, shortened around the link. `None` when the source
+    /// changed on disk since it was indexed, since the line may have moved.
+    pub snippet: Option<String>,
+}
+
+/// Cuts `line` down to `max_chars` characters centred on the first
+/// occurrence of `needle`, marking cut ends with `…`.
+pub(crate) fn snippet_around(line: &str, needle: &str, max_chars: usize) -> String {
+    let chars: Vec<char> = line.trim().chars().collect();
+    if chars.len() <= max_chars {
+        return chars.into_iter().collect();
+    }
+
+    let lower: String = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    let center = match lower.find(&needle.to_lowercase()) {
+        // Lowercasing can change byte lengths, so count chars up to the hit.
+        Some(byte) if lower.chars().count() == chars.len() => lower[..byte].chars().count() + needle.chars().count() / 2,
+        _ => 0,
+    };
+    let start = center.saturating_sub(max_chars / 2).min(chars.len() - max_chars);
+    let end = start + max_chars;
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    snippet.extend(&chars[start..end]);
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// Notes linking to `path`, each with the linking line read from disk, like
+/// the linked mentions list of other editors.
+#[command]
+pub async fn markdown_get_backlinks(
+    vault_path: String,
+    index_path: String,
+    path: String,
+    snippet_chars: Option<usize>,
+) -> Result<Vec<Backlink>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let max_chars = snippet_chars.unwrap_or(DEFAULT_SNIPPET_CHARS).max(1);
+
+    let rows = sqlx::query(r#"
+        SELECT l.source_note_path, l.target_path, l.link_type, l.position_line,
+               n.title, n.checksum
+        FROM note_links l
+        JOIN notes n ON n.path = l.source_note_path
+        WHERE l.target_note_path = ? AND l.source_note_path != ?
+        ORDER BY l.source_note_path, l.position_line
+    "#)
+    .bind(&path)
+    .bind(&path)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load backlinks: {}", e))?;
+
+    // Each source is read once, and only used when it still matches the index.
+    let mut sources: HashMap<String, Option<Vec<String>>> = HashMap::new();
+    let mut backlinks = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let source_path = row.get::<String, _>("source_note_path");
+        let target_path = row.get::<String, _>("target_path");
+        let line = row.get::<Option<i32>, _>("position_line");
+
+        let lines = sources.entry(source_path.clone()).or_insert_with(|| {
+            let raw = resolve_in_vault(&vault_path, &source_path)
+                .ok()
+                .and_then(|p| std::fs::read_to_string(p).ok())?;
+            if Some(compute_checksum(&raw)) != row.get::<Option<String>, _>("checksum") {
+                return None;
+            }
+            let (_, body) = split_frontmatter(&raw);
+            Some(body.lines().map(|l| l.to_string()).collect())
+        });
+        let snippet = line
+            .and_then(|line| lines.as_ref()?.get(line as usize))
+            .map(|text| snippet_around(text, &target_path, max_chars));
+
+        backlinks.push(Backlink {
+            source_path,
+            source_title: row.get::<String, _>("title"),
+            link_type: row.get::<String, _>("link_type"),
+            target_path,
+            line,
+            snippet,
+        });
+    }
+
+    Ok(backlinks)
+}
+
+/// Longest cycle `markdown_find_cycles` looks for when no bound is given,
+/// and the most it will accept.
+const DEFAULT_MAX_CYCLE_LEN: usize = 5;
+const MAX_CYCLE_LEN: usize = 10;
+/// Caps on reported cycles and on DFS steps, so dense graphs return a
+/// partial answer instead of running for minutes.
+const MAX_CYCLES: usize = 1000;
+const MAX_CYCLE_SEARCH_STEPS: usize = 1_000_000;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkCycles {
+    /// Each cycle lists its notes in link order, starting from the
+    /// alphabetically first; the link back to it is implied.
+    pub cycles: Vec<Vec<String>>,
+    /// The search stopped early at one of its caps.
+    pub truncated: bool,
+}
+
+struct CycleSearch<'a> {
+    adjacency: &'a [Vec<usize>],
+    max_len: usize,
+    steps: usize,
+    cycles: Vec<Vec<usize>>,
+    truncated: bool,
+}
+
+impl CycleSearch<'_> {
+    /// Extends `stack` from its last node, only through nodes numbered above
+    /// `stack[0]`, so each cycle is found once, from its lowest node.
+    fn walk(&mut self, stack: &mut Vec<usize>, on_stack: &mut [bool]) {
+        let start = stack[0];
+        let current = *stack.last().unwrap_or(&start);
+        for &next in &self.adjacency[current] {
+            if self.truncated {
+                return;
+            }
+            self.steps += 1;
+            if self.steps > MAX_CYCLE_SEARCH_STEPS || self.cycles.len() >= MAX_CYCLES {
+                self.truncated = true;
+                return;
+            }
+            if next == start && stack.len() > 1 {
+                self.cycles.push(stack.clone());
+            } else if next > start && !on_stack[next] && stack.len() < self.max_len {
+                stack.push(next);
+                on_stack[next] = true;
+                self.walk(stack, on_stack);
+                on_stack[next] = false;
+                stack.pop();
+            }
+        }
+    }
+}
+
+/// Distinct resolved links between different notes, attachments excluded.
+async fn load_note_edges(pool: &Pool<Sqlite>) -> Result<Vec<(String, String)>, String> {
+    let rows = sqlx::query(r#"
+        SELECT DISTINCT l.source_note_path, l.target_note_path
+        FROM note_links l
+        JOIN notes n ON n.path = l.target_note_path
+        WHERE l.link_type != 'attachment' AND l.source_note_path != l.target_note_path
+    "#)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load links: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| (row.get::<String, _>("source_note_path"), row.get::<String, _>("target_note_path")))
+        .collect())
+}
+
+/// Cycles of two to `max_len` notes in the resolved link graph, e.g. A links
+/// to B, B to C and C back to A. Self-links and attachments are ignored.
+/// Shorter cycles come first.
+#[command]
+pub async fn markdown_find_cycles(index_path: String, max_len: Option<usize>) -> Result<LinkCycles, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let max_len = max_len.unwrap_or(DEFAULT_MAX_CYCLE_LEN).clamp(2, MAX_CYCLE_LEN);
+
+    let edges = load_note_edges(&pool).await?;
+    let mut nodes: Vec<&str> = edges.iter().flat_map(|(a, b)| [a.as_str(), b.as_str()]).collect();
+    nodes.sort_unstable();
+    nodes.dedup();
+    let id = |path: &str| nodes.binary_search(&path).unwrap_or_default();
+
+    let mut adjacency = vec![Vec::new(); nodes.len()];
+    for (source, target) in &edges {
+        adjacency[id(source)].push(id(target));
+    }
+    for targets in &mut adjacency {
+        targets.sort_unstable();
+    }
+
+    let mut search = CycleSearch {
+        adjacency: &adjacency,
+        max_len,
+        steps: 0,
+        cycles: Vec::new(),
+        truncated: false,
+    };
+    let mut on_stack = vec![false; nodes.len()];
+    for start in 0..nodes.len() {
+        if search.truncated {
+            break;
+        }
+        let mut stack = vec![start];
+        on_stack[start] = true;
+        search.walk(&mut stack, &mut on_stack);
+        on_stack[start] = false;
+    }
+
+    let mut cycles: Vec<Vec<String>> = search
+        .cycles
+        .iter()
+        .map(|cycle| cycle.iter().map(|&i| nodes[i].to_string()).collect())
+        .collect();
+    cycles.sort_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+
+    Ok(LinkCycles {
+        cycles,
+        truncated: search.truncated,
+    })
+}
+
+const DEFAULT_LINK_PATH_DEPTH: usize = 6;
+
+/// Shortest chain of links from `from_path` to `to_path`, both ends
+/// included, or `None` if there is none within `max_depth` links (default
+/// 6). Links are followed in either direction unless `directed` is set.
+#[command]
+pub async fn markdown_link_path(
+    index_path: String,
+    from_path: String,
+    to_path: String,
+    max_depth: Option<usize>,
+    directed: Option<bool>,
+) -> Result<Option<Vec<String>>, String> {
+    if from_path == to_path {
+        return Ok(Some(vec![from_path]));
+    }
+    let pool = get_or_create_pool(&index_path).await?;
+    let max_depth = max_depth.unwrap_or(DEFAULT_LINK_PATH_DEPTH);
+    let directed = directed.unwrap_or(false);
+
+    // Built once per call; the BFS then never touches the database.
+    let edges = load_note_edges(&pool).await?;
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (source, target) in &edges {
+        adjacency.entry(source).or_default().push(target);
+        if !directed {
+            adjacency.entry(target).or_default().push(source);
+        }
+    }
+    for neighbours in adjacency.values_mut() {
+        neighbours.sort_unstable();
+        neighbours.dedup();
+    }
+
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut queue: VecDeque<(&str, usize)> = VecDeque::from([(from_path.as_str(), 0)]);
+    while let Some((node, depth)) = queue.pop_front() {
+        if depth >= max_depth {
+            continue;
+        }
+        for &next in adjacency.get(node).into_iter().flatten() {
+            if next == from_path || previous.contains_key(next) {
+                continue;
+            }
+            previous.insert(next, node);
+            if next == to_path {
+                let mut path = vec![to_path.clone()];
+                let mut current = next;
+                while let Some(&before) = previous.get(current) {
+                    path.push(before.to_string());
+                    current = before;
+                }
+                path.reverse();
+                return Ok(Some(path));
+            }
+            queue.push_back((next, depth + 1));
+        }
+    }
+
+    Ok(None)
+}
+
+const DEFAULT_IMPORTANCE_ITERATIONS: usize = 20;
+const DEFAULT_DAMPING: f64 = 0.85;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteImportance {
+    pub path: String,
+    pub title: String,
+    /// PageRank; scores across the vault sum to 1.
+    pub score: f64,
+}
+
+/// PageRank over the resolved link graph. Every indexed note takes part,
+/// and notes without outgoing links spread their rank evenly over all
+/// notes. Scores replace whatever `note_importance` held before and are
+/// returned best first.
+#[command]
+pub async fn markdown_compute_importance(
+    index_path: String,
+    iterations: Option<usize>,
+    damping: Option<f64>,
+) -> Result<Vec<NoteImportance>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let iterations = iterations.unwrap_or(DEFAULT_IMPORTANCE_ITERATIONS).max(1);
+    let damping = damping.unwrap_or(DEFAULT_DAMPING).clamp(0.0, 1.0);
+
+    let notes = sqlx::query("SELECT path, title FROM notes ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+    let paths: Vec<String> = notes.iter().map(|row| row.get::<String, _>("path")).collect();
+    let ids: HashMap<&str, usize> = paths.iter().enumerate().map(|(i, p)| (p.as_str(), i)).collect();
+    let n = paths.len();
+
+    let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (source, target) in load_note_edges(&pool).await? {
+        if let (Some(&s), Some(&t)) = (ids.get(source.as_str()), ids.get(target.as_str())) {
+            outgoing[s].push(t);
+        }
+    }
+
+    let mut rank = vec![1.0 / n.max(1) as f64; n];
+    for _ in 0..iterations {
+        let dangling: f64 = (0..n).filter(|&i| outgoing[i].is_empty()).map(|i| rank[i]).sum();
+        let base = (1.0 - damping + damping * dangling) / n as f64;
+        let mut next = vec![base; n];
+        for (source, targets) in outgoing.iter().enumerate() {
+            let share = damping * rank[source] / targets.len().max(1) as f64;
+            for &target in targets {
+                next[target] += share;
+            }
+        }
+        rank = next;
+    }
+
+    let computed = chrono::Utc::now().to_rfc3339();
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    sqlx::query("DELETE FROM note_importance")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to clear importance: {}", e))?;
+    for (path, score) in paths.iter().zip(&rank) {
+        sqlx::query("INSERT INTO note_importance (note_path, score, computed) VALUES (?, ?, ?)")
+            .bind(path)
+            .bind(score)
+            .bind(&computed)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to store importance: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit importance: {}", e))?;
+
+    let mut scores: Vec<NoteImportance> = notes
+        .iter()
+        .zip(rank)
+        .map(|(row, score)| NoteImportance {
+            path: row.get::<String, _>("path"),
+            title: row.get::<String, _>("title"),
+            score,
+        })
+        .collect();
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    Ok(scores)
+}
+
+/// The scores stored by the last `markdown_compute_importance`, best first.
+/// Notes indexed since then are missing until it runs again.
+#[command]
+pub async fn markdown_get_importance(index_path: String, limit: Option<i64>) -> Result<Vec<NoteImportance>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query(r#"
+        SELECT i.note_path, n.title, i.score
+        FROM note_importance i
+        JOIN notes n ON n.path = i.note_path
+        ORDER BY i.score DESC, i.note_path
+        LIMIT ?
+    "#)
+    .bind(limit.unwrap_or(-1))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load importance: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| NoteImportance {
+            path: row.get::<String, _>("note_path"),
+            title: row.get::<String, _>("title"),
+            score: row.get::<f64, _>("score"),
+        })
+        .collect())
+}
+
+const DEFAULT_LOCAL_GRAPH_DEPTH: usize = 1;
+const MAX_LOCAL_GRAPH_DEPTH: usize = 5;
+const DEFAULT_LOCAL_GRAPH_NODES: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub path: String,
+    pub title: String,
+    pub word_count: i64,
+    /// Hops from the centre note, which is 0.
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// `wiki`, `embed` or `markdown`.
+    pub link_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// True when notes within `depth` hops were left out to stay under
+    /// `max_nodes`.
+    pub truncated: bool,
+}
+
+/// Notes within `depth` links of `path` (default 1, at most 5), following
+/// links in either direction, and every link between them. Nearer notes win
+/// when the `max_nodes` cap (default 200) is hit.
+#[command]
+pub async fn markdown_get_local_graph(
+    index_path: String,
+    path: String,
+    depth: Option<usize>,
+    max_nodes: Option<usize>,
+) -> Result<LocalGraph, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let depth = depth.unwrap_or(DEFAULT_LOCAL_GRAPH_DEPTH).min(MAX_LOCAL_GRAPH_DEPTH);
+    let max_nodes = max_nodes.unwrap_or(DEFAULT_LOCAL_GRAPH_NODES).max(1);
+
+    let notes = sqlx::query("SELECT path, title, word_count FROM notes")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+    let meta: HashMap<String, (String, i64)> = notes
+        .iter()
+        .map(|row| {
+            (
+                row.get::<String, _>("path"),
+                (row.get::<String, _>("title"), row.get::<Option<i64>, _>("word_count").unwrap_or(0)),
+            )
+        })
+        .collect();
+    if !meta.contains_key(&path) {
+        return Err(format!("Note not indexed: {}", path));
+    }
+
+    let rows = sqlx::query(r#"
+        SELECT DISTINCT l.source_note_path, l.target_note_path, l.link_type
+        FROM note_links l
+        JOIN notes n ON n.path = l.target_note_path
+        WHERE l.link_type != 'attachment' AND l.source_note_path != l.target_note_path
+    "#)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load links: {}", e))?;
+    let edges: Vec<GraphEdge> = rows
+        .iter()
+        .map(|row| GraphEdge {
+            source: row.get::<String, _>("source_note_path"),
+            target: row.get::<String, _>("target_note_path"),
+            link_type: row.get::<String, _>("link_type"),
+        })
+        .collect();
+
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &edges {
+        adjacency.entry(&edge.source).or_default().push(&edge.target);
+        adjacency.entry(&edge.target).or_default().push(&edge.source);
+    }
+    for neighbours in adjacency.values_mut() {
+        neighbours.sort_unstable();
+        neighbours.dedup();
+    }
+
+    let mut depths: HashMap<&str, usize> = HashMap::from([(path.as_str(), 0)]);
+    let mut order: Vec<&str> = vec![path.as_str()];
+    let mut truncated = false;
+    let mut queue: VecDeque<&str> = VecDeque::from([path.as_str()]);
+    'walk: while let Some(node) = queue.pop_front() {
+        let hops = depths[node];
+        if hops >= depth {
+            continue;
+        }
+        for &next in adjacency.get(node).into_iter().flatten() {
+            if depths.contains_key(next) {
+                continue;
+            }
+            if order.len() >= max_nodes {
+                truncated = true;
+                break 'walk;
+            }
+            depths.insert(next, hops + 1);
+            order.push(next);
+            queue.push_back(next);
+        }
+    }
+
+    let nodes = order
+        .iter()
+        .map(|&p| {
+            let (title, word_count) = meta[p].clone();
+            GraphNode {
+                path: p.to_string(),
+                title,
+                word_count,
+                depth: depths[p],
+            }
+        })
+        .collect();
+    let edges = edges
+        .iter()
+        .filter(|edge| depths.contains_key(edge.source.as_str()) && depths.contains_key(edge.target.as_str()))
+        .cloned()
+        .collect();
+
+    Ok(LocalGraph { nodes, edges, truncated })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// For Gephi, yEd and other GraphML readers.
+    Graphml,
+    /// Graphviz.
+    Dot,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphExport {
+    pub out_path: String,
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// A double-quoted DOT ID, so paths and titles can hold any character.
+fn dot_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\r', "")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// Writes the note link graph to `out_path` as GraphML or DOT for Gephi or
+/// Graphviz. Nodes are notes, keyed by path with a `title` attribute, and
+/// edges are resolved links with a `link_type` attribute. Notes with fewer
+/// than `min_degree` links in or out are left out, with their edges.
+#[command]
+pub async fn markdown_export_graph(
+    index_path: String,
+    out_path: String,
+    format: GraphFormat,
+    min_degree: Option<usize>,
+) -> Result<GraphExport, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let min_degree = min_degree.unwrap_or(0);
+
+    let notes = sqlx::query("SELECT path, title FROM notes ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+    let rows = sqlx::query(r#"
+        SELECT DISTINCT l.source_note_path, l.target_note_path, l.link_type
+        FROM note_links l
+        JOIN notes n ON n.path = l.target_note_path
+        WHERE l.link_type != 'attachment' AND l.source_note_path != l.target_note_path
+        ORDER BY l.source_note_path, l.target_note_path, l.link_type
+    "#)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load links: {}", e))?;
+    let edges: Vec<GraphEdge> = rows
+        .iter()
+        .map(|row| GraphEdge {
+            source: row.get::<String, _>("source_note_path"),
+            target: row.get::<String, _>("target_note_path"),
+            link_type: row.get::<String, _>("link_type"),
+        })
+        .collect();
+
+    let mut degree: HashMap<&str, usize> = HashMap::new();
+    for edge in &edges {
+        *degree.entry(&edge.source).or_default() += 1;
+        *degree.entry(&edge.target).or_default() += 1;
+    }
+    let nodes: Vec<(String, String)> = notes
+        .iter()
+        .map(|row| (row.get::<String, _>("path"), row.get::<String, _>("title")))
+        .filter(|(path, _)| degree.get(path.as_str()).copied().unwrap_or(0) >= min_degree)
+        .collect();
+    let kept: HashSet<&str> = nodes.iter().map(|(path, _)| path.as_str()).collect();
+    let edges: Vec<&GraphEdge> = edges
+        .iter()
+        .filter(|edge| kept.contains(edge.source.as_str()) && kept.contains(edge.target.as_str()))
+        .collect();
+
+    let mut out = String::new();
+    match format {
+        GraphFormat::Graphml => {
+            out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+            out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+            out.push_str("  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n");
+            out.push_str("  <key id=\"link_type\" for=\"edge\" attr.name=\"link_type\" attr.type=\"string\"/>\n");
+            out.push_str("  <graph id=\"vault\" edgedefault=\"directed\">\n");
+            for (path, title) in &nodes {
+                out.push_str(&format!(
+                    "    <node id=\"{}\"><data key=\"title\">{}</data></node>\n",
+                    xml_escape(path),
+                    xml_escape(title)
+                ));
+            }
+            for (index, edge) in edges.iter().enumerate() {
+                out.push_str(&format!(
+                    "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"><data key=\"link_type\">{}</data></edge>\n",
+                    index,
+                    xml_escape(&edge.source),
+                    xml_escape(&edge.target),
+                    xml_escape(&edge.link_type)
+                ));
+            }
+            out.push_str("  </graph>\n</graphml>\n");
+        }
+        GraphFormat::Dot => {
+            out.push_str("digraph vault {\n");
+            for (path, title) in &nodes {
+                out.push_str(&format!("  {} [label={}, title={}];\n", dot_quote(path), dot_quote(title), dot_quote(title)));
+            }
+            for edge in &edges {
+                out.push_str(&format!(
+                    "  {} -> {} [link_type={}];\n",
+                    dot_quote(&edge.source),
+                    dot_quote(&edge.target),
+                    dot_quote(&edge.link_type)
+                ));
+            }
+            out.push_str("}\n");
+        }
+    }
+
+    let out_file = std::path::Path::new(&out_path);
+    if let Some(parent) = out_file.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+    }
+    write_atomic(out_file, &out)?;
+
+    Ok(GraphExport {
+        out_path,
+        nodes: nodes.len(),
+        edges: edges.len(),
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SequenceItem {
+    pub path: String,
+    pub title: String,
+    /// List nesting of the line the link is on; 0 outside lists and for
+    /// top-level items.
+    pub depth: usize,
+}
+
+/// Depth of each link-bearing line: list items nest by indent, anything
+/// else sits at 0 and ends the list.
+fn list_depth(indents: &mut Vec<usize>, line: &str) -> usize {
+    let trimmed = line.trim_start();
+    let is_item = trimmed.split_once(' ').is_some_and(|(marker, _)| is_list_marker(marker));
+    if !is_item {
+        indents.clear();
+        return 0;
+    }
+    let indent: usize = line[..line.len() - trimmed.len()]
+        .chars()
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum();
+    while indents.last().is_some_and(|&last| last > indent) {
+        indents.pop();
+    }
+    if indents.last() != Some(&indent) {
+        indents.push(indent);
+    }
+    indents.len() - 1
+}
+
+/// The notes a map-of-content note links to, in document order, for
+/// reading them as a sequence. Nested list items keep their depth. Each
+/// note is listed once, at its first link; unresolved links, attachments,
+/// external URLs and links back to the MOC itself are left out.
+#[command]
+pub async fn markdown_reading_sequence(index_path: String, moc_path: String) -> Result<Vec<SequenceItem>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let content: String = sqlx::query_scalar("SELECT content FROM notes WHERE path = ?")
+        .bind(&moc_path)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load note: {}", e))?
+        .ok_or_else(|| format!("Note not indexed: {}", moc_path))?;
+    let resolver = LinkResolver::load(&pool).await?;
+    let titles: HashMap<String, String> = sqlx::query("SELECT path, title FROM notes")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?
+        .iter()
+        .map(|row| (row.get::<String, _>("path"), row.get::<String, _>("title")))
+        .collect();
+
+    let mut sequence: Vec<SequenceItem> = Vec::new();
+    let mut indents: Vec<usize> = Vec::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || trimmed.is_empty() {
+            continue;
+        }
+        let depth = list_depth(&mut indents, line);
+
+        let mut targets: Vec<(usize, String)> = find_wikilinks(line)
+            .into_iter()
+            .filter(|link| !link.target.is_empty() && !is_attachment_target(&link.target))
+            .map(|link| (link.start, link.target))
+            .collect();
+        targets.extend(
+            find_markdown_links(line)
+                .into_iter()
+                .filter(|link| !link.is_image && !is_external_url(&link.target) && !link.target.starts_with('#'))
+                .map(|link| (link.start, percent_decode(link.target.split('#').next().unwrap_or(""))))
+                .filter(|(_, target)| !target.is_empty() && !is_attachment_target(target)),
+        );
+        targets.sort_by_key(|(start, _)| *start);
+
+        for (_, target) in targets {
+            let Some(path) = resolver.resolve(&moc_path, &target) else {
+                continue;
+            };
+            if path == moc_path || sequence.iter().any(|item| item.path == path) {
+                continue;
+            }
+            sequence.push(SequenceItem {
+                title: titles.get(&path).cloned().unwrap_or_default(),
+                path,
+                depth,
+            });
+        }
+    }
+
+    Ok(sequence)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FollowedLink {
+    /// The note to open, the shallowest candidate when ambiguous; `None`
+    /// for a broken link.
+    pub path: Option<String>,
+    /// `#heading` or `#^block` anchor to scroll to, without the `#`.
+    pub anchor: Option<String>,
+    /// Every note the link could mean, sorted; more than one when ambiguous.
+    pub candidates: Vec<String>,
+    pub broken: bool,
+}
+
+/// Resolves a clicked link from `source_path` to the note it opens. The
+/// link may be given whole (`[[target#heading|display]]`, `[text](path.md)`)
+/// or as its bare target; relative targets resolve against the source's
+/// folder first, exactly as links are indexed.
+#[command]
+pub async fn markdown_follow_link(
+    index_path: String,
+    source_path: String,
+    link_text: String,
+) -> Result<FollowedLink, String> {
+    let link_text = link_text.trim();
+    let (target, anchor) = if let Some(link) = find_wikilinks(link_text).into_iter().next() {
+        (link.target, link.heading.or(link.block_ref.map(|b| format!("^{}", b))))
+    } else if let Some(link) = find_markdown_links(link_text).into_iter().next() {
+        let (target, anchor) = link.target.split_once('#').unwrap_or((&link.target, ""));
+        (percent_decode(target), Some(percent_decode(anchor)))
+    } else {
+        let text = link_text.split('|').next().unwrap_or(link_text);
+        let (target, anchor) = text.split_once('#').unwrap_or((text, ""));
+        (percent_decode(target), Some(anchor.to_string()))
+    };
+    if is_external_url(&target) {
+        return Err(format!("Not a note link: {}", target));
+    }
+    let anchor = anchor.filter(|a| !a.is_empty());
+
+    if target.trim().is_empty() {
+        // `[[#heading]]` points into the source note itself.
+        return Ok(FollowedLink {
+            path: Some(source_path.clone()),
+            anchor,
+            candidates: vec![source_path],
+            broken: false,
+        });
+    }
+
+    let pool = get_or_create_pool(&index_path).await?;
+    let resolver = LinkResolver::load(&pool).await?;
+    let candidates = resolver.candidates(&source_path, &target);
+    let path = resolver.resolve(&source_path, &target);
+
+    Ok(FollowedLink {
+        broken: path.is_none(),
+        path,
+        anchor,
+        candidates,
+    })
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NormalizeLinksResult {
+    /// Rows whose stored target was respelled.
+    pub rewritten: usize,
+    /// Rows whose resolved note changed, including links that now resolve.
+    pub resolved: usize,
+    /// Note links that still resolve to nothing.
+    pub unresolved: usize,
+}
+
+/// Rewrites every stored link target to its canonical form and resolves
+/// note links again, for indexes built before targets were canonicalised,
+/// where `./note`, `note.md` and `note` could land on different rows of a
+/// backlink count. Attachment links are only respelled.
+#[command]
+pub async fn markdown_normalize_links(index_path: String) -> Result<NormalizeLinksResult, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    let resolver = LinkResolver::load(&mut *tx).await?;
+    let rows = sqlx::query("SELECT id, source_note_path, target_note_path, target_path, link_type FROM note_links")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to load links: {}", e))?;
+
+    let mut result = NormalizeLinksResult::default();
+    for row in &rows {
+        let source = row.get::<String, _>("source_note_path");
+        let target = row.get::<String, _>("target_path");
+        let resolved = row.get::<Option<String>, _>("target_note_path");
+        let link_type = row.get::<String, _>("link_type");
+        let canonical = canonical_link_target(&target, &link_type);
+        let re_resolved = if link_type == "attachment" {
+            resolved.clone()
+        } else {
+            let found = resolver.resolve(&source, &canonical);
+            result.unresolved += found.is_none() as usize;
+            found
+        };
+        if canonical == target && re_resolved == resolved {
+            continue;
+        }
+
+        sqlx::query("UPDATE note_links SET target_path = ?, target_note_path = ?, target_key = ? WHERE id = ?")
+            .bind(&canonical)
+            .bind(&re_resolved)
+            .bind(link_target_key(&canonical))
+            .bind(row.get::<String, _>("id"))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to update link: {}", e))?;
+        result.rewritten += (canonical != target) as usize;
+        result.resolved += (re_resolved != resolved) as usize;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit links: {}", e))?;
+    Ok(result)
+}
+
+const MAX_LINK_SUGGESTIONS: usize = 3;
+const MIN_SUGGESTION_SCORE: f64 = 0.5;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkSuggestion {
+    pub path: String,
+    pub title: String,
+    /// 0 to 1, where 1 is the same name ignoring case.
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrokenLinkGroup {
+    /// The target as stored; spellings differing only in case are grouped.
+    pub target: String,
+    /// Notes containing the link, with how often each does.
+    pub sources: Vec<BrokenLinkSource>,
+    pub suggestions: Vec<LinkSuggestion>,
+}
+
+/// Edit distance over characters.
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + (ca != cb) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// `1 - distance / longer length`, ignoring case; 0 when too far apart in
+/// length to reach `MIN_SUGGESTION_SCORE`.
+fn name_similarity(a: &[char], b: &str) -> f64 {
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let longer = a.len().max(b.len());
+    if longer == 0 || (a.len().abs_diff(b.len()) as f64) > longer as f64 * (1.0 - MIN_SUGGESTION_SCORE) {
+        return 0.0;
+    }
+    1.0 - levenshtein(a, &b) as f64 / longer as f64
+}
+
+/// Unresolved note links grouped by target, each with up to three notes it
+/// probably meant: the closest titles, aliases or file names by edit
+/// distance, scoring at least 0.5. Most-used targets come first.
+#[command]
+pub async fn markdown_broken_links_with_suggestions(index_path: String) -> Result<Vec<BrokenLinkGroup>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let links = sqlx::query(r#"
+        SELECT target_path, source_note_path, COUNT(*) AS count
+        FROM note_links
+        WHERE target_note_path IS NULL AND link_type != 'attachment'
+        GROUP BY lower(target_path), source_note_path
+        ORDER BY lower(target_path), source_note_path
+    "#)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load broken links: {}", e))?;
+
+    let mut groups: Vec<BrokenLinkGroup> = Vec::new();
+    for row in &links {
+        let target = row.get::<String, _>("target_path");
+        let source = BrokenLinkSource {
+            path: row.get::<String, _>("source_note_path"),
+            count: row.get::<i64, _>("count"),
+        };
+        match groups.last_mut() {
+            Some(group) if group.target.to_lowercase() == target.to_lowercase() => group.sources.push(source),
+            _ => groups.push(BrokenLinkGroup {
+                target,
+                sources: vec![source],
+                suggestions: Vec::new(),
+            }),
+        }
+    }
+    if groups.is_empty() {
+        return Ok(groups);
+    }
+
+    let notes = sqlx::query("SELECT path, title, aliases FROM notes")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+    let names: Vec<(String, String, Vec<String>)> = notes
+        .iter()
+        .map(|row| {
+            let path = row.get::<String, _>("path");
+            let title = row.get::<String, _>("title");
+            let stem = path.rsplit('/').next().unwrap_or(&path);
+            let mut names = vec![title.clone(), stem.strip_suffix(".md").unwrap_or(stem).to_string()];
+            names.extend(
+                row.get::<Option<String>, _>("aliases")
+                    .and_then(|a| serde_json::from_str::<Vec<String>>(&a).ok())
+                    .unwrap_or_default(),
+            );
+            (path, title, names)
+        })
+        .collect();
+
+    for group in &mut groups {
+        let wanted: Vec<char> = group.target.rsplit('/').next().unwrap_or(&group.target).to_lowercase().chars().collect();
+        let mut suggestions: Vec<LinkSuggestion> = names
+            .iter()
+            .filter_map(|(path, title, names)| {
+                let score = names.iter().map(|name| name_similarity(&wanted, name)).fold(0.0, f64::max);
+                (score >= MIN_SUGGESTION_SCORE).then(|| LinkSuggestion {
+                    path: path.clone(),
+                    title: title.clone(),
+                    score,
+                })
+            })
+            .collect();
+        suggestions.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+        suggestions.truncate(MAX_LINK_SUGGESTIONS);
+        group.suggestions = suggestions;
+    }
+
+    groups.sort_by_key(|group| std::cmp::Reverse(group.sources.iter().map(|s| s.count).sum::<i64>()));
+    Ok(groups)
+}
+
+/// Points a broken link in `source_path` at `new_target`, rewriting every
+/// occurrence or, with `all: false`, only the first, then reindexes the
+/// note. `old_target` may be spelled as written or as stored; headings,
+/// display text and `#fragments` are kept. Returns false without touching
+/// the file when no link to `old_target` is left, as happens with a stale
+/// suggestion.
+#[command]
+pub async fn markdown_fix_broken_link(
+    vault_path: String,
+    index_path: String,
+    source_path: String,
+    old_target: String,
+    new_target: String,
+    all: Option<bool>,
+) -> Result<bool, String> {
+    let all = all.unwrap_or(true);
+    let new_target = new_target.trim();
+    if new_target.is_empty() {
+        return Err("New link target is empty".to_string());
+    }
+    let wiki_target = new_target.strip_suffix(".md").unwrap_or(new_target);
+    let old_note = canonical_link_target(&old_target, "wiki").to_lowercase();
+    let old_attachment = canonical_link_target(&old_target, "attachment").to_lowercase();
+    let matches = |target: &str, link_type: &str| {
+        let canonical = canonical_link_target(target, link_type).to_lowercase();
+        canonical == if link_type == "attachment" { old_attachment.as_str() } else { old_note.as_str() }
+    };
+
+    let full_path = resolve_in_vault(&vault_path, &source_path)?;
+    let raw = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read note: {}", e))?;
+
+    let mut content = String::with_capacity(raw.len());
+    let mut replaced = 0;
+    let mut in_fence = false;
+    for line in raw.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence || trimmed.starts_with("```") || trimmed.starts_with("~~~") || (replaced > 0 && !all) {
+            content.push_str(line);
+            continue;
+        }
+
+        // (start, end, replacement) byte ranges of link targets in the line.
+        let mut edits: Vec<(usize, usize, String)> = Vec::new();
+        for link in find_wikilinks(line) {
+            let link_type = if is_attachment_target(&link.target) { "attachment" } else { "wiki" };
+            if link.target.is_empty() || !matches(&link.target, link_type) {
+                continue;
+            }
+            let from = line[link.start..].find("[[").map(|i| link.start + i + 2).unwrap_or(link.start);
+            if let Some(offset) = line[from..link.end].find(&link.target) {
+                edits.push((from + offset, from + offset + link.target.len(), wiki_target.to_string()));
+            }
+        }
+        for link in find_markdown_links(line) {
+            if link.target.is_empty() || link.target.starts_with('#') || is_external_url(&link.target) {
+                continue;
+            }
+            let (path, fragment) = link.target.split_once('#').unwrap_or((&link.target, ""));
+            let decoded = percent_decode(path);
+            let link_type = if link.is_image || is_attachment_target(&decoded) { "attachment" } else { "markdown" };
+            if !matches(&decoded, link_type) {
+                continue;
+            }
+            let slice = &line[link.start..link.end];
+            let Some(at) = slice.find("](").and_then(|i| slice[i..].find(&link.target).map(|j| link.start + i + j)) else {
+                continue;
+            };
+            let mut replacement = percent_encode_path(new_target);
+            if !fragment.is_empty() {
+                replacement = format!("{}#{}", replacement, fragment);
+            }
+            edits.push((at, at + link.target.len(), replacement));
+        }
+
+        edits.sort_by_key(|edit| edit.0);
+        if !all {
+            edits.truncate(1);
+        }
+        let mut line = line.to_string();
+        for (start, end, replacement) in edits.into_iter().rev() {
+            line.replace_range(start..end, &replacement);
+            replaced += 1;
+        }
+        content.push_str(&line);
+    }
+
+    if replaced == 0 {
+        return Ok(false);
+    }
+    write_atomic(&full_path, &content)?;
+    let pool = get_or_create_pool(&index_path).await?;
+    reindex_file(&pool, &vault_path, &source_path).await?;
+    Ok(true)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BacklinkSectionsResult {
+    /// Notes whose section was added or rewritten.
+    pub updated: usize,
+    /// Notes whose section was dropped for having no backlinks left.
+    pub removed: usize,
+    pub unchanged: usize,
+    pub errors: Vec<String>,
+}
+
+/// The note without its generated backlink section, plus the text that
+/// follows the section, or `None` when it has no complete section.
+fn split_backlink_section(raw: &str) -> Option<(&str, &str)> {
+    let start = raw.find(BACKLINKS_START)?;
+    let end = start + raw[start..].find(BACKLINKS_END)? + BACKLINKS_END.len();
+    let before = &raw[..start];
+    // Only a marker on a line of its own counts.
+    if !before.is_empty() && !before.ends_with('\n') {
+        return None;
+    }
+    let after = raw[end..].strip_prefix("\r\n").or_else(|| raw[end..].strip_prefix('\n')).unwrap_or(&raw[end..]);
+    Some((before, after))
+}
+
+/// Keeps a `## Backlinks` list between HTML comment markers at the end of
+/// every note that others link to, rewriting it in place on each run and
+/// removing it from notes nothing links to any more. Running it again
+/// without link changes leaves every file alone.
+#[command]
+pub async fn markdown_update_backlink_sections(
+    vault_path: String,
+    index_path: String,
+) -> Result<BacklinkSectionsResult, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query(r#"
+        SELECT DISTINCT l.target_note_path AS target, l.source_note_path AS source, n.title
+        FROM note_links l
+        JOIN notes n ON n.path = l.source_note_path
+        WHERE l.target_note_path IS NOT NULL AND l.target_note_path != l.source_note_path
+          AND l.link_type != 'attachment'
+        ORDER BY n.title COLLATE NOCASE, l.source_note_path
+    "#)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load backlinks: {}", e))?;
+    let mut backlinks: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    for row in &rows {
+        backlinks
+            .entry(row.get::<String, _>("target"))
+            .or_default()
+            .push((row.get::<String, _>("source"), row.get::<String, _>("title")));
+    }
+
+    let paths: Vec<String> = sqlx::query_scalar("SELECT path FROM notes ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+
+    let mut resolver = LinkResolver::load(&pool).await?;
+    let mut result = BacklinkSectionsResult::default();
+    for path in paths {
+        let sources = backlinks.get(&path);
+        let edited = resolve_in_vault(&vault_path, &path).and_then(|full_path| {
+            let raw = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read note: {}", e))?;
+            let newline = if raw.contains("\r\n") { "\r\n" } else { "\n" };
+            let existing = split_backlink_section(&raw);
+            if sources.is_none() && existing.is_none() {
+                return Ok(None);
+            }
+
+            let (before, after) = existing.unwrap_or((&raw, ""));
+            let mut content = before.trim_end().to_string();
+            if let Some(sources) = sources {
+                let mut lines = vec![BACKLINKS_START.to_string(), "## Backlinks".to_string(), String::new()];
+                for (source, title) in sources {
+                    let target = source.strip_suffix(".md").unwrap_or(source);
+                    let stem = target.rsplit('/').next().unwrap_or(target);
+                    let title = title.replace(['[', ']', '|'], "");
+                    lines.push(if title.is_empty() || title == stem {
+                        format!("- [[{}]]", target)
+                    } else {
+                        format!("- [[{}|{}]]", target, title)
+                    });
+                }
+                lines.push(BACKLINKS_END.to_string());
+                if !content.is_empty() {
+                    content.push_str(newline);
+                    content.push_str(newline);
+                }
+                content.push_str(&lines.join(newline));
+            }
+            if !content.is_empty() {
+                content.push_str(newline);
+            }
+            content.push_str(after);
+
+            if content == raw {
+                return Ok(Some(false));
+            }
+            write_atomic(&full_path, &content).map(|_| Some(true))
+        });
+
+        match edited {
+            Ok(None) | Ok(Some(false)) => result.unchanged += 1,
+            Ok(Some(true)) => match reindex_file_with(&pool, &vault_path, &path, &mut resolver).await {
+                Ok(_) if sources.is_some() => result.updated += 1,
+                Ok(_) => result.removed += 1,
+                Err(e) => result.errors.push(format!("{}: {}", path, e)),
+            },
+            Err(e) => result.errors.push(format!("{}: {}", path, e)),
+        }
+    }
+
+    Ok(result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlOccurrence {
+    pub path: String,
+    /// Zero-based, counted from the end of the frontmatter.
+    pub line: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExternalUrl {
+    pub url: String,
+    /// Every place the URL appears, in path then line order.
+    pub occurrences: Vec<UrlOccurrence>,
+}
+
+/// Every distinct `http(s)://` URL in the vault with where it is used, for
+/// checking links for rot. URLs are compared exactly and listed in order.
+#[command]
+pub async fn markdown_list_external_urls(index_path: String) -> Result<Vec<ExternalUrl>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query("SELECT url, note_path, line FROM note_urls ORDER BY url, note_path, line")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to list URLs: {}", e))?;
+
+    let mut urls: Vec<ExternalUrl> = Vec::new();
+    for row in &rows {
+        let url = row.get::<String, _>("url");
+        let occurrence = UrlOccurrence {
+            path: row.get::<String, _>("note_path"),
+            line: row.get::<i32, _>("line"),
+        };
+        match urls.last_mut() {
+            Some(last) if last.url == url => last.occurrences.push(occurrence),
+            _ => urls.push(ExternalUrl {
+                url,
+                occurrences: vec![occurrence],
+            }),
+        }
+    }
+
+    Ok(urls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spellings_of_a_note_link_canonicalize_the_same() {
+        for target in ["./My Note", "My Note.md", "My Note", "./My Note.MD", "dir/../My Note"] {
+            assert_eq!(canonical_link_target(target, "wiki"), "My Note", "{}", target);
+        }
+
+        // Markdown link targets are percent-decoded before canonicalizing.
+        let links = extract_links("[a](./My%20Note.md) [b](My%20Note) [[./My Note]] [[My Note.md]]");
+        let targets: Vec<&str> = links.iter().map(|link| link.target.as_str()).collect();
+        assert_eq!(targets, vec!["My Note"; 4]);
+    }
+
+    #[test]
+    fn canonical_targets_keep_leading_parents_and_attachment_extensions() {
+        assert_eq!(canonical_link_target("../notes/./plan.md", "markdown"), "../notes/plan");
+        assert_eq!(canonical_link_target("a\\b\\c.md", "wiki"), "a/b/c");
+        assert_eq!(canonical_link_target("./img/diagram.md", "attachment"), "img/diagram.md");
+    }
+}
@@ -0,0 +1,386 @@
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use super::frontmatter::split_frontmatter;
+use super::indexer::{normalize_line_endings, reindex_file_with};
+use super::links::LinkResolver;
+use super::search::get_or_create_pool;
+use super::vault::{is_markdown_path, walk_vault, write_atomic};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LintFinding {
+    /// One-based, counting frontmatter lines.
+    pub line: usize,
+    /// `mixed-line-endings`, `heading-blank-line`, `bare-url`,
+    /// `trailing-whitespace` or `mixed-indentation`.
+    pub rule: String,
+    pub severity: LintSeverity,
+    pub message: String,
+}
+
+fn finding(line: usize, rule: &str, severity: LintSeverity, message: String) -> LintFinding {
+    LintFinding {
+        line,
+        rule: rule.to_string(),
+        severity,
+        message,
+    }
+}
+
+fn is_heading(line: &str) -> bool {
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&hashes) && line[hashes..].chars().next().map_or(true, |c| c == ' ' || c == '\t')
+}
+
+/// Byte offsets of `http://`/`https://` URLs that aren't already a link
+/// destination, an autolink, quoted or inside a code span.
+fn bare_urls(line: &str) -> Vec<usize> {
+    // `[label]: https://...` reference definitions are fine as they are.
+    if line.trim_start().starts_with('[') && line.contains("]:") {
+        return Vec::new();
+    }
+
+    let mut found = Vec::new();
+    for (start, _) in line.match_indices("http") {
+        let rest = &line[start..];
+        if !rest.starts_with("http://") && !rest.starts_with("https://") {
+            continue;
+        }
+        let before = &line[..start];
+        let in_code = before.matches('`').count() % 2 == 1;
+        let wrapped = before.ends_with(['(', '<', '"', '\'', '[']);
+        let mid_word = before.chars().last().is_some_and(|c| c.is_alphanumeric());
+        if !in_code && !wrapped && !mid_word {
+            found.push(start);
+        }
+    }
+    found
+}
+
+/// Checks a note for formatting that renders unpredictably across markdown
+/// tools. Pure, so it can run on an unsaved buffer. Fenced code blocks are
+/// only checked for line endings.
+pub fn lint_note(content: &str) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let crlf = lines.iter().filter(|l| l.ends_with("\r\n")).count();
+    let lf = lines.iter().filter(|l| l.ends_with('\n') && !l.ends_with("\r\n")).count();
+    if crlf > 0 && lf > 0 {
+        let minority_is_crlf = crlf < lf;
+        if let Some(index) = lines.iter().position(|l| l.ends_with("\r\n") == minority_is_crlf && l.ends_with('\n')) {
+            findings.push(finding(
+                index + 1,
+                "mixed-line-endings",
+                LintSeverity::Warning,
+                format!("Mixed line endings: {} CRLF and {} LF lines", crlf, lf),
+            ));
+        }
+    }
+
+    let body_start = match split_frontmatter(content) {
+        (Some(_), body) => content[..content.len() - body.len()].matches('\n').count(),
+        (None, _) => 0,
+    };
+
+    let mut in_fence = false;
+    let mut indent_style: Option<char> = None;
+    let mut previous_blank = true;
+    for (index, raw) in lines.iter().enumerate().skip(body_start) {
+        let number = index + 1;
+        let line = raw.trim_end_matches(['\r', '\n']);
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            previous_blank = false;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        if is_heading(line) && !previous_blank {
+            findings.push(finding(
+                number,
+                "heading-blank-line",
+                LintSeverity::Warning,
+                "Heading should be preceded by a blank line".to_string(),
+            ));
+        }
+
+        for start in bare_urls(line) {
+            let url: String = line[start..].chars().take_while(|c| !c.is_whitespace()).collect();
+            findings.push(finding(
+                number,
+                "bare-url",
+                LintSeverity::Info,
+                format!("Bare URL {}; wrap it in <...> or a link", url),
+            ));
+        }
+
+        // Exactly two trailing spaces is a hard line break, not a mistake.
+        let trailing = &line[line.trim_end().len()..];
+        if !trimmed.is_empty() && !trailing.is_empty() && trailing != "  " {
+            findings.push(finding(
+                number,
+                "trailing-whitespace",
+                LintSeverity::Info,
+                "Trailing whitespace".to_string(),
+            ));
+        }
+
+        let indent = &line[..line.len() - trimmed.len()];
+        if !trimmed.is_empty() && !indent.is_empty() {
+            let mixed_here = indent.contains(' ') && indent.contains('\t');
+            let style = indent.chars().next().unwrap_or(' ');
+            let expected = *indent_style.get_or_insert(style);
+            if mixed_here || style != expected {
+                findings.push(finding(
+                    number,
+                    "mixed-indentation",
+                    LintSeverity::Warning,
+                    format!(
+                        "Indented with {} where earlier lines use {}",
+                        if mixed_here { "tabs and spaces" } else if style == '\t' { "tabs" } else { "spaces" },
+                        if expected == '\t' { "tabs" } else { "spaces" }
+                    ),
+                ));
+            }
+        }
+
+        previous_blank = trimmed.is_empty();
+    }
+
+    findings.sort_by_key(|f| f.line);
+    findings
+}
+
+/// Fixes accepted by `autofix`.
+pub const AUTOFIXES: [&str; 4] = ["line-endings", "trailing-whitespace", "final-newline", "heading-blank-line"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutofixResult {
+    pub content: String,
+    /// Lines changed or inserted, plus one for a fixed final newline.
+    pub changes: usize,
+}
+
+/// Applies the named fixes from `AUTOFIXES`:
+///
+/// - `line-endings`: CRLF becomes LF;
+/// - `trailing-whitespace`: trimmed, except two-space hard line breaks;
+/// - `final-newline`: exactly one newline at the end;
+/// - `heading-blank-line`: blank lines before and after headings.
+///
+/// Frontmatter and fenced code are left alone, except for line endings.
+pub fn autofix(content: &str, fixes: &[String]) -> Result<AutofixResult, String> {
+    if let Some(unknown) = fixes.iter().find(|f| !AUTOFIXES.contains(&f.as_str())) {
+        return Err(format!("Unknown fix: {}", unknown));
+    }
+    let enabled = |fix: &str| fixes.iter().any(|f| f == fix);
+    let mut changes = 0;
+
+    let mut lines: Vec<(String, &str)> = content
+        .split_inclusive('\n')
+        .map(|raw| {
+            let text = raw.trim_end_matches(['\r', '\n']);
+            (text.to_string(), &raw[text.len()..])
+        })
+        .collect();
+
+    if enabled("line-endings") {
+        for (_, ending) in lines.iter_mut().filter(|(_, ending)| *ending == "\r\n") {
+            *ending = "\n";
+            changes += 1;
+        }
+    }
+    let crlf = lines.iter().filter(|(_, e)| *e == "\r\n").count();
+    let lf = lines.iter().filter(|(_, e)| *e == "\n").count();
+    let newline = if crlf > lf { "\r\n" } else { "\n" };
+
+    let body_start = match split_frontmatter(content) {
+        (Some(_), body) => content[..content.len() - body.len()].matches('\n').count(),
+        (None, _) => 0,
+    };
+
+    let mut fixed: Vec<(String, &str)> = Vec::with_capacity(lines.len());
+    let mut in_fence = false;
+    let mut after_heading = false;
+    for (index, (mut text, ending)) in lines.into_iter().enumerate() {
+        if index < body_start {
+            fixed.push((text, ending));
+            continue;
+        }
+        let trimmed = text.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if in_fence {
+            fixed.push((text, ending));
+            continue;
+        }
+
+        if enabled("trailing-whitespace") {
+            let kept = text.trim_end().len();
+            if kept < text.len() && (kept == 0 || &text[kept..] != "  ") {
+                text.truncate(kept);
+                changes += 1;
+            }
+        }
+
+        if enabled("heading-blank-line") {
+            let blank = text.trim().is_empty();
+            let heading = is_heading(&text);
+            let previous_blank = fixed.len() <= body_start || fixed.last().is_some_and(|(t, _)| t.trim().is_empty());
+            if !blank && !previous_blank && (heading || after_heading) {
+                if let Some(last) = fixed.last_mut() {
+                    if last.1.is_empty() {
+                        last.1 = newline;
+                    }
+                }
+                fixed.push((String::new(), newline));
+                changes += 1;
+            }
+            after_heading = heading;
+        }
+
+        fixed.push((text, ending));
+    }
+
+    if enabled("final-newline") && fixed.iter().any(|(t, _)| !t.is_empty()) {
+        let mut changed = false;
+        while fixed.last().is_some_and(|(t, _)| t.trim().is_empty()) {
+            fixed.pop();
+            changed = true;
+        }
+        if let Some(last) = fixed.last_mut() {
+            if last.1.is_empty() {
+                last.1 = newline;
+                changed = true;
+            }
+        }
+        if changed {
+            changes += 1;
+        }
+    }
+
+    let content = fixed.iter().flat_map(|(text, ending)| [text.as_str(), *ending]).collect();
+    Ok(AutofixResult { content, changes })
+}
+
+#[command]
+pub async fn markdown_autofix(content: String, fixes: Vec<String>) -> Result<AutofixResult, String> {
+    autofix(&content, &fixes)
+}
+
+#[command]
+pub async fn markdown_lint_note(content: String) -> Result<Vec<LintFinding>, String> {
+    Ok(lint_note(&content))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LineEndingInfo {
+    pub path: String,
+    pub crlf_lines: usize,
+    pub lf_lines: usize,
+    pub mixed: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NormalizeLineEndingsResult {
+    pub changed: usize,
+    pub unchanged: usize,
+    pub errors: Vec<String>,
+}
+
+/// Counts of `\r\n` and bare `\n` line ends.
+fn count_line_endings(content: &str) -> (usize, usize) {
+    let crlf = content.matches("\r\n").count();
+    (crlf, content.matches('\n').count() - crlf)
+}
+
+/// Notes on disk (not attachments) with any CRLF line ends, mixed or not,
+/// sorted by path.
+#[command]
+pub async fn markdown_line_ending_report(vault_path: String) -> Result<Vec<LineEndingInfo>, String> {
+    let mut notes = Vec::new();
+    for (rel_path, entry) in walk_vault(&vault_path) {
+        if !entry.file_type().is_file() || !is_markdown_path(&rel_path) {
+            continue;
+        }
+        let Ok(raw) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let (crlf_lines, lf_lines) = count_line_endings(&raw);
+        if crlf_lines > 0 {
+            notes.push(LineEndingInfo {
+                path: rel_path,
+                crlf_lines,
+                lf_lines,
+                mixed: lf_lines > 0,
+            });
+        }
+    }
+    notes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(notes)
+}
+
+/// Rewrites every note whose line ends differ from `to`, saving atomically
+/// and reindexing each one. Lone `\r` line ends become `to` as well.
+#[command]
+pub async fn markdown_normalize_line_endings(
+    vault_path: String,
+    index_path: String,
+    to: LineEnding,
+) -> Result<NormalizeLineEndingsResult, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let mut resolver = LinkResolver::load(&pool).await?;
+    let mut result = NormalizeLineEndingsResult::default();
+
+    for (rel_path, entry) in walk_vault(&vault_path) {
+        if !entry.file_type().is_file() || !is_markdown_path(&rel_path) {
+            continue;
+        }
+        let raw = match std::fs::read_to_string(entry.path()) {
+            Ok(raw) => raw,
+            Err(e) => {
+                result.errors.push(format!("{}: Failed to read note: {}", rel_path, e));
+                continue;
+            }
+        };
+        let lf = normalize_line_endings(&raw);
+        let normalized = match to {
+            LineEnding::Lf => lf,
+            LineEnding::Crlf => lf.replace('\n', "\r\n"),
+        };
+        if normalized == raw {
+            result.unchanged += 1;
+            continue;
+        }
+
+        let saved = match write_atomic(entry.path(), &normalized) {
+            Ok(()) => reindex_file_with(&pool, &vault_path, &rel_path, &mut resolver).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        match saved {
+            Ok(()) => result.changed += 1,
+            Err(e) => result.errors.push(format!("{}: {}", rel_path, e)),
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::command;
+
+use super::attachments::{collapse, relative_to};
+use super::import::free_path;
+use super::indexer::reindex_file;
+use super::links::{find_markdown_links, percent_decode, percent_encode_path};
+use super::search::get_or_create_pool;
+use super::vault::{resolve_in_vault, write_atomic, VaultConfig};
+
+const DEFAULT_ATTACHMENT_FOLDER: &str = "attachments";
+const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalizedImage {
+    pub url: String,
+    /// Vault-relative path the image was saved to.
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImageDownloadFailure {
+    pub url: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LocalizeImagesResult {
+    pub downloaded: Vec<LocalizedImage>,
+    pub failed: Vec<ImageDownloadFailure>,
+    pub links_updated: usize,
+}
+
+fn extension_for(content_type: &str) -> Option<&'static str> {
+    match content_type.split(';').next()?.trim() {
+        "image/png" => Some("png"),
+        "image/jpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        "image/svg+xml" => Some("svg"),
+        "image/avif" => Some("avif"),
+        "image/bmp" => Some("bmp"),
+        _ => None,
+    }
+}
+
+/// A file name from the URL's last path segment, reduced to characters that
+/// are safe in links and on every filesystem, with the content type's
+/// extension when the URL has none.
+fn image_file_name(url: &str, content_type: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let segment = percent_decode(path.trim_end_matches('/').rsplit('/').next().unwrap_or(""));
+    let mut name: String = segment
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '-' })
+        .collect();
+    name = name.trim_matches(['-', '.']).to_string();
+
+    let has_extension = name
+        .rsplit_once('.')
+        .is_some_and(|(stem, ext)| !stem.is_empty() && (1..=5).contains(&ext.len()));
+    match (has_extension, extension_for(content_type)) {
+        (true, _) => name,
+        (false, Some(ext)) if !name.is_empty() => format!("{}.{}", name, ext),
+        (false, Some(ext)) => format!("image.{}", ext),
+        (false, None) if !name.is_empty() => name,
+        (false, None) => "image".to_string(),
+    }
+}
+
+/// Downloads one image, refusing non-image responses and bodies over
+/// `MAX_IMAGE_BYTES`. Returns the bytes and the content type.
+async fn download_image(client: &reqwest::Client, url: &str) -> Result<(Vec<u8>, String), String> {
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Server responded {}", response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_lowercase();
+    if !content_type.starts_with("image/") {
+        return Err(format!("Not an image ({})", if content_type.is_empty() { "no content type" } else { &content_type }));
+    }
+    if response.content_length().is_some_and(|len| len as usize > MAX_IMAGE_BYTES) {
+        return Err(format!("Larger than {} MB", MAX_IMAGE_BYTES / (1024 * 1024)));
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Download failed: {}", e))? {
+        if bytes.len() + chunk.len() > MAX_IMAGE_BYTES {
+            return Err(format!("Larger than {} MB", MAX_IMAGE_BYTES / (1024 * 1024)));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok((bytes, content_type))
+}
+
+/// The folder images are saved to: `attachment_folder`, else the vault's
+/// `attachmentFolder` setting, else `attachments`. A folder starting with
+/// `./` is relative to the note.
+fn attachment_folder_for(vault_path: &str, rel_path: &str, attachment_folder: Option<String>) -> String {
+    let folder = attachment_folder
+        .or_else(|| {
+            VaultConfig::load(vault_path).and_then(|config| {
+                config.settings.get("attachmentFolder").and_then(|v| v.as_str()).map(|f| f.to_string())
+            })
+        })
+        .map(|f| f.trim().trim_end_matches('/').to_string())
+        .filter(|f| !f.is_empty() && f != "/")
+        .unwrap_or_else(|| DEFAULT_ATTACHMENT_FOLDER.to_string());
+
+    match folder.strip_prefix("./").or((folder == ".").then_some("")) {
+        Some(relative) => {
+            let parent = rel_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+            collapse(&format!("{}/{}", parent, relative))
+        }
+        None => collapse(&folder),
+    }
+}
+
+/// Downloads the remote images a note embeds with `![alt](https://...)`
+/// into the attachment folder, points the links at the local copies and
+/// reindexes the note. Each download has a 30 second timeout and a 20 MB
+/// cap; failed URLs are reported and their links left alone. An image
+/// already saved under the same name with the same bytes is reused.
+#[command]
+pub async fn markdown_localize_images(
+    vault_path: String,
+    index_path: String,
+    rel_path: String,
+    attachment_folder: Option<String>,
+) -> Result<LocalizeImagesResult, String> {
+    let full_path = resolve_in_vault(&vault_path, &rel_path)?;
+    let raw = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read note: {}", e))?;
+    let folder = attachment_folder_for(&vault_path, &rel_path, attachment_folder);
+
+    let mut urls: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    for line in raw.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        for link in find_markdown_links(line) {
+            let is_remote = link.target.starts_with("http://") || link.target.starts_with("https://");
+            if link.is_image && is_remote && !urls.contains(&link.target) {
+                urls.push(link.target);
+            }
+        }
+    }
+
+    let mut result = LocalizeImagesResult::default();
+    if urls.is_empty() {
+        return Ok(result);
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut local: HashMap<String, String> = HashMap::new();
+    for url in urls {
+        let saved = match download_image(&client, &url).await {
+            Ok((bytes, content_type)) => {
+                let name = image_file_name(&url, &content_type);
+                let wanted = if folder.is_empty() { name } else { format!("{}/{}", folder, name) };
+                resolve_in_vault(&vault_path, &wanted).and_then(|wanted_path| {
+                    if std::fs::read(&wanted_path).is_ok_and(|existing| existing == bytes) {
+                        return Ok(wanted);
+                    }
+                    let dest = if wanted_path.exists() { free_path(&vault_path, &wanted)? } else { wanted };
+                    let dest_path = resolve_in_vault(&vault_path, &dest)?;
+                    if let Some(parent) = dest_path.parent() {
+                        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+                    }
+                    write_atomic(&dest_path, &bytes)?;
+                    Ok(dest)
+                })
+            }
+            Err(e) => Err(e),
+        };
+        match saved {
+            Ok(path) => {
+                local.insert(url.clone(), path.clone());
+                result.downloaded.push(LocalizedImage { url, path });
+            }
+            Err(reason) => result.failed.push(ImageDownloadFailure { url, reason }),
+        }
+    }
+    if local.is_empty() {
+        return Ok(result);
+    }
+
+    let mut content = String::with_capacity(raw.len());
+    in_fence = false;
+    for line in raw.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence || trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            content.push_str(line);
+            continue;
+        }
+
+        let links = find_markdown_links(line);
+        let mut line = line.to_string();
+        for link in links.into_iter().rev() {
+            let Some(path) = local.get(&link.target).filter(|_| link.is_image) else {
+                continue;
+            };
+            let slice = &line[link.start..link.end];
+            let Some(at) = slice.find("](").and_then(|i| slice[i..].find(&link.target).map(|j| link.start + i + j)) else {
+                continue;
+            };
+            line.replace_range(at..at + link.target.len(), &percent_encode_path(&relative_to(&rel_path, path)));
+            result.links_updated += 1;
+        }
+        content.push_str(&line);
+    }
+
+    write_atomic(&full_path, &content)?;
+    let pool = get_or_create_pool(&index_path).await?;
+    reindex_file(&pool, &vault_path, &rel_path).await?;
+
+    Ok(result)
+}
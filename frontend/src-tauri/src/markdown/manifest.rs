@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashMap;
+use tauri::{command, AppHandle, Emitter};
+
+use super::indexer::{compute_checksum, reindex_file_with};
+use super::links::LinkResolver;
+use super::search::{get_or_create_pool, remove_note};
+use super::vault::{is_markdown_path, walk_vault};
+
+const MANIFEST_BATCH_SIZE: usize = 200;
+
+/// A note as found on disk. `size` is the file length in bytes; `checksum`
+/// matches `markdown_compute_checksum`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub checksum: String,
+    pub size: u64,
+}
+
+/// Payload of the `vault-manifest-batch` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestBatch {
+    pub vault_path: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ManifestSummary {
+    pub note_count: usize,
+    pub total_size: u64,
+}
+
+/// Hashes every note on disk, honouring the vault's ignore rules, and hands
+/// entries to `on_batch` in groups of `batch_size`.
+pub fn scan_manifest(vault_path: &str, batch_size: usize, mut on_batch: impl FnMut(Vec<ManifestEntry>)) -> ManifestSummary {
+    let mut summary = ManifestSummary { note_count: 0, total_size: 0 };
+    let mut batch = Vec::with_capacity(batch_size);
+
+    for (rel_path, entry) in walk_vault(vault_path) {
+        if !entry.file_type().is_file() || !is_markdown_path(&rel_path) {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(entry.path()) else {
+            log::warn!("Skipping unreadable note {}", rel_path);
+            continue;
+        };
+
+        summary.note_count += 1;
+        summary.total_size += bytes.len() as u64;
+        batch.push(ManifestEntry {
+            path: rel_path,
+            checksum: compute_checksum(&String::from_utf8_lossy(&bytes)),
+            size: bytes.len() as u64,
+        });
+
+        if batch.len() >= batch_size {
+            on_batch(std::mem::replace(&mut batch, Vec::with_capacity(batch_size)));
+        }
+    }
+
+    if !batch.is_empty() {
+        on_batch(batch);
+    }
+    summary
+}
+
+/// Builds the manifest in one piece, for callers that compare it in memory.
+pub fn build_manifest(vault_path: &str) -> Vec<ManifestEntry> {
+    let mut entries = Vec::new();
+    scan_manifest(vault_path, MANIFEST_BATCH_SIZE, |batch| entries.extend(batch));
+    entries
+}
+
+/// Scans the filesystem, not the index, so the result can be compared with
+/// indexed checksums to detect drift. Entries are streamed as
+/// `vault-manifest-batch` events; the returned summary marks the end.
+#[command]
+pub async fn markdown_vault_manifest(
+    app: AppHandle,
+    vault_path: String,
+    batch_size: Option<usize>,
+) -> Result<ManifestSummary, String> {
+    let batch_size = batch_size.unwrap_or(MANIFEST_BATCH_SIZE).max(1);
+
+    tokio::task::spawn_blocking(move || {
+        scan_manifest(&vault_path, batch_size, |entries| {
+            let batch = ManifestBatch {
+                vault_path: vault_path.clone(),
+                entries,
+            };
+            if let Err(e) = app.emit("vault-manifest-batch", batch) {
+                log::error!("Failed to emit manifest batch: {}", e);
+            }
+        })
+    })
+    .await
+    .map_err(|e| format!("Manifest scan failed: {}", e))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReconcileReport {
+    /// On disk but not in the index.
+    pub unindexed: Vec<String>,
+    /// In the index but no longer on disk.
+    pub missing: Vec<String>,
+    /// Indexed with a checksum that no longer matches the file.
+    pub mismatched: Vec<String>,
+    pub applied: bool,
+    /// Fixes that failed when `apply` was set, as `path: error`.
+    pub errors: Vec<String>,
+}
+
+/// Compares the disk manifest with indexed checksums. With `apply`, new and
+/// changed notes are reindexed and missing ones removed from the index.
+#[command]
+pub async fn markdown_reconcile_index(
+    vault_path: String,
+    index_path: String,
+    apply: Option<bool>,
+) -> Result<ReconcileReport, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let scan_path = vault_path.clone();
+    let manifest = tokio::task::spawn_blocking(move || build_manifest(&scan_path))
+        .await
+        .map_err(|e| format!("Manifest scan failed: {}", e))?;
+
+    let rows = sqlx::query("SELECT path, checksum FROM notes")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load indexed notes: {}", e))?;
+    let mut indexed: HashMap<String, Option<String>> = rows
+        .iter()
+        .map(|row| (row.get::<String, _>("path"), row.get::<Option<String>, _>("checksum")))
+        .collect();
+
+    let mut report = ReconcileReport::default();
+    for entry in manifest {
+        match indexed.remove(&entry.path) {
+            None => report.unindexed.push(entry.path),
+            Some(checksum) if checksum.as_deref() != Some(entry.checksum.as_str()) => {
+                report.mismatched.push(entry.path)
+            }
+            Some(_) => {}
+        }
+    }
+    report.missing = indexed.into_keys().collect();
+    report.unindexed.sort();
+    report.mismatched.sort();
+    report.missing.sort();
+
+    if apply.unwrap_or(false) {
+        // Missing notes go first, so links aren't resolved to them again.
+        let mut resolver = LinkResolver::load(&pool).await?;
+        for path in &report.missing {
+            match remove_note(&pool, path).await {
+                Ok(()) => resolver.remove_note(path),
+                Err(e) => report.errors.push(format!("{}: {}", path, e)),
+            }
+        }
+        for path in report.unindexed.iter().chain(&report.mismatched) {
+            if let Err(e) = reindex_file_with(&pool, &vault_path, path, &mut resolver).await {
+                report.errors.push(format!("{}: {}", path, e));
+            }
+        }
+        report.applied = true;
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,195 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tauri::command;
+
+use super::frontmatter::split_frontmatter;
+use super::indexer::reindex_file;
+use super::links::{find_markdown_links, find_wikilinks, snippet_around};
+use super::search::get_or_create_pool;
+use super::vault::{resolve_in_vault, write_atomic};
+
+const MENTION_SNIPPET_CHARS: usize = 160;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnlinkedMention {
+    pub source_path: String,
+    pub source_title: String,
+    /// Zero-based, counted from the end of the source's frontmatter, like
+    /// `note_links.position_line`.
+    pub line: usize,
+    /// Byte offsets of the mention within the line.
+    pub start: usize,
+    pub end: usize,
+    /// The mention as written, in its original case.
+    pub text: String,
+    pub snippet: String,
+}
+
+/// Matches any of `terms`, ignoring case. Longer terms go first so
+/// `Project Plan` wins over `Project`. Word boundaries are checked by
+/// `find_mentions`, since `\b` fails next to punctuation as in `C++`.
+fn mention_regex(terms: &[String]) -> Result<Regex, String> {
+    let mut terms: Vec<&String> = terms.iter().collect();
+    terms.sort_by_key(|t| std::cmp::Reverse(t.chars().count()));
+    let alternation = terms.iter().map(|t| regex::escape(t)).collect::<Vec<_>>().join("|");
+
+    RegexBuilder::new(&alternation)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("Failed to build mention pattern: {}", e))
+}
+
+/// Whole-word matches of `regex` in `content` outside fenced code and
+/// outside existing wiki or markdown links, as `(line, start, end)`.
+pub(crate) fn find_mentions(content: &str, regex: &Regex) -> Vec<(usize, usize, usize)> {
+    let mut mentions = Vec::new();
+    let mut in_fence = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let linked: Vec<(usize, usize)> = find_wikilinks(line)
+            .iter()
+            .map(|l| (l.start, l.end))
+            .chain(find_markdown_links(line).iter().map(|l| (l.start, l.end)))
+            .collect();
+        for found in regex.find_iter(line) {
+            let whole_word = !line[..found.start()].chars().last().is_some_and(char::is_alphanumeric)
+                && !line[found.end()..].chars().next().is_some_and(char::is_alphanumeric);
+            let in_link = linked.iter().any(|(start, end)| found.start() < *end && found.end() > *start);
+            if whole_word && !in_link {
+                mentions.push((index, found.start(), found.end()));
+            }
+        }
+    }
+
+    mentions
+}
+
+/// Places where another note names `path` by title or alias in plain text,
+/// without linking to it. Full-text search narrows the candidates; each is
+/// then scanned for case-insensitive whole-word matches.
+#[command]
+pub async fn markdown_unlinked_mentions(index_path: String, path: String) -> Result<Vec<UnlinkedMention>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let target = sqlx::query("SELECT title, aliases FROM notes WHERE path = ?")
+        .bind(&path)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load note: {}", e))?
+        .ok_or_else(|| format!("Note not indexed: {}", path))?;
+
+    let mut terms: Vec<String> = vec![target.get::<String, _>("title")];
+    let aliases: Vec<String> = target
+        .get::<Option<String>, _>("aliases")
+        .and_then(|a| serde_json::from_str(&a).ok())
+        .unwrap_or_default();
+    terms.extend(aliases);
+    let mut terms: Vec<String> = terms
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| t.chars().any(char::is_alphanumeric))
+        .collect();
+    terms.sort_by_key(|t| t.to_lowercase());
+    terms.dedup_by_key(|t| t.to_lowercase());
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let fts_query = terms
+        .iter()
+        .map(|term| format!("content : \"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    let candidates = sqlx::query(r#"
+        SELECT n.path, n.title, n.content
+        FROM notes_fts
+        JOIN notes n ON notes_fts.rowid = n.rowid
+        WHERE notes_fts MATCH ? AND n.path != ?
+        ORDER BY n.path
+    "#)
+    .bind(&fts_query)
+    .bind(&path)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Mention search failed: {}", e))?;
+
+    let regex = mention_regex(&terms)?;
+    let mut mentions = Vec::new();
+    for row in &candidates {
+        let source_path = row.get::<String, _>("path");
+        let source_title = row.get::<String, _>("title");
+        let content = row.get::<Option<String>, _>("content").unwrap_or_default();
+        let lines: Vec<&str> = content.lines().collect();
+
+        for (line, start, end) in find_mentions(&content, &regex) {
+            let text = &lines[line][start..end];
+            mentions.push(UnlinkedMention {
+                source_path: source_path.clone(),
+                source_title: source_title.clone(),
+                line,
+                start,
+                end,
+                text: text.to_string(),
+                snippet: snippet_around(lines[line], text, MENTION_SNIPPET_CHARS),
+            });
+        }
+    }
+
+    Ok(mentions)
+}
+
+/// Turns a plain-text mention found by `markdown_unlinked_mentions` into a
+/// wikilink, keeping the original wording as the link's display text when
+/// it differs from `target_title`. Returns false, without editing, when the
+/// mention is no longer at `line`, e.g. because the note changed since the
+/// mentions were listed.
+#[command]
+pub async fn markdown_link_mention(
+    vault_path: String,
+    index_path: String,
+    source_rel: String,
+    line: usize,
+    mention_text: String,
+    target_title: String,
+) -> Result<bool, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    if mention_text.trim().is_empty() || target_title.trim().is_empty() {
+        return Err("Mention and target title are required".to_string());
+    }
+
+    let full_path = resolve_in_vault(&vault_path, &source_rel)?;
+    let raw = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read note: {}", e))?;
+    let (_, body) = split_frontmatter(&raw);
+    let body_offset = raw.len() - body.len();
+
+    let regex = mention_regex(std::slice::from_ref(&mention_text))?;
+    let Some((_, start, end)) = find_mentions(body, &regex)
+        .into_iter()
+        .find(|(l, start, end)| *l == line && body.lines().nth(line).map(|text| &text[*start..*end]) == Some(mention_text.as_str()))
+    else {
+        return Ok(false);
+    };
+
+    let line_offset: usize = body.split_inclusive('\n').take(line).map(str::len).sum();
+    let link = if mention_text == target_title {
+        format!("[[{}]]", target_title)
+    } else {
+        format!("[[{}|{}]]", target_title, mention_text)
+    };
+    let at = body_offset + line_offset;
+    let updated = format!("{}{}{}", &raw[..at + start], link, &raw[at + end..]);
+
+    write_atomic(&full_path, &updated)?;
+    reindex_file(&pool, &vault_path, &source_rel).await?;
+    Ok(true)
+}
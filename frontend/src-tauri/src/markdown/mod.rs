@@ -2,9 +2,83 @@ pub mod vault;
 pub mod notes;
 pub mod search;
 pub mod watcher;
+pub mod blocks;
+pub mod aliases;
+pub mod frontmatter;
+pub mod indexer;
+pub mod links;
+pub mod render;
+pub mod headings;
+pub mod embeds;
+pub mod trash;
+pub mod diff;
+pub mod external;
+pub mod bundle;
+pub mod grep;
+pub mod sync;
+pub mod manifest;
+pub mod windows;
+pub mod dates;
+pub mod attachments;
+pub mod convert;
+pub mod permalinks;
+pub mod site;
+pub mod tags;
+pub mod lint;
+pub mod mentions;
+pub mod versions;
+pub mod git;
+pub mod encryption;
+pub mod language;
+pub mod cjk;
+pub mod embeddings;
+pub mod analytics;
+pub mod rename;
+pub mod tasks;
+pub mod import;
+pub mod localize;
+pub mod templates;
+pub mod naming;
+pub mod urls;
 
 pub use vault::*;
 pub use notes::*;
 pub use search::*;
 pub use watcher::*;
+pub use blocks::*;
+pub use aliases::*;
+pub use indexer::*;
+pub use links::*;
+pub use render::*;
+pub use embeds::*;
+pub use trash::*;
+pub use diff::*;
+pub use external::*;
+pub use bundle::*;
+pub use grep::*;
+pub use sync::*;
+pub use manifest::*;
+pub use windows::*;
+pub use dates::*;
+pub use attachments::*;
+pub use convert::*;
+pub use permalinks::*;
+pub use site::*;
+pub use tags::*;
+pub use lint::*;
+pub use mentions::*;
+pub use versions::*;
+pub use git::*;
+pub use encryption::*;
+pub use language::*;
+pub use cjk::*;
+pub use embeddings::*;
+pub use analytics::*;
+pub use rename::*;
+pub use tasks::*;
+pub use import::*;
+pub use localize::*;
+pub use templates::*;
+pub use naming::*;
+pub use urls::*;
 
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+use super::import::free_path;
+use super::vault::{normalize_rel_path, resolve_in_vault, VaultConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NamingCase {
+    /// `meeting-notes`
+    Kebab,
+    /// `meeting_notes`
+    Snake,
+}
+
+/// A file name convention, stored as the `namingRule` vault setting.
+/// Only the file name is checked, not its folders.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamingRule {
+    pub case: Option<NamingCase>,
+    /// Names start with a `YYYY-MM-DD` date.
+    #[serde(default)]
+    pub date_prefix: bool,
+    /// Longest name allowed, in characters, without the `.md` extension.
+    pub max_length: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NamingViolation {
+    /// `case`, `date_prefix` or `max_length`.
+    pub rule: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NamingCheck {
+    pub valid: bool,
+    pub violations: Vec<NamingViolation>,
+    /// A compliant path in the same folder that isn't taken, when the name
+    /// breaks the rule.
+    pub suggested: Option<String>,
+}
+
+/// Length of a leading `YYYY-MM-DD` that is a real date, if any.
+fn date_prefix_len(stem: &str) -> Option<usize> {
+    let prefix = stem.get(..10)?;
+    chrono::NaiveDate::parse_from_str(prefix, "%Y-%m-%d").ok().map(|_| 10)
+}
+
+/// Splits a name into lowercase words at punctuation, spaces and
+/// camelCase boundaries.
+fn words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+        } else {
+            if c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()) && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            current.extend(c.to_lowercase());
+        }
+        previous = Some(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn separator(case: Option<NamingCase>) -> char {
+    match case {
+        Some(NamingCase::Snake) => '_',
+        _ => '-',
+    }
+}
+
+/// Cuts `text` to `max` characters, preferring a word boundary.
+fn truncate_name(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max).collect();
+    let at_boundary = text.chars().nth(max).is_some_and(|c| !c.is_alphanumeric());
+    let trimmed = match cut.rfind(|c: char| !c.is_alphanumeric()) {
+        Some(index) if !at_boundary && index > 0 => &cut[..index],
+        _ => &cut,
+    };
+    trimmed.trim_end_matches([' ', '-', '_']).to_string()
+}
+
+/// Checks a file name against `rule` and builds a compliant alternative.
+pub fn check_name(stem: &str, rule: &NamingRule) -> (Vec<NamingViolation>, String) {
+    let mut violations = Vec::new();
+    let sep = separator(rule.case);
+
+    // `2024-05-01-meeting-notes` is a date, the separator after it and the rest.
+    let (date, between, rest) = match date_prefix_len(stem) {
+        Some(len) => {
+            let after = &stem[len..];
+            let rest = after.trim_start_matches(|c: char| !c.is_alphanumeric());
+            (Some(stem[..len].to_string()), &after[..after.len() - rest.len()], rest)
+        }
+        None => (None, "", stem),
+    };
+    if rule.date_prefix && date.is_none() {
+        violations.push(NamingViolation {
+            rule: "date_prefix".to_string(),
+            message: "Name must start with a YYYY-MM-DD date".to_string(),
+        });
+    }
+
+    let mut rest_fixed = rest.to_string();
+    if let Some(case) = rule.case {
+        let fixed = words(rest).join(&sep.to_string());
+        let separated = date.is_none() || rest.is_empty() || between.len() == 1 && between.starts_with(sep);
+        if fixed != rest || !separated {
+            violations.push(NamingViolation {
+                rule: "case".to_string(),
+                message: match case {
+                    NamingCase::Kebab => "Name must be kebab-case, e.g. meeting-notes".to_string(),
+                    NamingCase::Snake => "Name must be snake_case, e.g. meeting_notes".to_string(),
+                },
+            });
+        }
+        rest_fixed = fixed;
+    }
+
+    let date = date.or_else(|| rule.date_prefix.then(|| chrono::Local::now().format("%Y-%m-%d").to_string()));
+    let join = |rest: &str| match (&date, rest.is_empty()) {
+        (Some(date), false) if rule.case.is_some() => format!("{}{}{}", date, sep, rest),
+        (Some(date), false) => format!("{} {}", date, rest),
+        (Some(date), true) => date.clone(),
+        (None, _) => rest.to_string(),
+    };
+
+    if let Some(max) = rule.max_length {
+        if stem.chars().count() > max {
+            violations.push(NamingViolation {
+                rule: "max_length".to_string(),
+                message: format!("Name must be at most {} characters", max),
+            });
+        }
+        let fixed = join(&rest_fixed);
+        if fixed.chars().count() > max {
+            let room = max.saturating_sub(date.as_ref().map(|d| d.len() + 1).unwrap_or(0));
+            rest_fixed = truncate_name(&rest_fixed, room);
+        }
+    }
+
+    let suggestion = match join(&rest_fixed) {
+        _ if violations.is_empty() => stem.to_string(),
+        fixed if fixed.is_empty() => "untitled".to_string(),
+        fixed => fixed,
+    };
+    (violations, suggestion)
+}
+
+/// Validates a note's file name against `rule`, or the vault's
+/// `namingRule` setting when `rule` is omitted, for a save hook to warn or
+/// rename. Without either rule every name is valid.
+#[command]
+pub async fn markdown_check_naming(
+    vault_path: String,
+    rel_path: String,
+    rule: Option<NamingRule>,
+) -> Result<NamingCheck, String> {
+    let rel_path = normalize_rel_path(&rel_path)?;
+    let rule = match rule {
+        Some(rule) => rule,
+        None => VaultConfig::load(&vault_path)
+            .and_then(|config| config.settings.get("namingRule").cloned())
+            .map(|value| serde_json::from_value(value).map_err(|e| format!("Invalid namingRule setting: {}", e)))
+            .transpose()?
+            .unwrap_or_default(),
+    };
+
+    let (dir, name) = match rel_path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{}/", dir), name),
+        None => (String::new(), rel_path.as_str()),
+    };
+    let stem = name.strip_suffix(".md").unwrap_or(name);
+    let (violations, fixed) = check_name(stem, &rule);
+
+    let suggested = if violations.is_empty() {
+        None
+    } else {
+        let candidate = format!("{}{}.md", dir, fixed);
+        let taken = !candidate.eq_ignore_ascii_case(&rel_path) && resolve_in_vault(&vault_path, &candidate)?.exists();
+        Some(if taken { free_path(&vault_path, &candidate)? } else { candidate })
+    };
+
+    Ok(NamingCheck {
+        valid: violations.is_empty(),
+        violations,
+        suggested,
+    })
+}
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 use tauri::command;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use walkdir::WalkDir;
 
+use super::vault::{is_markdown_path, normalize_rel_path, resolve_in_vault, walk_vault};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileTreeNode {
     pub name: String,
@@ -82,3 +85,86 @@ pub async fn markdown_get_file_tree(vault_path: String) -> Result<FileTreeNode,
     Ok(build_tree(&root_path, &root_path))
 }
 
+
+#[command]
+pub async fn markdown_list_all_paths(vault_path: String, include_files: bool) -> Result<Vec<String>, String> {
+    if !PathBuf::from(&vault_path).exists() {
+        return Err("Vault path does not exist".to_string());
+    }
+
+    let mut paths: Vec<String> = walk_vault(&vault_path)
+        .into_iter()
+        .filter(|(rel_path, entry)| {
+            entry.file_type().is_dir() || (include_files && is_markdown_path(rel_path))
+        })
+        .map(|(rel_path, _)| rel_path)
+        .collect();
+
+    paths.sort();
+    Ok(paths)
+}
+
+#[command]
+pub async fn markdown_create_folder(vault_path: String, rel_path: String) -> Result<String, String> {
+    let normalized = normalize_rel_path(&rel_path)?;
+    if normalized.is_empty() {
+        return Err("Folder name is empty".to_string());
+    }
+
+    let full_path = resolve_in_vault(&vault_path, &normalized)?;
+    if full_path.is_file() {
+        return Err(format!("A file already exists at {}", normalized));
+    }
+    std::fs::create_dir_all(&full_path)
+        .map_err(|e| format!("Failed to create folder: {}", e))?;
+
+    Ok(normalized)
+}
+
+/// Untitled names tried before giving up; past this the folder is full of
+/// them and something else is wrong.
+const MAX_UNTITLED_ATTEMPTS: usize = 10_000;
+
+/// Picks the first free `base.md`, `base 1.md`, `base 2.md`, ... in a folder
+/// and returns its vault-relative path. The file is created empty to claim
+/// the name, so two windows creating notes at once never get the same one:
+/// if another writer takes a name between the scan and the create, the next
+/// number is tried.
+#[command]
+pub async fn markdown_next_untitled_name(
+    vault_path: String,
+    folder_rel: String,
+    base: Option<String>,
+) -> Result<String, String> {
+    let folder = normalize_rel_path(&folder_rel)?;
+    let base = base.as_deref().map(str::trim).filter(|b| !b.is_empty()).unwrap_or("Untitled");
+    let base = base.strip_suffix(".md").unwrap_or(base);
+    if base.contains(['/', '\\']) {
+        return Err("Note name can't contain a path separator".to_string());
+    }
+
+    let folder_path = resolve_in_vault(&vault_path, &folder)?;
+    std::fs::create_dir_all(&folder_path).map_err(|e| format!("Failed to create folder: {}", e))?;
+
+    // Compare ignoring case, since that's how macOS and Windows see names.
+    let taken: HashSet<String> = std::fs::read_dir(&folder_path)
+        .map_err(|e| format!("Failed to read folder: {}", e))?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_lowercase()))
+        .collect();
+
+    for n in 0..MAX_UNTITLED_ATTEMPTS {
+        let name = if n == 0 { format!("{}.md", base) } else { format!("{} {}.md", base, n) };
+        if taken.contains(&name.to_lowercase()) {
+            continue;
+        }
+
+        match std::fs::File::options().write(true).create_new(true).open(folder_path.join(&name)) {
+            Ok(_) => return Ok(if folder.is_empty() { name } else { format!("{}/{}", folder, name) }),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to create note: {}", e)),
+        }
+    }
+
+    Err(format!("No free name for {} in {}", base, if folder.is_empty() { "the vault root" } else { &folder }))
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::{BTreeMap, HashSet};
+use tauri::command;
+
+use super::search::get_or_create_pool;
+
+/// Lowercases and joins runs of anything but letters and digits with a
+/// single hyphen. `/` is kept so permalinks can nest.
+pub fn slugify(text: &str) -> String {
+    text.split('/')
+        .map(|segment| {
+            let mut slug = String::new();
+            let mut pending_hyphen = false;
+            for c in segment.chars().flat_map(|c| c.to_lowercase()) {
+                if c.is_alphanumeric() {
+                    if pending_hyphen && !slug.is_empty() {
+                        slug.push('-');
+                    }
+                    pending_hyphen = false;
+                    slug.push(c);
+                } else {
+                    pending_hyphen = true;
+                }
+            }
+            slug
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Permalink {
+    pub path: String,
+    /// Unique across the vault.
+    pub slug: String,
+    /// The slug before de-duplication; differs from `slug` on a collision.
+    pub base_slug: String,
+    /// `frontmatter` when taken from `permalink`/`slug`, else `title`.
+    pub source: String,
+}
+
+/// Assigns every indexed note a unique slug. Slugs declared in frontmatter
+/// are placed first, then ones derived from titles (or file names when the
+/// title has nothing sluggable), each in path order; later claimants of a
+/// taken slug get `-2`, `-3` and so on. The result only changes when notes
+/// are added, removed or retitled.
+pub async fn assign_permalinks(pool: &Pool<Sqlite>) -> Result<Vec<Permalink>, String> {
+    let rows = sqlx::query("SELECT path, title, frontmatter FROM notes ORDER BY path")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+
+    let mut declared = Vec::new();
+    let mut derived = Vec::new();
+    for row in &rows {
+        let path = row.get::<String, _>("path");
+        let frontmatter: serde_json::Value = row
+            .get::<Option<String>, _>("frontmatter")
+            .and_then(|f| serde_json::from_str(&f).ok())
+            .unwrap_or_default();
+
+        let explicit = ["permalink", "slug"]
+            .iter()
+            .filter_map(|key| frontmatter.get(*key).and_then(|v| v.as_str()))
+            .map(slugify)
+            .find(|slug| !slug.is_empty());
+
+        match explicit {
+            Some(slug) => declared.push((path, slug, "frontmatter")),
+            None => {
+                let stem = path.rsplit('/').next().unwrap_or(&path);
+                let stem = stem.strip_suffix(".md").unwrap_or(stem);
+                let slug = [row.get::<String, _>("title").as_str(), stem]
+                    .into_iter()
+                    .map(|text| slugify(&text.replace('/', " ")))
+                    .find(|slug| !slug.is_empty())
+                    .unwrap_or_else(|| "note".to_string());
+                derived.push((path, slug, "title"));
+            }
+        }
+    }
+
+    let mut taken = HashSet::new();
+    let mut permalinks = Vec::with_capacity(rows.len());
+    for (path, base_slug, source) in declared.into_iter().chain(derived) {
+        let mut slug = base_slug.clone();
+        let mut n = 2;
+        while !taken.insert(slug.clone()) {
+            slug = format!("{}-{}", base_slug, n);
+            n += 1;
+        }
+        permalinks.push(Permalink {
+            path,
+            slug,
+            base_slug,
+            source: source.to_string(),
+        });
+    }
+
+    permalinks.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(permalinks)
+}
+
+#[command]
+pub async fn markdown_get_permalink(index_path: String, path: String) -> Result<String, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    assign_permalinks(&pool)
+        .await?
+        .into_iter()
+        .find(|permalink| permalink.path == path)
+        .map(|permalink| permalink.slug)
+        .ok_or_else(|| format!("Note not indexed: {}", path))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SlugCollision {
+    pub slug: String,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PermalinkReport {
+    pub permalinks: Vec<Permalink>,
+    /// Base slugs claimed by more than one note, before de-duplication.
+    pub collisions: Vec<SlugCollision>,
+}
+
+#[command]
+pub async fn markdown_list_permalinks(index_path: String) -> Result<PermalinkReport, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let permalinks = assign_permalinks(&pool).await?;
+
+    let mut by_slug: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for permalink in &permalinks {
+        by_slug.entry(&permalink.base_slug).or_default().push(permalink.path.clone());
+    }
+    let collisions = by_slug
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(slug, paths)| SlugCollision { slug: slug.to_string(), paths })
+        .collect();
+
+    Ok(PermalinkReport { permalinks, collisions })
+}
@@ -0,0 +1,384 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+use tauri::command;
+
+use super::indexer::reindex_file_with;
+use super::links::{find_markdown_links, find_wikilinks, percent_decode, percent_encode_path, LinkResolver};
+use super::search::{get_or_create_pool, rename_note};
+use super::vault::{resolve_in_vault, write_atomic};
+use super::versions::rename_versions;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteRename {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenameCollision {
+    /// The proposed new path.
+    pub path: String,
+    pub sources: Vec<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BulkRenameResult {
+    /// In the order they are applied.
+    pub renames: Vec<NoteRename>,
+    /// Any collision aborts the whole rename.
+    pub collisions: Vec<RenameCollision>,
+    /// Notes whose links to a renamed note are rewritten, by current path.
+    pub rewritten_notes: Vec<String>,
+    pub links_updated: usize,
+    pub applied: bool,
+    pub errors: Vec<String>,
+}
+
+fn split_name(path: &str) -> (&str, &str) {
+    let (dir, file) = path.rsplit_once('/').unwrap_or(("", path));
+    (dir, file.strip_suffix(".md").unwrap_or(file))
+}
+
+fn is_valid_stem(stem: &str) -> bool {
+    !stem.trim().is_empty() && !stem.starts_with('.') && !stem.contains(['/', '\\'])
+}
+
+/// Orders renames so none lands on a path another has yet to vacate.
+/// Returns `None` when they form a cycle, e.g. swapping two names.
+fn order_renames(renames: Vec<NoteRename>) -> Option<Vec<NoteRename>> {
+    let mut pending = renames;
+    let mut ordered = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let sources: HashSet<String> = pending.iter().map(|r| r.from.to_lowercase()).collect();
+        let (ready, blocked): (Vec<NoteRename>, Vec<NoteRename>) = pending
+            .into_iter()
+            .partition(|r| r.from.eq_ignore_ascii_case(&r.to) || !sources.contains(&r.to.to_lowercase()));
+        if ready.is_empty() {
+            return None;
+        }
+        ordered.extend(ready);
+        pending = blocked;
+    }
+    Some(ordered)
+}
+
+/// Swaps the filename in a link target for the renamed note's, keeping the
+/// folder, any `.md` suffix and, for markdown links, percent-encoding and
+/// `#fragment`. `None` when the target names the note some other way, such
+/// as by title or alias, and so still resolves after the rename.
+fn retarget(raw: &str, old_stem: &str, new_stem: &str, markdown: bool) -> Option<String> {
+    let (path, fragment) = match raw.split_once('#') {
+        Some((path, fragment)) if markdown => (path, Some(fragment)),
+        _ => (raw, None),
+    };
+    let (dir, segment) = match path.rsplit_once('/') {
+        Some((dir, segment)) => (Some(dir), segment),
+        None => (None, path),
+    };
+    let decoded = if markdown { percent_decode(segment) } else { segment.to_string() };
+    let suffix = if decoded.to_lowercase().ends_with(".md") { &decoded[decoded.len() - 3..] } else { "" };
+    if !decoded[..decoded.len() - suffix.len()].eq_ignore_ascii_case(old_stem) {
+        return None;
+    }
+
+    let mut name = format!("{}{}", new_stem, suffix);
+    if markdown && (segment.contains('%') || name.contains(char::is_whitespace)) {
+        name = percent_encode_path(&name);
+    }
+    let mut target = match dir {
+        Some(dir) => format!("{}/{}", dir, name),
+        None => name,
+    };
+    if let Some(fragment) = fragment {
+        target.push('#');
+        target.push_str(fragment);
+    }
+    Some(target)
+}
+
+/// Rewrites links in `content` that resolve to a renamed note. Returns the
+/// new content and how many links changed.
+fn rewrite_links(
+    content: &str,
+    source_path: &str,
+    resolver: &LinkResolver,
+    renamed: &HashMap<String, String>,
+) -> (String, usize) {
+    let mut output = String::with_capacity(content.len());
+    let mut updated = 0;
+    let mut in_fence = false;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if in_fence || trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            output.push_str(line);
+            continue;
+        }
+
+        // (start, end, replacement) byte ranges of link targets in the line.
+        let mut edits: Vec<(usize, usize, String)> = Vec::new();
+        let mut consider = |opener: &str, start: usize, end: usize, target: &str, resolve_as: &str, markdown: bool| {
+            let Some(old_path) = resolver.resolve(source_path, resolve_as) else {
+                return;
+            };
+            let Some(new_path) = renamed.get(&old_path) else {
+                return;
+            };
+            let (_, old_stem) = split_name(&old_path);
+            let (_, new_stem) = split_name(new_path);
+            let Some(replacement) = retarget(target, old_stem, new_stem, markdown) else {
+                return;
+            };
+            let link = &line[start..end];
+            let Some(from) = link.find(opener).map(|i| i + opener.len()) else {
+                return;
+            };
+            if let Some(offset) = link[from..].find(target) {
+                let at = start + from + offset;
+                edits.push((at, at + target.len(), replacement));
+            }
+        };
+
+        for link in find_wikilinks(line) {
+            if !link.target.is_empty() {
+                consider("[[", link.start, link.end, &link.target, &link.target, false);
+            }
+        }
+        for link in find_markdown_links(line) {
+            if link.target.is_empty() || link.target.starts_with('#') || link.target.contains("://") {
+                continue;
+            }
+            let resolve_as = percent_decode(link.target.split('#').next().unwrap_or(""));
+            consider("](", link.start, link.end, &link.target, &resolve_as, true);
+        }
+
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.0));
+        let mut line = line.to_string();
+        for (start, end, replacement) in edits {
+            line.replace_range(start..end, &replacement);
+            updated += 1;
+        }
+        output.push_str(&line);
+    }
+
+    (output, updated)
+}
+
+/// Applies `pattern` to the filename of every indexed note, without its
+/// `.md` extension and leaving the folder alone, then rewrites links to the
+/// renamed notes throughout the vault. `replacement` may use `$1` or
+/// `${name}` groups.
+///
+/// Nothing is touched when `dry_run` is set or when any proposed name
+/// collides with another, with an existing file, or is not a usable
+/// filename; the result lists the renames and collisions either way.
+#[command]
+pub async fn markdown_bulk_rename(
+    vault_path: String,
+    index_path: String,
+    pattern: String,
+    replacement: String,
+    dry_run: Option<bool>,
+) -> Result<BulkRenameResult, String> {
+    let regex = Regex::new(&pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let paths: Vec<String> = sqlx::query("SELECT path FROM notes ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?
+        .iter()
+        .map(|row| row.get::<String, _>("path"))
+        .collect();
+
+    let mut result = BulkRenameResult::default();
+    let mut proposed = Vec::new();
+    let mut targets: HashMap<String, Vec<String>> = HashMap::new();
+    for path in &paths {
+        let (dir, stem) = split_name(path);
+        let new_stem = regex.replace_all(stem, replacement.as_str());
+        if new_stem == stem {
+            continue;
+        }
+        let to = if dir.is_empty() { format!("{}.md", new_stem) } else { format!("{}/{}.md", dir, new_stem) };
+        if !is_valid_stem(&new_stem) {
+            result.collisions.push(RenameCollision {
+                path: to,
+                sources: vec![path.clone()],
+                reason: "Not a valid file name".to_string(),
+            });
+            continue;
+        }
+        targets.entry(to.to_lowercase()).or_default().push(path.clone());
+        proposed.push(NoteRename { from: path.clone(), to });
+    }
+
+    let sources: HashSet<String> = proposed.iter().map(|r| r.from.to_lowercase()).collect();
+    for rename in &proposed {
+        let key = rename.to.to_lowercase();
+        let claimants = &targets[&key];
+        if claimants.len() > 1 {
+            if claimants[0] == rename.from {
+                result.collisions.push(RenameCollision {
+                    path: rename.to.clone(),
+                    sources: claimants.clone(),
+                    reason: "Several notes would get this name".to_string(),
+                });
+            }
+            continue;
+        }
+        if !rename.from.eq_ignore_ascii_case(&rename.to)
+            && !sources.contains(&key)
+            && resolve_in_vault(&vault_path, &rename.to)?.exists()
+        {
+            result.collisions.push(RenameCollision {
+                path: rename.to.clone(),
+                sources: vec![rename.from.clone()],
+                reason: "A file with this name already exists".to_string(),
+            });
+        }
+    }
+
+    match order_renames(proposed.clone()) {
+        Some(ordered) => result.renames = ordered,
+        None => {
+            result.renames = proposed;
+            result.collisions.push(RenameCollision {
+                path: String::new(),
+                sources: result.renames.iter().map(|r| r.from.clone()).collect(),
+                reason: "Renames form a cycle; rename in two passes".to_string(),
+            });
+        }
+    }
+
+    let renamed: HashMap<String, String> = result.renames.iter().map(|r| (r.from.clone(), r.to.clone())).collect();
+    let mut resolver = LinkResolver::load(&pool).await?;
+    let mut rewrites: Vec<(String, String)> = Vec::new();
+    for path in &paths {
+        let raw = match resolve_in_vault(&vault_path, path).and_then(|full_path| {
+            std::fs::read_to_string(full_path).map_err(|e| format!("Failed to read note: {}", e))
+        }) {
+            Ok(raw) => raw,
+            Err(e) => {
+                result.errors.push(format!("{}: {}", path, e));
+                continue;
+            }
+        };
+        let (content, updated) = rewrite_links(&raw, path, &resolver, &renamed);
+        if updated > 0 {
+            result.links_updated += updated;
+            result.rewritten_notes.push(path.clone());
+            rewrites.push((path.clone(), content));
+        }
+    }
+
+    if dry_run.unwrap_or(false) || !result.collisions.is_empty() || result.renames.is_empty() {
+        return Ok(result);
+    }
+
+    // Links first, so a failed move leaves notes pointing at names that
+    // are about to exist rather than at names that no longer do.
+    for (path, content) in &rewrites {
+        if let Err(e) = resolve_in_vault(&vault_path, path).and_then(|full_path| write_atomic(&full_path, content)) {
+            result.errors.push(format!("{}: {}", path, e));
+        }
+    }
+
+    let mut moved = Vec::new();
+    for rename in &result.renames {
+        let moved_on_disk = resolve_in_vault(&vault_path, &rename.from).and_then(|from| {
+            let to = resolve_in_vault(&vault_path, &rename.to)?;
+            std::fs::rename(from, to).map_err(|e| format!("Failed to rename note: {}", e))
+        });
+        if let Err(e) = moved_on_disk {
+            result.errors.push(format!("{}: {}", rename.from, e));
+            continue;
+        }
+        if let Err(e) = rename_versions(&vault_path, &rename.from, &rename.to) {
+            log::warn!("Failed to move versions of {}: {}", rename.from, e);
+        }
+        match rename_note(&pool, &rename.from, &rename.to).await {
+            Ok(()) => resolver.remove_note(&rename.from),
+            Err(e) => result.errors.push(format!("{}: {}", rename.from, e)),
+        }
+        moved.push(rename.to.clone());
+    }
+
+    let mut reindex: Vec<String> = rewrites
+        .iter()
+        .map(|(path, _)| renamed.get(path).cloned().unwrap_or_else(|| path.clone()))
+        .chain(moved)
+        .collect();
+    reindex.sort();
+    reindex.dedup();
+    for path in reindex {
+        if let Err(e) = reindex_file_with(&pool, &vault_path, &path, &mut resolver).await {
+            result.errors.push(format!("{}: {}", path, e));
+        }
+    }
+
+    result.applied = true;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename(from: &str, to: &str) -> NoteRename {
+        NoteRename { from: from.to_string(), to: to.to_string() }
+    }
+
+    #[test]
+    fn renames_are_ordered_and_cycles_rejected() {
+        let ordered = order_renames(vec![rename("a.md", "b.md"), rename("b.md", "c.md")]).unwrap();
+        let order: Vec<&str> = ordered.iter().map(|r| r.from.as_str()).collect();
+        assert_eq!(order, vec!["b.md", "a.md"]);
+
+        assert!(order_renames(vec![rename("a.md", "b.md"), rename("b.md", "a.md")]).is_none());
+        // A case-only rename vacates its own path.
+        assert!(order_renames(vec![rename("a.md", "A.md")]).is_some());
+    }
+
+    #[test]
+    fn retarget_keeps_folder_suffix_encoding_and_fragment() {
+        assert_eq!(
+            retarget("dir/Old%20Name.md#Intro", "Old Name", "New Name", true).as_deref(),
+            Some("dir/New%20Name.md#Intro"),
+        );
+        assert_eq!(retarget("old", "old", "new plan", false).as_deref(), Some("new plan"));
+        assert_eq!(retarget("old.md", "old", "new plan", true).as_deref(), Some("new%20plan.md"));
+        // Named by title rather than filename: still resolves, so left alone.
+        assert_eq!(retarget("Project Plan", "old", "new", false), None);
+    }
+
+    #[test]
+    fn rewrites_only_links_that_use_the_old_filename() {
+        let mut resolver = LinkResolver::default();
+        resolver.insert_note("notes/old.md", "Project Plan", &[]);
+        resolver.insert_note("notes/Old Name.md", "Old Name", &[]);
+        resolver.insert_note("notes/index.md", "Index", &[]);
+        let renamed = HashMap::from([
+            ("notes/old.md".to_string(), "notes/new.md".to_string()),
+            ("notes/Old Name.md".to_string(), "notes/New Name.md".to_string()),
+        ]);
+
+        let content = "See [[old|the plan]] and [[Project Plan]].\n\
+                       [Intro](old.md#Intro) [Top](Old%20Name.md#Top)\n\
+                       ```\n[[old]]\n```\n";
+        let (output, updated) = rewrite_links(content, "notes/index.md", &resolver, &renamed);
+        assert_eq!(
+            output,
+            "See [[new|the plan]] and [[Project Plan]].\n\
+             [Intro](new.md#Intro) [Top](New%20Name.md#Top)\n\
+             ```\n[[old]]\n```\n",
+        );
+        assert_eq!(updated, 3);
+    }
+}
@@ -0,0 +1,194 @@
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::command;
+
+use super::frontmatter::split_frontmatter;
+use super::links::{find_wikilinks, WikiLink};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RenderOptions {
+    /// Pass raw HTML in the note through untouched. Off by default, in which
+    /// case raw HTML is escaped and the output is sanitized.
+    #[serde(default)]
+    pub allow_html: bool,
+    /// Resolved vault paths for attachment embeds, keyed by link target and
+    /// rendered as `data-path`. Filled in by `markdown_render_with_embeds`.
+    #[serde(skip)]
+    pub attachment_paths: HashMap<String, String>,
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders a wikilink as an anchor the frontend can intercept by its
+/// `data-*` attributes. Embeds get a placeholder with the same attributes.
+fn wikilink_html(link: &WikiLink, options: &RenderOptions) -> String {
+    let mut attrs = format!(" data-target=\"{}\"", escape_html(&link.target));
+    if let Some(path) = link.is_embed.then(|| options.attachment_paths.get(&link.target)).flatten() {
+        attrs.push_str(&format!(" data-path=\"{}\"", escape_html(path)));
+    }
+    if let Some(heading) = &link.heading {
+        attrs.push_str(&format!(" data-heading=\"{}\"", escape_html(heading)));
+    }
+    if let Some(block) = &link.block_ref {
+        attrs.push_str(&format!(" data-block=\"{}\"", escape_html(block)));
+    }
+
+    let label = link.display_text.clone().unwrap_or_else(|| {
+        match (&link.heading, &link.block_ref) {
+            (Some(heading), _) if link.target.is_empty() => heading.clone(),
+            (Some(heading), _) => format!("{} > {}", link.target, heading),
+            _ => link.target.clone(),
+        }
+    });
+
+    if link.is_embed {
+        format!("<span class=\"wikilink-embed\"{}>{}</span>", attrs, escape_html(&label))
+    } else {
+        format!("<a class=\"wikilink\" href=\"#\"{}>{}</a>", attrs, escape_html(&label))
+    }
+}
+
+fn flush_text<'a>(buffer: &mut String, events: &mut Vec<Event<'a>>, options: &RenderOptions) {
+    if buffer.is_empty() {
+        return;
+    }
+
+    let mut cursor = 0;
+    for link in find_wikilinks(buffer) {
+        if link.start > cursor {
+            events.push(Event::Text(CowStr::from(buffer[cursor..link.start].to_string())));
+        }
+        events.push(Event::InlineHtml(CowStr::from(wikilink_html(&link, options))));
+        cursor = link.end;
+    }
+    if cursor < buffer.len() {
+        events.push(Event::Text(CowStr::from(buffer[cursor..].to_string())));
+    }
+    buffer.clear();
+}
+
+fn sanitize(html: &str) -> String {
+    ammonia::Builder::default()
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        .add_generic_attributes(&["class", "id"])
+        .add_generic_attribute_prefixes(&["data-"])
+        .clean(html)
+        .to_string()
+}
+
+/// Renders markdown (GFM tables, task lists, footnotes, strikethrough) to an
+/// HTML string. Any leading frontmatter block is skipped.
+pub fn render_html(content: &str, options: &RenderOptions) -> String {
+    let (_, body) = split_frontmatter(content);
+
+    let mut parser_options = Options::empty();
+    parser_options.insert(Options::ENABLE_TABLES);
+    parser_options.insert(Options::ENABLE_TASKLISTS);
+    parser_options.insert(Options::ENABLE_FOOTNOTES);
+    parser_options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let mut events = Vec::new();
+    let mut text = String::new();
+    let mut in_code_block = false;
+
+    for event in Parser::new_ext(body, parser_options) {
+        match event {
+            Event::Text(t) if !in_code_block => text.push_str(&t),
+            Event::Html(raw) | Event::InlineHtml(raw) if !options.allow_html => {
+                text.push_str(&raw);
+            }
+            other => {
+                flush_text(&mut text, &mut events, options);
+                match &other {
+                    Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+                    Event::End(TagEnd::CodeBlock) => in_code_block = false,
+                    _ => {}
+                }
+                events.push(other);
+            }
+        }
+    }
+    flush_text(&mut text, &mut events, options);
+
+    let mut output = String::new();
+    html::push_html(&mut output, events.into_iter());
+
+    if options.allow_html {
+        output
+    } else {
+        sanitize(&output)
+    }
+}
+
+#[command]
+pub async fn markdown_render_html(
+    content: String,
+    options: Option<RenderOptions>,
+) -> Result<String, String> {
+    Ok(render_html(&content, &options.unwrap_or_default()))
+}
+
+/// Converts pasted HTML to markdown. It is sanitised first, which drops
+/// scripts, styles and event handlers and repairs malformed markup, so
+/// only content reaches the converter. Setext headings are rewritten as
+/// `#` headings, as the editor writes them, and runs of blank lines are
+/// collapsed.
+pub fn html_to_markdown(html: &str) -> String {
+    let cleaned = ammonia::Builder::default()
+        .add_tag_attributes("code", &["class"])
+        .clean(html)
+        .to_string();
+    let converted = html2md::parse_html(&cleaned);
+
+    let mut markdown = String::new();
+    let mut lines = converted.lines().map(str::trim_end).peekable();
+    let mut in_fence = false;
+    let mut blank_lines = 0;
+    while let Some(line) = lines.next() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if line.is_empty() && !in_fence {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+
+        let underline = lines.peek().filter(|next| {
+            !in_fence && !line.is_empty() && next.len() >= 3 && (next.chars().all(|c| c == '=') || next.chars().all(|c| c == '-'))
+        });
+        match underline.map(|next| next.starts_with('=')) {
+            Some(level_one) => {
+                markdown.push_str(if level_one { "# " } else { "## " });
+                markdown.push_str(line);
+                lines.next();
+            }
+            None => markdown.push_str(line),
+        }
+        markdown.push('\n');
+    }
+    markdown.trim().to_string()
+}
+
+#[command]
+pub async fn markdown_html_to_markdown(html: String) -> Result<String, String> {
+    Ok(html_to_markdown(&html))
+}
@@ -1,36 +1,153 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite, Row};
-use std::collections::HashMap;
+use sqlx::{sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow}, Pool, Sqlite, Row};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::command;
 use tokio::sync::Mutex;
 
+use super::attachments::AttachmentResolver;
+use super::blocks::index_blocks;
+use super::cjk::{backfill_cjk, has_cjk, index_cjk, search_cjk};
+use super::grep::build_grep_regex;
+use super::headings::{backfill_headings, index_headings};
+use super::language::detect_language;
+use super::indexer::{note_excerpt, reindex_file, EXCERPT_CHARS};
+use super::links::{backfill_link_keys, backfill_urls, index_links, index_urls, LinkResolver};
+use super::vault::{is_markdown_path, normalize_rel_path, resolve_in_vault, vault_for_index, write_atomic, VaultConfig};
+
 lazy_static::lazy_static! {
     static ref DB_POOLS: Arc<Mutex<HashMap<String, Pool<Sqlite>>>> = Arc::new(Mutex::new(HashMap::new()));
 }
 
-async fn get_or_create_pool(db_path: &str) -> Result<Pool<Sqlite>, String> {
+const DEFAULT_MAX_CONNECTIONS: u32 = 5;
+
+/// How long SQLite itself waits on a locked database before giving up.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Overall budget for retrying a write that still failed with a lock error,
+/// e.g. when SQLite returns BUSY without waiting to avoid a deadlock.
+const WRITE_RETRY_TIMEOUT: Duration = Duration::from_secs(10);
+const WRITE_RETRY_MAX_DELAY: Duration = Duration::from_millis(500);
+
+/// True for SQLITE_BUSY/SQLITE_LOCKED and their extended codes, which the
+/// sqlx error text reports as `(code: N)`. Anything else is a real failure.
+pub(crate) fn is_lock_error(message: &str) -> bool {
+    let lock_code = message
+        .split("(code: ")
+        .nth(1)
+        .and_then(|rest| rest.split(')').next())
+        .and_then(|code| code.trim().parse::<i32>().ok())
+        .map(|code| matches!(code & 0xff, 5 | 6))
+        .unwrap_or(false);
+
+    lock_code || message.contains("database is locked") || message.contains("database table is locked")
+}
+
+/// Runs a write, retrying with exponential backoff while it fails with a
+/// transient lock error.
+pub(crate) async fn retry_on_lock<T, F, Fut>(mut operation: F) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let started = Instant::now();
+    let mut delay = Duration::from_millis(10);
+
+    loop {
+        match operation().await {
+            Err(e) if is_lock_error(&e) && started.elapsed() + delay < WRITE_RETRY_TIMEOUT => {
+                log::warn!("Index write hit a lock, retrying in {:?}: {}", delay, e);
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(WRITE_RETRY_MAX_DELAY);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Pool size from the `indexMaxConnections` vault setting, for an index at
+/// the usual `<vault>/.vault/index.db`.
+fn configured_max_connections(db_path: &str) -> Option<u32> {
+    let config = VaultConfig::load(vault_for_index(db_path)?)?;
+    let max = config.settings.get("indexMaxConnections")?.as_u64()?;
+    Some(max.clamp(1, 64) as u32)
+}
+
+pub(crate) async fn get_or_create_pool(db_path: &str) -> Result<Pool<Sqlite>, String> {
+    get_or_create_pool_with(db_path, None).await
+}
+
+/// Returns the cached pool for an index, reopening it when an explicit size
+/// differs from the cached pool's. Without one, the vault setting or the
+/// default applies.
+async fn get_or_create_pool_with(db_path: &str, max_connections: Option<u32>) -> Result<Pool<Sqlite>, String> {
     let mut pools = DB_POOLS.lock().await;
 
     if let Some(pool) = pools.get(db_path) {
-        return Ok(pool.clone());
+        if max_connections.map_or(true, |max| max == pool.options().get_max_connections()) {
+            return Ok(pool.clone());
+        }
     }
 
+    let max_connections = max_connections
+        .or_else(|| configured_max_connections(db_path))
+        .unwrap_or(DEFAULT_MAX_CONNECTIONS);
+
     let db_url = format!("sqlite:{}?mode=rwc", db_path);
+    let connect_options = SqliteConnectOptions::from_str(&db_url)
+        .map_err(|e| format!("Invalid database path: {}", e))?
+        .busy_timeout(BUSY_TIMEOUT);
     let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect(&db_url)
+        .max_connections(max_connections)
+        .connect_with(connect_options)
         .await
         .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
-    pools.insert(db_path.to_string(), pool.clone());
+    if let Some(previous) = pools.insert(db_path.to_string(), pool.clone()) {
+        // Let queries still holding a connection from the old pool finish.
+        tokio::spawn(async move { previous.close().await });
+    }
     Ok(pool)
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoolStats {
+    pub max_connections: u32,
+    pub size: u32,
+    pub active: u32,
+    pub idle: u32,
+    pub at_capacity: bool,
+}
+
+/// Connection usage for an index's pool, to diagnose "database is locked"
+/// errors under concurrent indexing and search.
 #[command]
-pub async fn markdown_init_index(index_path: String) -> Result<(), String> {
+pub async fn markdown_pool_stats(index_path: String) -> Result<PoolStats, String> {
     let pool = get_or_create_pool(&index_path).await?;
 
+    let max_connections = pool.options().get_max_connections();
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+    let active = size.saturating_sub(idle);
+
+    Ok(PoolStats {
+        max_connections,
+        size,
+        active,
+        idle,
+        at_capacity: size >= max_connections && idle == 0,
+    })
+}
+
+/// Creates or migrates the index schema. `max_connections` overrides the
+/// pool size for this index.
+#[command]
+pub async fn markdown_init_index(index_path: String, max_connections: Option<u32>) -> Result<(), String> {
+    let pool = get_or_create_pool_with(&index_path, max_connections.map(|max| max.max(1))).await?;
+
     sqlx::query(r#"
         CREATE TABLE IF NOT EXISTS notes (
             id TEXT PRIMARY KEY,
@@ -45,27 +162,192 @@ pub async fn markdown_init_index(index_path: String) -> Result<(), String> {
             created TEXT,
             updated TEXT,
             is_starred INTEGER DEFAULT 0,
-            is_template INTEGER DEFAULT 0
+            is_template INTEGER DEFAULT 0,
+            last_opened TEXT,
+            language TEXT,
+            excerpt TEXT
         )
     "#)
     .execute(&pool)
     .await
     .map_err(|e| format!("Failed to create notes table: {}", e))?;
 
+    // `last_opened` was added after the first release of the schema.
+    let has_last_opened: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('notes') WHERE name = 'last_opened'",
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to inspect notes table: {}", e))?;
+
+    if !has_last_opened {
+        sqlx::query("ALTER TABLE notes ADD COLUMN last_opened TEXT")
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("Failed to add last_opened column: {}", e))?;
+    }
+
+    // So was `language`; it fills in as notes are reindexed.
+    let has_language: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('notes') WHERE name = 'language'",
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to inspect notes table: {}", e))?;
+
+    if !has_language {
+        sqlx::query("ALTER TABLE notes ADD COLUMN language TEXT")
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("Failed to add language column: {}", e))?;
+    }
+
+    // And `excerpt`, filled in from the indexed content so list views have
+    // previews without waiting for every note to be reindexed.
+    let has_excerpt: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('notes') WHERE name = 'excerpt'",
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to inspect notes table: {}", e))?;
+
+    if !has_excerpt {
+        sqlx::query("ALTER TABLE notes ADD COLUMN excerpt TEXT")
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("Failed to add excerpt column: {}", e))?;
+        backfill_excerpts(&pool).await?;
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_notes_last_opened ON notes(last_opened)")
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to create last_opened index: {}", e))?;
+
+    // Earlier indexes declared `content_rowid=id`, which never matched the TEXT
+    // ids and left the FTS table empty. Recreate those against the implicit rowid.
+    let existing_fts: Option<String> = sqlx::query_scalar(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'notes_fts'",
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| format!("Failed to inspect FTS table: {}", e))?;
+
+    let needs_rebuild = match existing_fts {
+        Some(sql) if sql.contains("content_rowid=id") => {
+            sqlx::query("DROP TABLE notes_fts")
+                .execute(&pool)
+                .await
+                .map_err(|e| format!("Failed to drop legacy FTS table: {}", e))?;
+            true
+        }
+        Some(_) => false,
+        None => true,
+    };
+
     sqlx::query(r#"
         CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
             title,
             content,
             tags,
             aliases,
-            content=notes,
-            content_rowid=id
+            content=notes
         )
     "#)
     .execute(&pool)
     .await
     .map_err(|e| format!("Failed to create FTS table: {}", e))?;
 
+    // Earlier indexes reindexed the FTS row on every update; replace that
+    // trigger with the column-scoped one below.
+    let existing_update_trigger: Option<String> = sqlx::query_scalar(
+        "SELECT sql FROM sqlite_master WHERE type = 'trigger' AND name = 'notes_fts_update'",
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| format!("Failed to inspect FTS trigger: {}", e))?;
+
+    if existing_update_trigger.is_some_and(|sql| !sql.contains("WHEN")) {
+        sqlx::query("DROP TRIGGER notes_fts_update")
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("Failed to drop legacy FTS trigger: {}", e))?;
+    }
+
+    for trigger in [
+        r#"
+        CREATE TRIGGER IF NOT EXISTS notes_fts_insert AFTER INSERT ON notes BEGIN
+            INSERT INTO notes_fts(rowid, title, content, tags, aliases)
+            VALUES (new.rowid, new.title, new.content, new.tags, new.aliases);
+        END
+        "#,
+        r#"
+        CREATE TRIGGER IF NOT EXISTS notes_fts_delete AFTER DELETE ON notes BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, title, content, tags, aliases)
+            VALUES ('delete', old.rowid, old.title, old.content, old.tags, old.aliases);
+        END
+        "#,
+        // Only reindex the FTS row when a searchable column changed, so
+        // metadata-only updates like starring skip re-tokenizing the note.
+        r#"
+        CREATE TRIGGER IF NOT EXISTS notes_fts_update AFTER UPDATE OF title, content, tags, aliases ON notes
+        WHEN old.title IS NOT new.title
+            OR old.content IS NOT new.content
+            OR old.tags IS NOT new.tags
+            OR old.aliases IS NOT new.aliases
+        BEGIN
+            INSERT INTO notes_fts(notes_fts, rowid, title, content, tags, aliases)
+            VALUES ('delete', old.rowid, old.title, old.content, old.tags, old.aliases);
+            INSERT INTO notes_fts(rowid, title, content, tags, aliases)
+            VALUES (new.rowid, new.title, new.content, new.tags, new.aliases);
+        END
+        "#,
+    ] {
+        sqlx::query(trigger)
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("Failed to create FTS trigger: {}", e))?;
+    }
+
+    // `unicode61` treats a run of CJK text as one token, so those notes are
+    // also indexed as bigrams; see `cjk::index_cjk`.
+    let has_cjk_fts: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'notes_fts_cjk'",
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to inspect schema: {}", e))?;
+
+    sqlx::query("CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts_cjk USING fts5(title, content)")
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to create CJK FTS table: {}", e))?;
+
+    sqlx::query(r#"
+        CREATE TRIGGER IF NOT EXISTS notes_fts_cjk_delete AFTER DELETE ON notes BEGIN
+            DELETE FROM notes_fts_cjk WHERE rowid = old.rowid;
+        END
+    "#)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to create CJK FTS trigger: {}", e))?;
+
+    if !has_cjk_fts {
+        backfill_cjk(&pool).await?;
+    }
+
+    sqlx::query("CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts_vocab USING fts5vocab(notes_fts, 'row')")
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to create FTS vocabulary table: {}", e))?;
+
+    if needs_rebuild {
+        sqlx::query("INSERT INTO notes_fts(notes_fts) VALUES ('rebuild')")
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("Failed to rebuild FTS table: {}", e))?;
+    }
+
     sqlx::query(r#"
         CREATE TABLE IF NOT EXISTS note_links (
             id TEXT PRIMARY KEY,
@@ -73,13 +355,31 @@ pub async fn markdown_init_index(index_path: String) -> Result<(), String> {
             target_note_path TEXT,
             target_path TEXT NOT NULL,
             link_type TEXT NOT NULL,
-            position_line INTEGER
+            position_line INTEGER,
+            target_key TEXT
         )
     "#)
     .execute(&pool)
     .await
     .map_err(|e| format!("Failed to create note_links table: {}", e))?;
 
+    // `target_key` came later; it lets a newly indexed note find the dangling
+    // links it satisfies without scanning them all.
+    let has_target_key: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('note_links') WHERE name = 'target_key'",
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to inspect note_links table: {}", e))?;
+
+    if !has_target_key {
+        sqlx::query("ALTER TABLE note_links ADD COLUMN target_key TEXT")
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("Failed to add target_key column: {}", e))?;
+        backfill_link_keys(&pool).await?;
+    }
+
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_links_source ON note_links(source_note_path)")
         .execute(&pool)
         .await
@@ -90,62 +390,457 @@ pub async fn markdown_init_index(index_path: String) -> Result<(), String> {
         .await
         .map_err(|e| format!("Failed to create target index: {}", e))?;
 
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_links_target_key ON note_links(target_key)")
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to create target key index: {}", e))?;
+
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS note_blocks (
+            note_path TEXT NOT NULL,
+            block_id TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            is_duplicate INTEGER DEFAULT 0
+        )
+    "#)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to create note_blocks table: {}", e))?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_blocks_note ON note_blocks(note_path, block_id)")
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to create blocks index: {}", e))?;
+
+    let has_headings: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'note_headings'",
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to inspect schema: {}", e))?;
+
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS note_headings (
+            note_path TEXT NOT NULL,
+            level INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            line INTEGER NOT NULL
+        )
+    "#)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to create note_headings table: {}", e))?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_headings_note ON note_headings(note_path)")
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to create headings index: {}", e))?;
+
+    if !has_headings {
+        backfill_headings(&pool).await?;
+    }
+
+    let has_urls: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'note_urls'",
+    )
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to inspect schema: {}", e))?;
+
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS note_urls (
+            note_path TEXT NOT NULL,
+            url TEXT NOT NULL,
+            line INTEGER NOT NULL
+        )
+    "#)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to create note_urls table: {}", e))?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_urls_note ON note_urls(note_path)")
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to create URLs index: {}", e))?;
+
+    if !has_urls {
+        backfill_urls(&pool).await?;
+    }
+
+    // Vectors from the app's embedding model, as little-endian f32s.
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS note_embeddings (
+            note_path TEXT PRIMARY KEY,
+            dimensions INTEGER NOT NULL,
+            vector BLOB NOT NULL,
+            checksum TEXT,
+            updated TEXT NOT NULL
+        )
+    "#)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to create note_embeddings table: {}", e))?;
+
+    // Filled by `markdown_compute_importance`.
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS note_importance (
+            note_path TEXT PRIMARY KEY,
+            score REAL NOT NULL,
+            computed TEXT NOT NULL
+        )
+    "#)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to create note_importance table: {}", e))?;
+
+    // Paths removed from the index, kept so sync clients can propagate deletes.
+    sqlx::query(r#"
+        CREATE TABLE IF NOT EXISTS tombstones (
+            path TEXT PRIMARY KEY,
+            deleted_at TEXT NOT NULL
+        )
+    "#)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to create tombstones table: {}", e))?;
+
     Ok(())
 }
 
-#[command]
-pub async fn markdown_index_note(
-    indexPath: String,
-    path: String,
-    title: String,
-    content: String,
-    frontmatter: String,
-    tags: String,
-    aliases: String,
-    word_count: i32,
-    checksum: String,
+async fn backfill_excerpts(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let rows = sqlx::query("SELECT path, content FROM notes")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    for row in &rows {
+        let content = row.get::<Option<String>, _>("content").unwrap_or_default();
+        sqlx::query("UPDATE notes SET excerpt = ? WHERE path = ?")
+            .bind(note_excerpt(&content, EXCERPT_CHARS))
+            .bind(row.get::<String, _>("path"))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to store excerpt: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit excerpts: {}", e))
+}
+
+/// A fully-derived note row, as written to the `notes` table.
+pub struct NoteRecord {
+    pub path: String,
+    pub title: String,
+    pub content: String,
+    pub frontmatter: String,
+    pub tags: String,
+    pub aliases: String,
+    pub word_count: i32,
+    pub checksum: String,
+}
+
+pub(crate) async fn upsert_note(pool: &Pool<Sqlite>, note: &NoteRecord) -> Result<(), String> {
+    retry_on_lock(|| upsert_note_once(pool, note, None)).await
+}
+
+/// `upsert_note` for batches: links resolve against `resolver`, loaded once
+/// by the caller and updated here with the note's path, title and aliases,
+/// instead of a fresh load of every note per upsert.
+pub(crate) async fn upsert_note_with(
+    pool: &Pool<Sqlite>,
+    note: &NoteRecord,
+    resolver: &mut LinkResolver,
 ) -> Result<(), String> {
-    let pool = get_or_create_pool(&indexPath).await?;
+    let aliases: Vec<String> = serde_json::from_str(&note.aliases).unwrap_or_default();
+    resolver.insert_note(&note.path, &note.title, &aliases);
+    let resolver = &*resolver;
+    retry_on_lock(|| upsert_note_once(pool, note, Some(resolver))).await
+}
 
+async fn upsert_note_once(
+    pool: &Pool<Sqlite>,
+    note: &NoteRecord,
+    resolver: Option<&LinkResolver>,
+) -> Result<(), String> {
     let now = chrono::Utc::now().to_rfc3339();
-    let note_id = format!("note_{}", path.replace('/', "_").replace('\\', "_"));
+    let note_id = format!("note_{}", note.path.replace(['/', '\\'], "_"));
 
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    // The checksum is compared first so unchanged notes rarely need the full
+    // content comparison; the content check guards against stale checksums.
+    // Titles and aliases feed link resolution, so they count for links too.
+    let existing = sqlx::query(r#"
+        SELECT checksum IS ? AND content = ? AS content_unchanged,
+               title = ? AND aliases IS ? AS names_unchanged
+        FROM notes WHERE path = ?
+    "#)
+    .bind(&note.checksum)
+    .bind(&note.content)
+    .bind(&note.title)
+    .bind(&note.aliases)
+    .bind(&note.path)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to check indexed note: {}", e))?;
+
+    let (content_unchanged, names_unchanged) = existing
+        .map(|row| (row.get::<bool, _>("content_unchanged"), row.get::<bool, _>("names_unchanged")))
+        .unwrap_or((false, false));
+
+    // An upsert rather than INSERT OR REPLACE keeps the rowid stable, so the
+    // FTS update trigger fires instead of a silent delete. The trigger skips
+    // rows whose searchable columns are unchanged.
     sqlx::query(r#"
-        INSERT OR REPLACE INTO notes (id, path, title, content, frontmatter, tags, aliases, word_count, checksum, created, updated)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, COALESCE((SELECT created FROM notes WHERE path = ?), ?), ?)
+        INSERT INTO notes (id, path, title, content, frontmatter, tags, aliases, word_count, checksum, created, updated, language, excerpt)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(path) DO UPDATE SET
+            title = excluded.title,
+            content = excluded.content,
+            frontmatter = excluded.frontmatter,
+            tags = excluded.tags,
+            aliases = excluded.aliases,
+            word_count = excluded.word_count,
+            checksum = excluded.checksum,
+            updated = excluded.updated,
+            language = excluded.language,
+            excerpt = excluded.excerpt
     "#)
     .bind(&note_id)
-    .bind(&path)
-    .bind(&title)
-    .bind(&content)
-    .bind(&frontmatter)
-    .bind(&tags)
-    .bind(&aliases)
-    .bind(word_count)
-    .bind(&checksum)
-    .bind(&path)
+    .bind(&note.path)
+    .bind(&note.title)
+    .bind(&note.content)
+    .bind(&note.frontmatter)
+    .bind(&note.tags)
+    .bind(&note.aliases)
+    .bind(note.word_count)
+    .bind(&note.checksum)
     .bind(&now)
     .bind(&now)
-    .execute(&pool)
+    .bind(detect_language(&note.content).language)
+    .bind(note_excerpt(&note.content, EXCERPT_CHARS))
+    .execute(&mut *tx)
     .await
     .map_err(|e| format!("Failed to index note: {}", e))?;
 
+    sqlx::query("DELETE FROM tombstones WHERE path = ?")
+        .bind(&note.path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to clear tombstone: {}", e))?;
+
+    if !content_unchanged {
+        index_blocks(&mut tx, &note.path, &note.content).await?;
+        index_headings(&mut tx, &note.path, &note.content).await?;
+        index_urls(&mut tx, &note.path, &note.content).await?;
+    }
+    if !(content_unchanged && names_unchanged) {
+        let db_path = pool.connect_options().get_filename().to_string_lossy().to_string();
+        let attachments = vault_for_index(&db_path).map(AttachmentResolver::load);
+        let loaded;
+        let resolver = match resolver {
+            Some(resolver) => resolver,
+            None => {
+                loaded = LinkResolver::load(&mut *tx).await?;
+                &loaded
+            }
+        };
+        index_links(&mut tx, &note.path, &note.content, attachments.as_ref(), resolver).await?;
+        index_cjk(&mut tx, &note.path, &note.title, &note.content).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit index: {}", e))?;
+
     Ok(())
 }
 
-#[command]
-pub async fn markdown_remove_from_index(index_path: String, path: String) -> Result<(), String> {
-    let pool = get_or_create_pool(&index_path).await?;
+pub(crate) async fn remove_note(pool: &Pool<Sqlite>, path: &str) -> Result<(), String> {
+    retry_on_lock(|| remove_note_once(pool, path)).await
+}
 
-    sqlx::query("DELETE FROM notes WHERE path = ?")
-        .bind(&path)
-        .execute(&pool)
+async fn record_tombstone(tx: &mut sqlx::Transaction<'_, Sqlite>, path: &str) -> Result<(), String> {
+    sqlx::query(r#"
+        INSERT INTO tombstones (path, deleted_at) VALUES (?, ?)
+        ON CONFLICT(path) DO UPDATE SET deleted_at = excluded.deleted_at
+    "#)
+    .bind(path)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| format!("Failed to record tombstone: {}", e))?;
+    Ok(())
+}
+
+async fn remove_note_once(pool: &Pool<Sqlite>, path: &str) -> Result<(), String> {
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    let removed = sqlx::query("DELETE FROM notes WHERE path = ?")
+        .bind(path)
+        .execute(&mut *tx)
         .await
         .map_err(|e| format!("Failed to remove note: {}", e))?;
 
+    if removed.rows_affected() > 0 {
+        record_tombstone(&mut tx, path).await?;
+    }
+
+    sqlx::query("DELETE FROM note_blocks WHERE note_path = ?")
+        .bind(path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to remove note blocks: {}", e))?;
+
+    sqlx::query("DELETE FROM note_headings WHERE note_path = ?")
+        .bind(path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to remove note headings: {}", e))?;
+
+    sqlx::query("DELETE FROM note_urls WHERE note_path = ?")
+        .bind(path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to remove note URLs: {}", e))?;
+
+    sqlx::query("DELETE FROM note_embeddings WHERE note_path = ?")
+        .bind(path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to remove note embedding: {}", e))?;
+
+    sqlx::query("DELETE FROM note_importance WHERE note_path = ?")
+        .bind(path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to remove note importance: {}", e))?;
+
+    sqlx::query("DELETE FROM note_links WHERE source_note_path = ?")
+        .bind(path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to remove note links: {}", e))?;
+
+    // Links into the removed note become broken rather than disappearing.
+    sqlx::query("UPDATE note_links SET target_note_path = NULL WHERE target_note_path = ?")
+        .bind(path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to unlink note: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit removal: {}", e))?;
+
     Ok(())
 }
 
+/// Moves a note's index rows to a new path, keeping incoming links pointed
+/// at it. The caller reindexes the new path if the content changed too.
+pub(crate) async fn rename_note(pool: &Pool<Sqlite>, old_path: &str, new_path: &str) -> Result<(), String> {
+    retry_on_lock(|| rename_note_once(pool, old_path, new_path)).await
+}
+
+async fn rename_note_once(pool: &Pool<Sqlite>, old_path: &str, new_path: &str) -> Result<(), String> {
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    let renamed = sqlx::query("UPDATE notes SET path = ?, id = ?, updated = ? WHERE path = ?")
+        .bind(new_path)
+        .bind(format!("note_{}", new_path.replace(['/', '\\'], "_")))
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(old_path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to rename note: {}", e))?;
+
+    // To a sync client a rename is a delete of the old path plus a new note.
+    if renamed.rows_affected() > 0 {
+        record_tombstone(&mut tx, old_path).await?;
+    }
+    sqlx::query("DELETE FROM tombstones WHERE path = ?")
+        .bind(new_path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to clear tombstone: {}", e))?;
+
+    // Link ids are `<source>:<n>`, so they move with the source path.
+    sqlx::query(r#"
+        UPDATE note_links SET source_note_path = ?1, id = ?1 || substr(id, length(?2) + 1)
+        WHERE source_note_path = ?2
+    "#)
+    .bind(new_path)
+    .bind(old_path)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to rename note links: {}", e))?;
+
+    for sql in [
+        "UPDATE note_blocks SET note_path = ? WHERE note_path = ?",
+        "UPDATE note_headings SET note_path = ? WHERE note_path = ?",
+        "UPDATE note_urls SET note_path = ? WHERE note_path = ?",
+        "UPDATE note_embeddings SET note_path = ? WHERE note_path = ?",
+        "UPDATE note_importance SET note_path = ? WHERE note_path = ?",
+        "UPDATE note_links SET target_note_path = ? WHERE target_note_path = ?",
+    ] {
+        sqlx::query(sql)
+            .bind(new_path)
+            .bind(old_path)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to rename note: {}", e))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit rename: {}", e))?;
+
+    Ok(())
+}
+
+#[command]
+pub async fn markdown_index_note(
+    indexPath: String,
+    path: String,
+    title: String,
+    content: String,
+    frontmatter: String,
+    tags: String,
+    aliases: String,
+    word_count: i32,
+    checksum: String,
+) -> Result<(), String> {
+    let pool = get_or_create_pool(&indexPath).await?;
+
+    upsert_note(&pool, &NoteRecord {
+        path,
+        title,
+        content,
+        frontmatter,
+        tags,
+        aliases,
+        word_count,
+        checksum,
+    })
+    .await
+}
+
+#[command]
+pub async fn markdown_remove_from_index(index_path: String, path: String) -> Result<(), String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    remove_note(&pool, &path).await
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NoteResult {
     pub id: String,
@@ -160,93 +855,1032 @@ pub struct NoteResult {
     pub word_count: i32,
     pub is_starred: bool,
     pub is_template: bool,
+    pub last_opened: Option<String>,
+    /// ISO 639-3; `None` when undetected or not yet reindexed.
+    pub language: Option<String>,
+    /// The first paragraph, for previews; `None` for notes without one.
+    pub excerpt: Option<String>,
+}
+
+/// Builds a `NoteResult` from a `SELECT * FROM notes` row. Link counts are
+/// left at zero; `markdown_link_counts` fills them in on demand.
+pub(crate) fn note_result_from_row(row: &SqliteRow) -> NoteResult {
+    NoteResult {
+        id: row.get::<String, _>("id"),
+        path: row.get::<String, _>("path"),
+        title: row.get::<String, _>("title"),
+        created: row.get::<Option<String>, _>("created").unwrap_or_default(),
+        updated: row.get::<Option<String>, _>("updated").unwrap_or_default(),
+        tags: row.get::<Option<String>, _>("tags").unwrap_or_else(|| "[]".to_string()),
+        aliases: row.get::<Option<String>, _>("aliases").unwrap_or_else(|| "[]".to_string()),
+        link_count: 0,
+        backlink_count: 0,
+        word_count: row.get::<Option<i32>, _>("word_count").unwrap_or(0),
+        is_starred: row.get::<Option<i32>, _>("is_starred").unwrap_or(0) != 0,
+        is_template: row.get::<Option<i32>, _>("is_template").unwrap_or(0) != 0,
+        last_opened: row.try_get::<Option<String>, _>("last_opened").ok().flatten(),
+        language: row.try_get::<Option<String>, _>("language").ok().flatten(),
+        excerpt: row.try_get::<Option<String>, _>("excerpt").ok().flatten(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteSortField {
+    #[default]
+    Updated,
+    Created,
+    Title,
+    WordCount,
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteListPage {
+    pub notes: Vec<NoteResult>,
+    /// Notes matching the filter across all pages.
+    pub total: i64,
+}
+
+/// Criteria for `markdown_list_notes`; unset fields match every note.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NoteListFilter {
+    /// Vault-relative folder, subfolders included.
+    pub folder: Option<String>,
+    /// Ignores case and a leading `#`.
+    pub tag: Option<String>,
+    /// RFC 3339; only notes updated after it.
+    pub updated_after: Option<String>,
+}
+
+/// The `WHERE` clause for a `NoteListFilter`, bound as folder, tag, then
+/// `updated_after`, with NULL meaning "any".
+const NOTE_LIST_WHERE: &str = r#"
+    WHERE (?1 IS NULL OR substr(path, 1, length(?1) + 1) = ?1 || '/')
+      AND (?2 IS NULL OR EXISTS (
+          SELECT 1 FROM json_each(CASE WHEN json_valid(notes.tags) THEN notes.tags ELSE '[]' END) AS tag
+          WHERE tag.type = 'text' AND lower(ltrim(tag.value, '#')) = ?2
+      ))
+      AND (?3 IS NULL OR updated > ?3)
+"#;
+
+/// Lists indexed notes, newest first by default. The filter, `limit` and
+/// `offset` are bound and `sort_by`/`sort_dir` map onto fixed ORDER BY
+/// clauses, so no caller input is spliced into the SQL.
 #[command]
 pub async fn markdown_list_notes(
     indexPath: String,
-    filter: String,
-) -> Result<Vec<NoteResult>, String> {
+    filter: Option<NoteListFilter>,
+    sort_by: Option<NoteSortField>,
+    sort_dir: Option<SortDirection>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<NoteListPage, String> {
     let pool = get_or_create_pool(&indexPath).await?;
 
-    let query = if filter.is_empty() {
-        "SELECT * FROM notes ORDER BY updated DESC".to_string()
-    } else {
-        format!("SELECT * FROM notes {} ORDER BY updated DESC", filter)
+    let filter = filter.unwrap_or_default();
+    let folder = filter
+        .folder
+        .map(|folder| folder.trim().trim_matches('/').to_string())
+        .filter(|folder| !folder.is_empty());
+    let tag = filter
+        .tag
+        .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+        .filter(|tag| !tag.is_empty());
+    let updated_after = filter.updated_after.filter(|after| !after.trim().is_empty());
+
+    let order_by = match (sort_by.unwrap_or_default(), sort_dir.unwrap_or_default()) {
+        (NoteSortField::Updated, SortDirection::Asc) => "updated ASC, path",
+        (NoteSortField::Updated, SortDirection::Desc) => "updated DESC, path",
+        (NoteSortField::Created, SortDirection::Asc) => "created ASC, path",
+        (NoteSortField::Created, SortDirection::Desc) => "created DESC, path",
+        (NoteSortField::Title, SortDirection::Asc) => "title COLLATE NOCASE ASC, path",
+        (NoteSortField::Title, SortDirection::Desc) => "title COLLATE NOCASE DESC, path",
+        (NoteSortField::WordCount, SortDirection::Asc) => "word_count ASC, path",
+        (NoteSortField::WordCount, SortDirection::Desc) => "word_count DESC, path",
     };
 
-    let rows = sqlx::query(&query)
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM notes {}", NOTE_LIST_WHERE))
+        .bind(&folder)
+        .bind(&tag)
+        .bind(&updated_after)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| format!("Query failed: {}", e))?;
+
+    // SQLite treats a negative LIMIT as no limit.
+    let rows = sqlx::query(&format!("SELECT * FROM notes {} ORDER BY {} LIMIT ?4 OFFSET ?5", NOTE_LIST_WHERE, order_by))
+        .bind(&folder)
+        .bind(&tag)
+        .bind(&updated_after)
+        .bind(limit.filter(|limit| *limit >= 0).unwrap_or(-1))
+        .bind(offset.unwrap_or(0).max(0))
         .fetch_all(&pool)
         .await
         .map_err(|e| format!("Query failed: {}", e))?;
 
     let notes: Vec<NoteResult> = rows
         .iter()
-        .map(|row| {
-            NoteResult {
-                id: row.get::<String, _>("id"),
-                path: row.get::<String, _>("path"),
-                title: row.get::<String, _>("title"),
-                created: row.get::<Option<String>, _>("created").unwrap_or_default(),
-                updated: row.get::<Option<String>, _>("updated").unwrap_or_default(),
-                tags: row.get::<Option<String>, _>("tags").unwrap_or_else(|| "[]".to_string()),
-                aliases: row.get::<Option<String>, _>("aliases").unwrap_or_else(|| "[]".to_string()),
-                link_count: 0,
-                backlink_count: 0,
-                word_count: row.get::<Option<i32>, _>("word_count").unwrap_or(0),
-                is_starred: row.get::<Option<i32>, _>("is_starred").unwrap_or(0) != 0,
-                is_template: row.get::<Option<i32>, _>("is_template").unwrap_or(0) != 0,
-            }
+        .map(note_result_from_row)
+        .collect();
+
+    Ok(NoteListPage { notes, total })
+}
+
+/// Quick-open by file name: notes whose basename contains `substring`,
+/// ignoring case. Matches nearer the start of the name rank first, then
+/// shorter names, so `read` puts `readme.md` ahead of `thread.md`.
+#[command]
+pub async fn markdown_find_by_filename(
+    index_path: String,
+    substring: String,
+    limit: Option<i32>,
+) -> Result<Vec<NoteResult>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let needle = substring.trim().to_lowercase();
+    if needle.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Matched here rather than with LIKE, which only ignores ASCII case and
+    // would drop `Über.md` for `über`. Only paths are loaded for the scan.
+    let paths: Vec<String> = sqlx::query_scalar("SELECT path FROM notes")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Filename lookup failed: {}", e))?;
+
+    let mut matches: Vec<(usize, usize, String)> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.rsplit('/').next().unwrap_or(&path).to_lowercase();
+            let position = name.find(&needle)?;
+            Some((position, name.len(), path))
         })
         .collect();
 
-    Ok(notes)
+    matches.sort();
+    matches.truncate(limit.unwrap_or(20).max(1) as usize);
+
+    let wanted: Vec<&str> = matches.iter().map(|(_, _, path)| path.as_str()).collect();
+    let rows = sqlx::query("SELECT * FROM notes WHERE path IN (SELECT value FROM json_each(?))")
+        .bind(serde_json::to_string(&wanted).map_err(|e| e.to_string())?)
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Filename lookup failed: {}", e))?;
+    let mut notes: HashMap<String, NoteResult> = rows
+        .iter()
+        .map(|row| (row.get::<String, _>("path"), note_result_from_row(row)))
+        .collect();
+
+    Ok(matches.into_iter().filter_map(|(_, _, path)| notes.remove(&path)).collect())
+}
+
+/// Stamps the note as opened now, without touching `updated`. Returns false
+/// when the note isn't indexed.
+#[command]
+pub async fn markdown_record_open(index_path: String, path: String) -> Result<bool, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let result = sqlx::query("UPDATE notes SET last_opened = ? WHERE path = ?")
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(&path)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to record open: {}", e))?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Notes by when they were last opened, most recent first. Notes never
+/// opened are left out.
+#[command]
+pub async fn markdown_list_recently_opened(index_path: String, limit: Option<i32>) -> Result<Vec<NoteResult>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query(r#"
+        SELECT * FROM notes
+        WHERE last_opened IS NOT NULL
+        ORDER BY last_opened DESC
+        LIMIT ?
+    "#)
+    .bind(limit.unwrap_or(20).max(1))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to list recently opened notes: {}", e))?;
+
+    Ok(rows.iter().map(note_result_from_row).collect())
+}
+
+/// Notes whose indexed `word_count` is within `min_words..=max_words`,
+/// largest first unless `sort_dir` is `asc`. Either bound may be left off.
+#[command]
+pub async fn markdown_list_by_size(
+    index_path: String,
+    min_words: Option<i64>,
+    max_words: Option<i64>,
+    sort_dir: Option<SortDirection>,
+    limit: Option<i64>,
+) -> Result<Vec<NoteResult>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let order_by = match sort_dir.unwrap_or_default() {
+        SortDirection::Asc => "COALESCE(word_count, 0) ASC, path",
+        SortDirection::Desc => "COALESCE(word_count, 0) DESC, path",
+    };
+    let rows = sqlx::query(&format!(
+        "SELECT * FROM notes WHERE COALESCE(word_count, 0) BETWEEN ? AND ? ORDER BY {} LIMIT ?",
+        order_by
+    ))
+    .bind(min_words.unwrap_or(0))
+    .bind(max_words.unwrap_or(i64::MAX))
+    .bind(limit.filter(|limit| *limit >= 0).unwrap_or(-1))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to list notes by size: {}", e))?;
+
+    Ok(rows.iter().map(note_result_from_row).collect())
+}
+
+const EMPTY_NOTE_MAX_WORDS: i64 = 3;
+
+/// Stubs: notes with at most `max_words` words (default 3), smallest first.
+#[command]
+pub async fn markdown_list_empty_notes(index_path: String, max_words: Option<i64>) -> Result<Vec<NoteResult>, String> {
+    markdown_list_by_size(
+        index_path,
+        None,
+        Some(max_words.unwrap_or(EMPTY_NOTE_MAX_WORDS)),
+        Some(SortDirection::Asc),
+        None,
+    )
+    .await
+}
+
+/// Notes indexed without frontmatter, or with an empty block, by path.
+#[command]
+pub async fn markdown_list_missing_frontmatter(index_path: String) -> Result<Vec<NoteResult>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let rows = sqlx::query(r#"
+        SELECT * FROM notes
+        WHERE frontmatter IS NULL OR trim(frontmatter) IN ('', '{}', 'null')
+        ORDER BY path
+    "#)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to list notes: {}", e))?;
+    Ok(rows.iter().map(note_result_from_row).collect())
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiblingSort {
+    /// File name, ignoring case, as in the file tree.
+    #[default]
+    Name,
+    /// Most recently updated first, as in the note list.
+    Updated,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SiblingNotes {
+    pub previous: Option<String>,
+    pub next: Option<String>,
+}
+
+/// The notes before and after `path` within its folder, for stepping
+/// through a folder with the keyboard. Subfolders aren't entered, and a
+/// missing neighbour at either end is `None`.
+#[command]
+pub async fn markdown_sibling_notes(
+    index_path: String,
+    path: String,
+    sort: Option<SiblingSort>,
+) -> Result<SiblingNotes, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let folder = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+    let prefix = if folder.is_empty() { String::new() } else { format!("{}/", folder) };
+    let rows = sqlx::query("SELECT path, updated FROM notes WHERE path LIKE ? ESCAPE '\\'")
+        .bind(format!("{}%", escape_like(&prefix)))
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load folder: {}", e))?;
+
+    let mut siblings: Vec<(String, String)> = rows
+        .iter()
+        .map(|row| (row.get::<String, _>("path"), row.get::<Option<String>, _>("updated").unwrap_or_default()))
+        .filter(|(p, _)| p.starts_with(&prefix) && !p[prefix.len()..].contains('/'))
+        .collect();
+    match sort.unwrap_or_default() {
+        SiblingSort::Name => siblings.sort_by_cached_key(|(p, _)| (p.to_lowercase(), p.clone())),
+        SiblingSort::Updated => siblings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0))),
+    }
+
+    let position = siblings
+        .iter()
+        .position(|(p, _)| *p == path)
+        .ok_or_else(|| format!("Note not indexed: {}", path))?;
+
+    Ok(SiblingNotes {
+        previous: position.checked_sub(1).map(|i| siblings[i].0.clone()),
+        next: siblings.get(position + 1).map(|(p, _)| p.clone()),
+    })
+}
+
+/// Per-column bm25 weights for `markdown_search_notes`. Omitted fields fall
+/// back to the defaults, which favour title and tag matches over body text.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchWeights {
+    pub title: Option<f64>,
+    pub content: Option<f64>,
+    pub tags: Option<f64>,
+    pub aliases: Option<f64>,
 }
 
+/// Wall-clock cap on the regex refinement pass in `markdown_search_notes`.
+const REGEX_FILTER_BUDGET: Duration = Duration::from_secs(2);
+const MAX_REGEX_MATCHES_PER_NOTE: usize = 100;
+
 #[command]
 pub async fn markdown_search_notes(
     indexPath: String,
     query: String,
     limit: Option<i32>,
+    weights: Option<SearchWeights>,
+    regex_filter: Option<String>,
 ) -> Result<Vec<serde_json::Value>, String> {
     let pool = get_or_create_pool(&indexPath).await?;
 
     let limit = limit.unwrap_or(50);
+    let weights = weights.unwrap_or_default();
+    let regex = regex_filter
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| build_grep_regex(&pattern, false, false))
+        .transpose()?;
+    // The regex refinement below runs over the default index only.
+    if has_cjk(&query) && regex.is_none() {
+        return search_cjk(&pool, &query, weights.title.unwrap_or(10.0), weights.content.unwrap_or(1.0), limit).await;
+    }
     let fts_query = query
         .split_whitespace()
         .map(|term| format!("\"{}\"*", term))
         .collect::<Vec<_>>()
         .join(" OR ");
 
+    // bm25 is lower-is-better; negate it so callers can keep treating a
+    // higher score as a better match.
     let sql = r#"
-        SELECT n.*, bm25(notes_fts) as score,
+        SELECT n.*, -bm25(notes_fts, ?, ?, ?, ?) as score,
                snippet(notes_fts, 1, '<mark>', '</mark>', '...', 32) as snippet
         FROM notes_fts
-        JOIN notes n ON notes_fts.rowid = n.id
+        JOIN notes n ON notes_fts.rowid = n.rowid
         WHERE notes_fts MATCH ?
         ORDER BY score DESC
         LIMIT ?
     "#;
 
     let rows = sqlx::query(sql)
+        .bind(weights.title.unwrap_or(10.0))
+        .bind(weights.content.unwrap_or(1.0))
+        .bind(weights.tags.unwrap_or(5.0))
+        .bind(weights.aliases.unwrap_or(5.0))
         .bind(&fts_query)
-        .bind(limit)
+        // With a regex filter the limit applies after filtering; -1 is no limit.
+        .bind(if regex.is_some() { -1 } else { limit })
         .fetch_all(&pool)
         .await
         .map_err(|e| format!("Search failed: {}", e))?;
 
-    let results: Vec<serde_json::Value> = rows
-        .iter()
-        .map(|row| {
-            serde_json::json!({
-                "id": row.get::<String, _>("id"),
-                "path": row.get::<String, _>("path"),
-                "title": row.get::<String, _>("title"),
-                "score": row.get::<f64, _>("score"),
-                "snippet": row.get::<Option<String>, _>("snippet").unwrap_or_default(),
+    let Some(regex) = regex else {
+        let results: Vec<serde_json::Value> = rows
+            .iter()
+            .map(|row| {
+                serde_json::json!({
+                    "id": row.get::<String, _>("id"),
+                    "path": row.get::<String, _>("path"),
+                    "title": row.get::<String, _>("title"),
+                    "score": row.get::<f64, _>("score"),
+                    "snippet": row.get::<Option<String>, _>("snippet").unwrap_or_default(),
+                })
             })
-        })
-        .collect();
+            .collect();
+
+        return Ok(results);
+    };
+
+    let started = Instant::now();
+    let mut results = Vec::new();
+    for row in &rows {
+        if started.elapsed() > REGEX_FILTER_BUDGET {
+            return Err("Regex filter took too long; narrow the search query".to_string());
+        }
+        if results.len() >= limit.max(0) as usize {
+            break;
+        }
+
+        let content = row.get::<String, _>("content");
+        let matches: Vec<serde_json::Value> = content
+            .lines()
+            .enumerate()
+            .flat_map(|(line, text)| {
+                regex.find_iter(text).map(move |found| {
+                    serde_json::json!({ "line": line, "start": found.start(), "end": found.end() })
+                })
+            })
+            .take(MAX_REGEX_MATCHES_PER_NOTE)
+            .collect();
+        if matches.is_empty() {
+            continue;
+        }
+
+        results.push(serde_json::json!({
+            "id": row.get::<String, _>("id"),
+            "path": row.get::<String, _>("path"),
+            "title": row.get::<String, _>("title"),
+            "score": row.get::<f64, _>("score"),
+            "snippet": row.get::<Option<String>, _>("snippet").unwrap_or_default(),
+            "regex_matches": matches,
+        }));
+    }
 
     Ok(results)
 }
+
+const DEFAULT_EXPORT_LIMIT: i32 = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchExport {
+    pub path: String,
+    pub result_count: usize,
+}
+
+/// Runs a search and saves the hits as a new note at `out_path`: a header
+/// with the query and time, then a `[[wikilink]]` per hit with its snippet,
+/// matches marked `==like this==`. The note is indexed straight away, so it
+/// can serve as a starting point for a MOC. `limit` defaults to 100.
+#[command]
+pub async fn markdown_export_search_results(
+    vault_path: String,
+    index_path: String,
+    query: String,
+    out_path: String,
+    limit: Option<i32>,
+) -> Result<SearchExport, String> {
+    let mut rel_path = normalize_rel_path(&out_path)?;
+    if rel_path.is_empty() {
+        return Err("Output path is empty".to_string());
+    }
+    if !is_markdown_path(&rel_path) {
+        rel_path.push_str(".md");
+    }
+    let full_path = resolve_in_vault(&vault_path, &rel_path)?;
+    if full_path.exists() {
+        return Err(format!("A file already exists at {}", rel_path));
+    }
+
+    let hits = markdown_search_notes(
+        index_path.clone(),
+        query.clone(),
+        Some(limit.unwrap_or(DEFAULT_EXPORT_LIMIT)),
+        None,
+        None,
+    )
+    .await?;
+
+    let mut report = format!(
+        "# Search: {}\n\nQuery `{}`, exported {}. {} result{}.\n\n",
+        query.trim(),
+        query.trim().replace('`', "'"),
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        hits.len(),
+        if hits.len() == 1 { "" } else { "s" },
+    );
+    for hit in &hits {
+        let path = hit["path"].as_str().unwrap_or_default();
+        let target = path.strip_suffix(".md").unwrap_or(path);
+        let title: String = hit["title"].as_str().unwrap_or_default().replace(['[', ']', '|'], "");
+        let stem = target.rsplit('/').next().unwrap_or(target);
+        if title.is_empty() || title == stem {
+            report.push_str(&format!("- [[{}]]\n", target));
+        } else {
+            report.push_str(&format!("- [[{}|{}]]\n", target, title));
+        }
+
+        let snippet = hit["snippet"]
+            .as_str()
+            .unwrap_or_default()
+            .replace("<mark>", "==")
+            .replace("</mark>", "==")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !snippet.is_empty() {
+            report.push_str(&format!("  {}\n", snippet));
+        }
+    }
+
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+    }
+    write_atomic(&full_path, &report)?;
+    let pool = get_or_create_pool(&index_path).await?;
+    reindex_file(&pool, &vault_path, &rel_path).await?;
+
+    Ok(SearchExport {
+        path: rel_path,
+        result_count: hits.len(),
+    })
+}
+
+/// Escapes `%`, `_` and `\` for use in a `LIKE ... ESCAPE '\'` pattern.
+pub(crate) fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchSuggestion {
+    /// `title`, `tag` or `term`.
+    pub kind: String,
+    pub value: String,
+    pub path: Option<String>,
+    /// Number of notes a tag or term appears in; zero for titles.
+    pub frequency: i64,
+}
+
+/// Lightweight completions for a search box: recently updated titles starting
+/// with the prefix (or with a word starting with it), then the most used
+/// matching tags, then the most widespread indexed terms.
+#[command]
+pub async fn markdown_search_suggest(
+    index_path: String,
+    prefix: String,
+    limit: Option<i32>,
+) -> Result<Vec<SearchSuggestion>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let limit = limit.unwrap_or(10).max(1);
+    let prefix = prefix.trim().to_string();
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+    let escaped = escape_like(&prefix);
+
+    let titles = sqlx::query(r#"
+        SELECT title, path, title LIKE ? ESCAPE '\' AS starts
+        FROM notes
+        WHERE title LIKE ? ESCAPE '\' OR title LIKE ? ESCAPE '\'
+        ORDER BY starts DESC, updated DESC
+        LIMIT ?
+    "#)
+    .bind(format!("{}%", escaped))
+    .bind(format!("{}%", escaped))
+    .bind(format!("% {}%", escaped))
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Title lookup failed: {}", e))?;
+
+    let tags = sqlx::query(r#"
+        SELECT tag.value AS tag, COUNT(*) AS frequency
+        FROM notes, json_each(CASE WHEN json_valid(notes.tags) THEN notes.tags ELSE '[]' END) AS tag
+        WHERE tag.value LIKE ? ESCAPE '\'
+        GROUP BY tag.value
+        ORDER BY frequency DESC
+        LIMIT ?
+    "#)
+    .bind(format!("{}%", escaped))
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Tag lookup failed: {}", e))?;
+
+    // The vocabulary is stored lowercased by the unicode61 tokenizer; a range
+    // scan on `term` keeps this cheap enough for every keystroke.
+    let term_prefix = prefix.to_lowercase();
+    let terms = sqlx::query(r#"
+        SELECT term, doc FROM notes_fts_vocab
+        WHERE term >= ? AND term < ?
+        ORDER BY doc DESC
+        LIMIT ?
+    "#)
+    .bind(&term_prefix)
+    .bind(format!("{}\u{10ffff}", term_prefix))
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Term lookup failed: {}", e))?;
+
+    let mut seen = std::collections::HashSet::new();
+    let mut suggestions = Vec::new();
+
+    for row in &titles {
+        let value = row.get::<String, _>("title");
+        if seen.insert(value.to_lowercase()) {
+            suggestions.push(SearchSuggestion {
+                kind: "title".to_string(),
+                value,
+                path: Some(row.get::<String, _>("path")),
+                frequency: 0,
+            });
+        }
+    }
+    for row in &tags {
+        let value = row.get::<String, _>("tag");
+        if seen.insert(value.to_lowercase()) {
+            suggestions.push(SearchSuggestion {
+                kind: "tag".to_string(),
+                value,
+                path: None,
+                frequency: row.get::<i64, _>("frequency"),
+            });
+        }
+    }
+    for row in &terms {
+        let value = row.get::<String, _>("term");
+        if seen.insert(value.to_lowercase()) {
+            suggestions.push(SearchSuggestion {
+                kind: "term".to_string(),
+                value,
+                path: None,
+                frequency: row.get::<i64, _>("doc"),
+            });
+        }
+    }
+
+    suggestions.truncate(limit as usize);
+    Ok(suggestions)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagCooccurrence {
+    pub tag: String,
+    /// Number of notes tagged with both this tag and the one asked about.
+    pub count: i64,
+}
+
+/// Tags that most often share a note with `tag`, for suggesting related tags
+/// while editing. Matching ignores case and a leading `#`.
+#[command]
+pub async fn markdown_tag_cooccurrence(
+    index_path: String,
+    tag: String,
+    limit: Option<i32>,
+) -> Result<Vec<TagCooccurrence>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    if tag.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // DISTINCT guards against a note listing the same tag twice.
+    let rows = sqlx::query(r#"
+        WITH note_tags AS (
+            SELECT DISTINCT notes.rowid AS note_id, tag.value AS tag
+            FROM notes, json_each(CASE WHEN json_valid(notes.tags) THEN notes.tags ELSE '[]' END) AS tag
+            WHERE tag.type = 'text'
+        ),
+        tagged AS (
+            SELECT DISTINCT note_id FROM note_tags
+            WHERE lower(ltrim(tag, '#')) = ?
+        )
+        SELECT other.tag AS tag, COUNT(DISTINCT other.note_id) AS count
+        FROM tagged
+        JOIN note_tags AS other ON other.note_id = tagged.note_id
+        WHERE lower(ltrim(other.tag, '#')) != ?
+        GROUP BY lower(ltrim(other.tag, '#'))
+        ORDER BY count DESC, tag
+        LIMIT ?
+    "#)
+    .bind(&tag)
+    .bind(&tag)
+    .bind(limit.unwrap_or(10).max(1))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to compute tag co-occurrence: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| TagCooccurrence {
+            tag: row.get::<String, _>("tag"),
+            count: row.get::<i64, _>("count"),
+        })
+        .collect())
+}
+
+/// Notes tagged `tag`, from frontmatter or inline, ignoring case and a
+/// leading `#`. With `include_nested` (the default) `#area` also matches
+/// `#area/subarea`. Most recently updated first.
+#[command]
+pub async fn markdown_search_by_tag(
+    index_path: String,
+    tag: String,
+    include_nested: Option<bool>,
+) -> Result<Vec<NoteResult>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let tag = tag.trim().trim_start_matches('#').trim_end_matches('/').to_lowercase();
+    if tag.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let rows = sqlx::query(r#"
+        SELECT * FROM notes
+        WHERE EXISTS (
+            SELECT 1 FROM json_each(CASE WHEN json_valid(notes.tags) THEN notes.tags ELSE '[]' END) AS tag
+            WHERE tag.type = 'text'
+              AND (lower(ltrim(tag.value, '#')) = ?
+                   OR (? AND lower(ltrim(tag.value, '#')) LIKE ? ESCAPE '\'))
+        )
+        ORDER BY updated DESC, path
+    "#)
+    .bind(&tag)
+    .bind(include_nested.unwrap_or(true))
+    .bind(format!("{}/%", escape_like(&tag)))
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Tag search failed: {}", e))?;
+
+    Ok(rows.iter().map(note_result_from_row).collect())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagTreeNode {
+    /// Last segment of the tag, e.g. `subarea`.
+    pub name: String,
+    /// Full tag, e.g. `area/subarea`.
+    pub tag: String,
+    /// Notes tagged with exactly this tag.
+    pub count: i64,
+    /// Notes tagged with this tag or anything below it, each counted once.
+    pub total: i64,
+    pub children: Vec<TagTreeNode>,
+}
+
+#[derive(Default)]
+struct TagTreeBuilder {
+    name: String,
+    tag: String,
+    count: i64,
+    total: i64,
+    children: BTreeMap<String, TagTreeBuilder>,
+}
+
+impl TagTreeBuilder {
+    fn build(self) -> TagTreeNode {
+        TagTreeNode {
+            name: self.name,
+            tag: self.tag,
+            count: self.count,
+            total: self.total,
+            children: self.children.into_values().map(TagTreeBuilder::build).collect(),
+        }
+    }
+}
+
+/// All tags as a tree split on `/`, for a hierarchical tag browser. Tags
+/// group ignoring case, keeping the spelling seen first in path order, and
+/// siblings are sorted by name.
+#[command]
+pub async fn markdown_tag_tree(index_path: String) -> Result<Vec<TagTreeNode>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query("SELECT tags FROM notes ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load tags: {}", e))?;
+
+    let mut root = TagTreeBuilder::default();
+    for row in &rows {
+        let tags: Vec<String> = row
+            .get::<Option<String>, _>("tags")
+            .and_then(|t| serde_json::from_str(&t).ok())
+            .unwrap_or_default();
+
+        // Sets per note, so a note tagged both `a` and `a/b` adds one to
+        // `a`'s total, not two.
+        let mut own: HashSet<String> = HashSet::new();
+        let mut within: HashSet<String> = HashSet::new();
+        for tag in &tags {
+            let segments: Vec<&str> = tag
+                .trim()
+                .trim_start_matches('#')
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .collect();
+            if segments.is_empty() {
+                continue;
+            }
+
+            let mut node = &mut root;
+            for depth in 0..segments.len() {
+                let prefix = segments[..=depth].join("/");
+                let key = prefix.to_lowercase();
+                node = node.children.entry(segments[depth].to_lowercase()).or_insert_with(|| TagTreeBuilder {
+                    name: segments[depth].to_string(),
+                    tag: prefix,
+                    ..Default::default()
+                });
+                if within.insert(key.clone()) {
+                    node.total += 1;
+                }
+                if depth == segments.len() - 1 && own.insert(key) {
+                    node.count += 1;
+                }
+            }
+        }
+    }
+
+    Ok(root.build().children)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FolderTagCount {
+    pub tag: String,
+    /// Notes in the folder with this tag.
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FolderStats {
+    pub folder: String,
+    pub note_count: i64,
+    pub total_words: i64,
+    pub last_updated: Option<String>,
+    /// Most used first.
+    pub tags: Vec<FolderTagCount>,
+}
+
+/// Counts, words and tags for the notes under `folder_rel`, from the index
+/// alone. Subfolders are included unless `recursive` is false; an empty
+/// folder means the whole vault.
+#[command]
+pub async fn markdown_folder_stats(
+    index_path: String,
+    folder_rel: String,
+    recursive: Option<bool>,
+) -> Result<FolderStats, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let folder = folder_rel.trim().trim_matches('/').to_string();
+    let prefix = if folder.is_empty() { String::new() } else { format!("{}/", folder) };
+    let recursive = recursive.unwrap_or(true);
+
+    let rows = sqlx::query("SELECT path, word_count, updated, tags FROM notes WHERE path LIKE ? ESCAPE '\\'")
+        .bind(format!("{}%", escape_like(&prefix)))
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load folder: {}", e))?;
+
+    let mut stats = FolderStats {
+        folder,
+        note_count: 0,
+        total_words: 0,
+        last_updated: None,
+        tags: Vec::new(),
+    };
+    // Keyed ignoring case; the first spelling seen is reported.
+    let mut tags: HashMap<String, FolderTagCount> = HashMap::new();
+    for row in &rows {
+        let path = row.get::<String, _>("path");
+        if !path.starts_with(&prefix) || (!recursive && path[prefix.len()..].contains('/')) {
+            continue;
+        }
+
+        stats.note_count += 1;
+        stats.total_words += row.get::<Option<i64>, _>("word_count").unwrap_or(0);
+        if let Some(updated) = row.get::<Option<String>, _>("updated") {
+            if stats.last_updated.as_ref().map_or(true, |latest| updated > *latest) {
+                stats.last_updated = Some(updated);
+            }
+        }
+
+        let note_tags: Vec<String> = row
+            .get::<Option<String>, _>("tags")
+            .and_then(|t| serde_json::from_str(&t).ok())
+            .unwrap_or_default();
+        let unique: HashSet<String> = note_tags.iter().map(|t| t.to_lowercase()).collect();
+        for key in unique {
+            let spelling = note_tags.iter().find(|t| t.to_lowercase() == key).cloned().unwrap_or_default();
+            tags.entry(key)
+                .or_insert(FolderTagCount { tag: spelling, count: 0 })
+                .count += 1;
+        }
+    }
+
+    stats.tags = tags.into_values().collect();
+    stats.tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::indexer::compute_checksum;
+
+    /// A fresh index in a temp dir, kept alive by the returned guard.
+    async fn test_index() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let index_path = dir.path().join("index.db").to_string_lossy().to_string();
+        markdown_init_index(index_path.clone(), None).await.expect("init index");
+        (dir, index_path)
+    }
+
+    fn record(path: &str, title: &str, content: &str) -> NoteRecord {
+        NoteRecord {
+            path: path.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            frontmatter: "{}".to_string(),
+            tags: "[]".to_string(),
+            aliases: "[]".to_string(),
+            word_count: content.split_whitespace().count() as i32,
+            checksum: compute_checksum(content),
+        }
+    }
+
+    #[tokio::test]
+    async fn title_match_outranks_body_match() {
+        let (_dir, index_path) = test_index().await;
+        let pool = get_or_create_pool(&index_path).await.unwrap();
+        upsert_note(&pool, &record("body.md", "Misc", "Gardening tips, and more gardening.")).await.unwrap();
+        upsert_note(&pool, &record("title.md", "Gardening", "Notes about soil.")).await.unwrap();
+
+        let results = markdown_search_notes(index_path, "gardening".to_string(), None, None, None).await.unwrap();
+        let paths: Vec<&str> = results.iter().filter_map(|r| r["path"].as_str()).collect();
+        assert_eq!(paths, vec!["title.md", "body.md"]);
+    }
+
+    #[tokio::test]
+    async fn filename_lookup_ignores_non_ascii_case() {
+        let (_dir, index_path) = test_index().await;
+        let pool = get_or_create_pool(&index_path).await.unwrap();
+        upsert_note(&pool, &record("Über Plan.md", "Über Plan", "")).await.unwrap();
+        upsert_note(&pool, &record("trips/über.md", "Trip", "")).await.unwrap();
+        upsert_note(&pool, &record("über/other.md", "Other", "")).await.unwrap();
+
+        let results = markdown_find_by_filename(index_path, "ÜBER".to_string(), None).await.unwrap();
+        let paths: Vec<&str> = results.iter().map(|note| note.path.as_str()).collect();
+        assert_eq!(paths, vec!["trips/über.md", "Über Plan.md"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_writers_never_see_lock_errors() {
+        let (_dir, index_path) = test_index().await;
+        let pool = get_or_create_pool(&index_path).await.unwrap();
+
+        let writers: Vec<_> = (0..32)
+            .map(|i| {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    // Half the writers share a path so they contend on the same rows.
+                    let path = if i % 2 == 0 { format!("note-{}.md", i) } else { "shared.md".to_string() };
+                    upsert_note(&pool, &record(&path, &format!("Note {}", i), &format!("[[shared]] body {}", i))).await
+                })
+            })
+            .collect();
+
+        for writer in writers {
+            let result = writer.await.expect("writer panicked");
+            assert!(result.is_ok(), "write failed: {:?}", result);
+        }
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notes").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 17);
+    }
+
+    #[tokio::test]
+    async fn batch_upserts_resolve_only_the_dangling_links_they_satisfy() {
+        let (_dir, index_path) = test_index().await;
+        let pool = get_or_create_pool(&index_path).await.unwrap();
+        let mut resolver = LinkResolver::load(&pool).await.unwrap();
+
+        let source = record("a.md", "A", "[[ÜBER PLAN]] [[dir/Later]] [[missing]]");
+        upsert_note_with(&pool, &source, &mut resolver).await.unwrap();
+        upsert_note_with(&pool, &record("plan.md", "Über Plan", ""), &mut resolver).await.unwrap();
+        upsert_note_with(&pool, &record("dir/later.md", "Later", ""), &mut resolver).await.unwrap();
+
+        let resolved = |target: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, Option<String>>("SELECT target_note_path FROM note_links WHERE target_path = ?")
+                    .bind(target)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+        // The title only matches once lowercased outside SQLite, whose
+        // `lower()` leaves `Ü` alone.
+        assert_eq!(resolved("ÜBER PLAN").await.as_deref(), Some("plan.md"));
+        assert_eq!(resolved("dir/Later").await.as_deref(), Some("dir/later.md"));
+        assert_eq!(resolved("missing").await, None);
+    }
+}
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::Row;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use tauri::command;
+
+use super::attachments::{relative_to, AttachmentResolver};
+use super::bundle::MissingDependency;
+use super::convert::{convert_wikilinks_with, ConvertWikilinksOptions, UnresolvedLinkMode};
+use super::embeds::is_attachment_target;
+use super::frontmatter::parse_frontmatter;
+use super::links::{find_markdown_links, is_external_url, percent_decode, percent_encode_path, LinkResolver};
+use super::permalinks::assign_permalinks;
+use super::render::{escape_html, render_html, RenderOptions};
+use super::search::get_or_create_pool;
+use super::vault::resolve_in_vault;
+
+/// Attachments are copied under this folder. Slugs never contain `_`, so it
+/// can't collide with a page.
+const SITE_FILES_DIR: &str = "_files";
+const SEARCH_EXCERPT_CHARS: usize = 300;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExportSiteOptions {
+    /// Only publish notes with `publish: true` in their frontmatter. Without
+    /// it every note is published unless it opts out.
+    #[serde(default)]
+    pub require_publish: bool,
+    /// Shown on the index page and in page titles. Defaults to "Notes".
+    pub site_title: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublishedNote {
+    pub path: String,
+    pub slug: String,
+    /// Page location relative to `out_dir`.
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportSiteResult {
+    pub out_dir: String,
+    pub published: Vec<PublishedNote>,
+    /// Notes left out because they are private or not marked for publishing.
+    pub skipped: Vec<String>,
+    pub attachments: Vec<String>,
+    /// Links to missing or unpublished notes, rendered as plain text.
+    pub unresolved: Vec<MissingDependency>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SearchEntry {
+    title: String,
+    url: String,
+    tags: Vec<String>,
+    excerpt: String,
+}
+
+fn is_true(value: Option<&Value>) -> bool {
+    match value {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => s.eq_ignore_ascii_case("true"),
+        _ => false,
+    }
+}
+
+fn is_false(value: Option<&Value>) -> bool {
+    match value {
+        Some(Value::Bool(b)) => !*b,
+        Some(Value::String(s)) => s.eq_ignore_ascii_case("false"),
+        _ => false,
+    }
+}
+
+fn page_url(slug: &str) -> String {
+    format!("{}/index.html", slug)
+}
+
+fn page_html(site_title: &str, title: &str, home: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title} - {site}</title>\n</head>\n<body>\n\
+         <nav><a href=\"{home}\">{site}</a></nav>\n<main>\n<h1>{title}</h1>\n{body}</main>\n</body>\n</html>\n",
+        title = escape_html(title),
+        site = escape_html(site_title),
+        home = escape_html(home),
+        body = body,
+    )
+}
+
+fn write_file(out_dir: &Path, rel_path: &str, contents: &[u8]) -> Result<(), String> {
+    let path = out_dir.join(rel_path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Publishes the vault as static HTML: one `<slug>/index.html` per note, an
+/// `index.html` listing them and a `search.json` for client-side search.
+/// Notes with `private: true` or `publish: false` are always skipped. Links
+/// point at permalinks; links to unpublished notes become plain text.
+/// Referenced attachments are copied under `_files/`.
+#[command]
+pub async fn markdown_export_site(
+    vault_path: String,
+    index_path: String,
+    out_dir: String,
+    options: Option<ExportSiteOptions>,
+) -> Result<ExportSiteResult, String> {
+    let options = options.unwrap_or_default();
+    let site_title = options.site_title.clone().unwrap_or_else(|| "Notes".to_string());
+    let out = Path::new(&out_dir);
+    std::fs::create_dir_all(out).map_err(|e| format!("Failed to create output folder: {}", e))?;
+
+    let pool = get_or_create_pool(&index_path).await?;
+    let resolver = LinkResolver::load(&pool).await?;
+    let attachments = AttachmentResolver::load(&vault_path);
+    let permalinks = assign_permalinks(&pool).await?;
+
+    let tag_rows = sqlx::query("SELECT path, tags FROM notes")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load tags: {}", e))?;
+    let tags: HashMap<String, Vec<String>> = tag_rows
+        .iter()
+        .map(|row| {
+            let tags = row
+                .get::<Option<String>, _>("tags")
+                .and_then(|t| serde_json::from_str(&t).ok())
+                .unwrap_or_default();
+            (row.get::<String, _>("path"), tags)
+        })
+        .collect();
+
+    // Decide what gets published before rendering, so links can tell
+    // published targets from unpublished ones.
+    let mut skipped = Vec::new();
+    let mut pages: Vec<(String, String, String, String)> = Vec::new();
+    for permalink in &permalinks {
+        let raw = match resolve_in_vault(&vault_path, &permalink.path)
+            .and_then(|p| std::fs::read_to_string(p).map_err(|e| format!("Failed to read note: {}", e)))
+        {
+            Ok(raw) => raw,
+            Err(e) => {
+                log::warn!("Skipping {}: {}", permalink.path, e);
+                skipped.push(permalink.path.clone());
+                continue;
+            }
+        };
+        let (frontmatter, _) = parse_frontmatter(&raw);
+        let private = is_true(frontmatter.get("private")) || is_false(frontmatter.get("publish"));
+        if private || (options.require_publish && !is_true(frontmatter.get("publish"))) {
+            skipped.push(permalink.path.clone());
+            continue;
+        }
+        let title = frontmatter
+            .get("title")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| {
+                let name = permalink.path.rsplit('/').next().unwrap_or(&permalink.path);
+                name.strip_suffix(".md").unwrap_or(name).to_string()
+            });
+        pages.push((permalink.path.clone(), permalink.slug.clone(), title, raw));
+    }
+    let published_slugs: HashMap<String, String> = pages
+        .iter()
+        .map(|(path, slug, _, _)| (path.clone(), slug.clone()))
+        .collect();
+
+    let mut copied: BTreeSet<String> = BTreeSet::new();
+    let mut unresolved = Vec::new();
+    let mut published = Vec::new();
+    let mut search = Vec::new();
+
+    for (path, slug, title, raw) in &pages {
+        let url = page_url(slug);
+        let (_, body) = parse_frontmatter(raw);
+
+        let mut referenced: Vec<String> = Vec::new();
+
+        // Standard markdown links are relative to the note in the vault;
+        // point them at the exported pages and copies instead.
+        let mut linked = String::new();
+        let mut in_fence = false;
+        for line in body.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            if in_fence {
+                linked.push_str(line);
+                continue;
+            }
+            let mut cursor = 0;
+            for link in find_markdown_links(line) {
+                if is_external_url(&link.target) || link.target.starts_with('#') || link.target.is_empty() {
+                    continue;
+                }
+                let (target, anchor) = match link.target.split_once('#') {
+                    Some((target, anchor)) => (target, Some(anchor)),
+                    None => (link.target.as_str(), None),
+                };
+                let decoded = percent_decode(target);
+                let destination = if link.is_image || is_attachment_target(&decoded) {
+                    attachments.resolve(path, &decoded).map(|file| {
+                        referenced.push(file.clone());
+                        relative_to(&url, &format!("{}/{}", SITE_FILES_DIR, file))
+                    })
+                } else {
+                    resolver
+                        .resolve(path, decoded.strip_suffix(".md").unwrap_or(&decoded))
+                        .and_then(|note| published_slugs.get(&note))
+                        .map(|slug| relative_to(&url, &page_url(slug)))
+                };
+                let Some(mut destination) = destination else {
+                    unresolved.push(MissingDependency {
+                        source_path: path.clone(),
+                        target: decoded,
+                        reason: "Not found or not published".to_string(),
+                    });
+                    continue;
+                };
+                destination = percent_encode_path(&destination);
+                if let Some(anchor) = anchor {
+                    destination.push('#');
+                    destination.push_str(anchor);
+                }
+                linked.push_str(&line[cursor..link.start]);
+                let bang = if link.is_image { "!" } else { "" };
+                linked.push_str(&format!("{}[{}]({})", bang, link.text, destination));
+                cursor = link.end;
+            }
+            linked.push_str(&line[cursor..]);
+        }
+
+        let convert_options = ConvertWikilinksOptions {
+            source_path: Some(path.clone()),
+            url_encode: true,
+            add_extension: true,
+            unresolved: UnresolvedLinkMode::Plain,
+        };
+        let converted = convert_wikilinks_with(&linked, &resolver, &attachments, &convert_options, |target, is_note| {
+            if is_note {
+                published_slugs.get(target).map(|slug| relative_to(&url, &page_url(slug)))
+            } else {
+                referenced.push(target.to_string());
+                Some(relative_to(&url, &format!("{}/{}", SITE_FILES_DIR, target)))
+            }
+        });
+        for target in converted.unresolved {
+            unresolved.push(MissingDependency {
+                source_path: path.clone(),
+                target,
+                reason: "Not found or not published".to_string(),
+            });
+        }
+
+        for file in referenced {
+            if !copied.insert(file.clone()) {
+                continue;
+            }
+            match resolve_in_vault(&vault_path, &file).and_then(|p| std::fs::read(p).map_err(|e| e.to_string())) {
+                Ok(bytes) => write_file(out, &format!("{}/{}", SITE_FILES_DIR, file), &bytes)?,
+                Err(e) => unresolved.push(MissingDependency {
+                    source_path: path.clone(),
+                    target: file.clone(),
+                    reason: format!("Failed to read attachment: {}", e),
+                }),
+            }
+        }
+
+        let html = render_html(&converted.content, &RenderOptions::default());
+        let home = relative_to(&url, "index.html");
+        write_file(out, &url, page_html(&site_title, title, &home, &html).as_bytes())?;
+
+        let excerpt: String = body
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(SEARCH_EXCERPT_CHARS)
+            .collect();
+        search.push(SearchEntry {
+            title: title.clone(),
+            url: url.clone(),
+            tags: tags.get(path).cloned().unwrap_or_default(),
+            excerpt,
+        });
+        published.push(PublishedNote {
+            path: path.clone(),
+            slug: slug.clone(),
+            url,
+        });
+    }
+
+    let mut by_title: BTreeMap<String, (&str, &str)> = BTreeMap::new();
+    for (path, slug, title, _) in &pages {
+        by_title.insert(format!("{}\n{}", title.to_lowercase(), path), (title, slug));
+    }
+    let mut list = String::from("<ul>\n");
+    for (title, slug) in by_title.values() {
+        list.push_str(&format!(
+            "<li><a href=\"{}\">{}</a></li>\n",
+            escape_html(&page_url(slug)),
+            escape_html(title)
+        ));
+    }
+    list.push_str("</ul>\n");
+    write_file(out, "index.html", page_html(&site_title, &site_title, "index.html", &list).as_bytes())?;
+
+    let search_json = serde_json::to_string(&search).map_err(|e| format!("Failed to serialize search index: {}", e))?;
+    write_file(out, "search.json", search_json.as_bytes())?;
+
+    Ok(ExportSiteResult {
+        out_dir,
+        published,
+        skipped,
+        attachments: copied.into_iter().collect(),
+        unresolved,
+    })
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use tauri::command;
+
+use super::search::get_or_create_pool;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModifiedNote {
+    pub path: String,
+    pub title: String,
+    pub checksum: Option<String>,
+    pub updated: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub path: String,
+    pub deleted_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModifiedSince {
+    pub notes: Vec<ModifiedNote>,
+    pub deleted: Vec<Tombstone>,
+}
+
+/// Validates an RFC3339 timestamp and normalises it to UTC, so comparisons
+/// don't depend on the caller's offset or precision.
+pub(crate) fn parse_timestamp(value: &str) -> Result<String, String> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&chrono::Utc).to_rfc3339())
+        .map_err(|e| format!("Invalid timestamp {}: {}", value, e))
+}
+
+/// Notes updated after `since`, oldest first, plus paths deleted after it.
+/// Enough for a sync client to work out what to push without diffing the
+/// whole vault.
+#[command]
+pub async fn markdown_list_modified_since(index_path: String, since: String) -> Result<ModifiedSince, String> {
+    let since = parse_timestamp(&since)?;
+    let pool = get_or_create_pool(&index_path).await?;
+
+    // julianday() parses both sides, so offsets and fractional seconds compare
+    // correctly rather than as strings.
+    let rows = sqlx::query(r#"
+        SELECT path, title, checksum, updated FROM notes
+        WHERE julianday(updated) > julianday(?)
+        ORDER BY julianday(updated), path
+    "#)
+    .bind(&since)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to list modified notes: {}", e))?;
+
+    let notes = rows
+        .iter()
+        .map(|row| ModifiedNote {
+            path: row.get::<String, _>("path"),
+            title: row.get::<String, _>("title"),
+            checksum: row.get::<Option<String>, _>("checksum"),
+            updated: row.get::<String, _>("updated"),
+        })
+        .collect();
+
+    let deleted = list_tombstones(&pool, Some(&since)).await?;
+
+    Ok(ModifiedSince { notes, deleted })
+}
+
+async fn list_tombstones(pool: &Pool<Sqlite>, since: Option<&str>) -> Result<Vec<Tombstone>, String> {
+    let rows = sqlx::query(r#"
+        SELECT path, deleted_at FROM tombstones
+        WHERE ? IS NULL OR julianday(deleted_at) > julianday(?)
+        ORDER BY julianday(deleted_at), path
+    "#)
+    .bind(since)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list deleted notes: {}", e))?;
+
+    Ok(rows
+        .iter()
+        .map(|row| Tombstone {
+            path: row.get::<String, _>("path"),
+            deleted_at: row.get::<String, _>("deleted_at"),
+        })
+        .collect())
+}
+
+/// Paths removed from the index after `since`, or all of them.
+#[command]
+pub async fn markdown_list_tombstones(index_path: String, since: Option<String>) -> Result<Vec<Tombstone>, String> {
+    let since = since.as_deref().map(parse_timestamp).transpose()?;
+    let pool = get_or_create_pool(&index_path).await?;
+    list_tombstones(&pool, since.as_deref()).await
+}
+
+/// Drops tombstones recorded before `older_than`, once every client has had
+/// the chance to see them. Returns how many were removed.
+#[command]
+pub async fn markdown_purge_tombstones(index_path: String, older_than: String) -> Result<u64, String> {
+    let older_than = parse_timestamp(&older_than)?;
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let result = sqlx::query("DELETE FROM tombstones WHERE julianday(deleted_at) < julianday(?)")
+        .bind(&older_than)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to purge tombstones: {}", e))?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use tauri::command;
+
+use super::analytics::TagUsage;
+use super::frontmatter::{split_frontmatter, update_frontmatter_list};
+use super::indexer::reindex_file_with;
+use super::links::LinkResolver;
+use super::search::get_or_create_pool;
+use super::vault::{resolve_in_vault, write_atomic};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BulkTagResult {
+    pub changed: usize,
+    /// Notes that already had the tag, or already lacked it when removing.
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
+
+fn normalize_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim().trim_start_matches('#');
+    if tag.is_empty() {
+        return Err("Tag is empty".to_string());
+    }
+    Ok(tag.to_string())
+}
+
+async fn bulk_edit_tags(
+    vault_path: &str,
+    index_path: &str,
+    paths: Vec<String>,
+    update: impl Fn(&mut Vec<String>),
+) -> Result<BulkTagResult, String> {
+    let pool = get_or_create_pool(index_path).await?;
+    let mut resolver = LinkResolver::load(&pool).await?;
+    let mut result = BulkTagResult::default();
+
+    for path in paths {
+        let edited = resolve_in_vault(vault_path, &path).and_then(|full_path| {
+            let raw = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read note: {}", e))?;
+            match update_frontmatter_list(&raw, "tags", &update) {
+                Some(updated) => write_atomic(&full_path, &updated).map(|_| true),
+                None => Ok(false),
+            }
+        });
+
+        match edited {
+            Ok(true) => match reindex_file_with(&pool, vault_path, &path, &mut resolver).await {
+                Ok(_) => result.changed += 1,
+                Err(e) => result.errors.push(format!("{}: {}", path, e)),
+            },
+            Ok(false) => result.skipped += 1,
+            Err(e) => result.errors.push(format!("{}: {}", path, e)),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Adds `tag` to the `tags` frontmatter of each note, creating the
+/// frontmatter when a note has none. Tags compare case-insensitively.
+#[command]
+pub async fn markdown_bulk_add_tag(
+    vault_path: String,
+    index_path: String,
+    paths: Vec<String>,
+    tag: String,
+) -> Result<BulkTagResult, String> {
+    let tag = normalize_tag(&tag)?;
+    bulk_edit_tags(&vault_path, &index_path, paths, |tags| {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            tags.push(tag.clone());
+        }
+    })
+    .await
+}
+
+#[command]
+pub async fn markdown_bulk_remove_tag(
+    vault_path: String,
+    index_path: String,
+    paths: Vec<String>,
+    tag: String,
+) -> Result<BulkTagResult, String> {
+    let tag = normalize_tag(&tag)?;
+    bulk_edit_tags(&vault_path, &index_path, paths, |tags| {
+        tags.retain(|t| !t.trim_start_matches('#').eq_ignore_ascii_case(&tag));
+    })
+    .await
+}
+
+/// What tag spellings are compared by: lowercase letters, digits and the
+/// `/` of nested tags, so `ToDo`, `todo`, `to-do` and `to_do` all become
+/// `todo`.
+fn tag_slug(tag: &str) -> String {
+    tag.trim_start_matches('#')
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '/')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagVariants {
+    pub slug: String,
+    /// Each spelling with the number of notes using it, most used first.
+    pub variants: Vec<TagUsage>,
+}
+
+/// Groups of two or more tag spellings that share a slug, for merging with
+/// `markdown_merge_tags`. Groups with the most notes come first.
+#[command]
+pub async fn markdown_find_tag_variants(index_path: String) -> Result<Vec<TagVariants>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query(r#"
+        SELECT ltrim(tag.value, '#') AS tag, COUNT(DISTINCT notes.path) AS uses
+        FROM notes, json_each(CASE WHEN json_valid(notes.tags) THEN notes.tags ELSE '[]' END) AS tag
+        WHERE tag.type = 'text' AND ltrim(tag.value, '#') != ''
+        GROUP BY ltrim(tag.value, '#')
+    "#)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load tags: {}", e))?;
+
+    let mut groups: BTreeMap<String, Vec<TagUsage>> = BTreeMap::new();
+    for row in &rows {
+        let tag = row.get::<String, _>("tag");
+        let slug = tag_slug(&tag);
+        if !slug.is_empty() {
+            groups.entry(slug).or_default().push(TagUsage {
+                tag,
+                count: row.get::<i64, _>("uses"),
+            });
+        }
+    }
+
+    let mut clusters: Vec<TagVariants> = groups
+        .into_iter()
+        .filter(|(_, variants)| variants.len() > 1)
+        .map(|(slug, mut variants)| {
+            variants.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+            TagVariants { slug, variants }
+        })
+        .collect();
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.variants.iter().map(|v| v.count).sum::<i64>()));
+    Ok(clusters)
+}
+
+/// Rewrites inline `#tag` occurrences in the body that match one of
+/// `variants`, following the same rules as `extract_tags`. Returns `None`
+/// when nothing matched.
+fn rename_inline_tags(content: &str, variants: &[String], into: &str) -> Option<String> {
+    let (_, body) = split_frontmatter(content);
+    let mut output = content[..content.len() - body.len()].to_string();
+    let mut changed = false;
+    let mut in_fence = false;
+
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            output.push_str(line);
+            continue;
+        }
+        if in_fence {
+            output.push_str(line);
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut previous: Option<char> = None;
+        let mut copied = 0;
+        for (index, c) in line.char_indices() {
+            if c == '`' {
+                in_code = !in_code;
+            } else if c == '#' && !in_code && previous.map_or(true, char::is_whitespace) {
+                let len: usize = line[index + 1..]
+                    .chars()
+                    .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '/'))
+                    .map(char::len_utf8)
+                    .sum();
+                let tag = line[index + 1..index + 1 + len].trim_matches('/');
+                if variants.iter().any(|v| v == tag) {
+                    output.push_str(&line[copied..index + 1]);
+                    output.push_str(into);
+                    copied = index + 1 + len;
+                    changed = true;
+                }
+            }
+            previous = Some(c);
+        }
+        output.push_str(&line[copied..]);
+    }
+
+    changed.then_some(output)
+}
+
+/// Replaces every tag in `variants` with `into`, in the frontmatter `tags`
+/// list and inline in the body, on each note using one of them. Spellings
+/// match exactly, so `ToDo` can be merged into `todo`. Use it to merge a
+/// group from `markdown_find_tag_variants`.
+#[command]
+pub async fn markdown_merge_tags(
+    vault_path: String,
+    index_path: String,
+    variants: Vec<String>,
+    into: String,
+) -> Result<BulkTagResult, String> {
+    let into = normalize_tag(&into)?;
+    let variants: Vec<String> = variants
+        .iter()
+        .map(|v| v.trim().trim_start_matches('#').to_string())
+        .filter(|v| !v.is_empty() && v != &into)
+        .collect();
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let rows = sqlx::query("SELECT path, tags FROM notes ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+    let is_variant = |tag: &str| variants.iter().any(|v| v == tag.trim().trim_start_matches('#'));
+
+    let mut resolver = LinkResolver::load(&pool).await?;
+    let mut result = BulkTagResult::default();
+    for row in &rows {
+        let tags: Vec<String> = row
+            .get::<Option<String>, _>("tags")
+            .and_then(|t| serde_json::from_str(&t).ok())
+            .unwrap_or_default();
+        if !tags.iter().any(|t| is_variant(t)) {
+            continue;
+        }
+
+        let path = row.get::<String, _>("path");
+        let edited = resolve_in_vault(&vault_path, &path).and_then(|full_path| {
+            let raw = std::fs::read_to_string(&full_path).map_err(|e| format!("Failed to read note: {}", e))?;
+            let frontmatter = update_frontmatter_list(&raw, "tags", |list| {
+                if !list.iter().any(|t| is_variant(t)) {
+                    return;
+                }
+                let mut merged: Vec<String> = Vec::with_capacity(list.len());
+                for tag in list.drain(..) {
+                    let tag = if is_variant(&tag) { into.clone() } else { tag };
+                    if !merged.iter().any(|t| t.trim_start_matches('#').eq_ignore_ascii_case(tag.trim_start_matches('#'))) {
+                        merged.push(tag);
+                    }
+                }
+                *list = merged;
+            });
+            let current = frontmatter.as_deref().unwrap_or(&raw);
+            match rename_inline_tags(current, &variants, &into).or(frontmatter) {
+                Some(updated) => write_atomic(&full_path, &updated).map(|_| true),
+                None => Ok(false),
+            }
+        });
+
+        match edited {
+            Ok(true) => match reindex_file_with(&pool, &vault_path, &path, &mut resolver).await {
+                Ok(_) => result.changed += 1,
+                Err(e) => result.errors.push(format!("{}: {}", path, e)),
+            },
+            Ok(false) => result.skipped += 1,
+            Err(e) => result.errors.push(format!("{}: {}", path, e)),
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,205 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::path::Path;
+use tauri::command;
+
+use super::indexer::compute_checksum;
+use super::search::get_or_create_pool;
+use super::vault::write_atomic;
+
+/// A `- [ ]` or `- [x]` list item. `line` is zero-based within the content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Task {
+    /// The item's text with its due date marker removed.
+    pub text: String,
+    pub done: bool,
+    /// `YYYY-MM-DD`.
+    pub due: Option<String>,
+    pub line: usize,
+}
+
+/// Due date markers after which a `YYYY-MM-DD` date is read: the Tasks
+/// plugin's emoji and Dataview-style inline fields.
+const DUE_MARKERS: [&str; 3] = ["📅", "due::", "due:"];
+
+/// Finds a due date marker and its date, returning the date and the text
+/// with both removed. Dataview's `[due:: ...]` brackets are removed too.
+fn take_due_date(text: &str) -> (String, Option<NaiveDate>) {
+    for marker in DUE_MARKERS {
+        let Some(at) = text.find(marker) else {
+            continue;
+        };
+        let rest = text[at + marker.len()..].trim_start();
+        let Some(date) = rest.get(..10).and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()) else {
+            continue;
+        };
+        let mut before = &text[..at];
+        let mut after = &rest[10..];
+        if before.ends_with('[') && after.starts_with(']') {
+            before = &before[..before.len() - 1];
+            after = &after[1..];
+        }
+        let text = format!("{} {}", before.trim_end(), after.trim_start());
+        return (text.trim().to_string(), Some(date));
+    }
+    (text.trim().to_string(), None)
+}
+
+/// `-`, `*`, `+`, `1.` or `1)`.
+pub(crate) fn is_list_marker(marker: &str) -> bool {
+    if matches!(marker, "-" | "*" | "+") {
+        return true;
+    }
+    match marker.strip_suffix(['.', ')']) {
+        Some(number) => !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// Collects checklist items, skipping fenced code. Items may use `-`, `*`,
+/// `+` or a numbered marker, at any indent.
+pub fn extract_tasks(content: &str) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    let mut in_fence = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let item = match trimmed.split_once(' ') {
+            Some((marker, rest)) if is_list_marker(marker) => rest,
+            _ => continue,
+        };
+        let (done, text) = match item.get(..3) {
+            Some("[ ]") => (false, &item[3..]),
+            Some("[x]") | Some("[X]") => (true, &item[3..]),
+            _ => continue,
+        };
+        let (text, due) = take_due_date(text);
+        if text.is_empty() {
+            continue;
+        }
+        tasks.push(Task {
+            text,
+            done,
+            due: due.map(|d| d.format("%Y-%m-%d").to_string()),
+            line: index,
+        });
+    }
+
+    tasks
+}
+
+/// Escapes a TEXT value per RFC 5545.
+fn ics_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line to 75 octets, continuing with a leading space, and
+/// ends it with CRLF.
+fn push_ics_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskExportResult {
+    pub out_path: String,
+    pub exported: usize,
+    /// Tasks left out for having no due date.
+    pub skipped: usize,
+}
+
+/// Writes every task with a due date to an iCalendar file at `out_path`
+/// for calendar apps to subscribe to. Tasks become VTODOs, or all-day
+/// VEVENTs with `as_events` for apps that don't show to-dos. UIDs are
+/// derived from the note and task text, so re-exporting updates entries in
+/// place rather than duplicating them.
+#[command]
+pub async fn markdown_export_tasks_ics(
+    index_path: String,
+    out_path: String,
+    as_events: Option<bool>,
+) -> Result<TaskExportResult, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let as_events = as_events.unwrap_or(false);
+
+    let rows = sqlx::query("SELECT path, title, content FROM notes ORDER BY path")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load notes: {}", e))?;
+
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut ics = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//Nenspace//Tasks//EN", "CALSCALE:GREGORIAN"] {
+        push_ics_line(&mut ics, line);
+    }
+
+    let mut exported = 0;
+    let mut skipped = 0;
+    for row in &rows {
+        let path = row.get::<String, _>("path");
+        let title = row.get::<String, _>("title");
+        for task in extract_tasks(&row.get::<String, _>("content")) {
+            let Some(due) = task.due.as_deref().and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()) else {
+                skipped += 1;
+                continue;
+            };
+            let uid = &compute_checksum(&format!("{}\n{}", path, task.text))[..32];
+            let date = due.format("%Y%m%d").to_string();
+            let description = format!("{} ({}, line {})", title, path, task.line + 1);
+
+            let component = if as_events { "VEVENT" } else { "VTODO" };
+            push_ics_line(&mut ics, &format!("BEGIN:{}", component));
+            push_ics_line(&mut ics, &format!("UID:{}@nenspace", uid));
+            push_ics_line(&mut ics, &format!("DTSTAMP:{}", stamp));
+            push_ics_line(&mut ics, &format!("SUMMARY:{}", ics_escape(&task.text)));
+            push_ics_line(&mut ics, &format!("DESCRIPTION:{}", ics_escape(&description)));
+            if as_events {
+                let end = due.succ_opt().unwrap_or(due).format("%Y%m%d");
+                push_ics_line(&mut ics, &format!("DTSTART;VALUE=DATE:{}", date));
+                push_ics_line(&mut ics, &format!("DTEND;VALUE=DATE:{}", end));
+                if task.done {
+                    push_ics_line(&mut ics, "TRANSP:TRANSPARENT");
+                }
+            } else {
+                push_ics_line(&mut ics, &format!("DUE;VALUE=DATE:{}", date));
+                push_ics_line(&mut ics, if task.done { "STATUS:COMPLETED" } else { "STATUS:NEEDS-ACTION" });
+            }
+            push_ics_line(&mut ics, &format!("END:{}", component));
+            exported += 1;
+        }
+    }
+    push_ics_line(&mut ics, "END:VCALENDAR");
+
+    let out = Path::new(&out_path);
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+    }
+    write_atomic(out, &ics)?;
+
+    Ok(TaskExportResult {
+        out_path,
+        exported,
+        skipped,
+    })
+}
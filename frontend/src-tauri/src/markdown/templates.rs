@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tauri::command;
+
+use super::frontmatter::split_frontmatter;
+use super::search::get_or_create_pool;
+use super::vault::{resolve_in_vault, VaultConfig};
+
+const DEFAULT_TEMPLATE_FOLDER: &str = "templates";
+const PREVIEW_LINES: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateDetails {
+    pub path: String,
+    pub title: String,
+    /// The first few non-blank lines of the body.
+    pub preview: String,
+    /// `{{name}}` placeholders, frontmatter included, in order of first use.
+    pub variables: Vec<String>,
+}
+
+/// Names inside `{{...}}`, trimmed, without repeats. Empty or multi-line
+/// braces are not placeholders.
+pub fn template_variables(content: &str) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+    let mut rest = content;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            break;
+        };
+        let name = after[..close].trim();
+        if !name.is_empty() && !name.contains(['{', '\n']) && !variables.iter().any(|v| v == name) {
+            variables.push(name.to_string());
+        }
+        rest = &after[close + 2..];
+    }
+    variables
+}
+
+/// Templates for a picker: notes flagged `is_template` or kept under the
+/// vault's `templateFolder` (default `templates`), each with a preview and
+/// the variables to fill in when creating a note from it. Files are read
+/// from disk so placeholders in frontmatter are found too.
+#[command]
+pub async fn markdown_list_template_details(
+    index_path: String,
+    vault_path: String,
+) -> Result<Vec<TemplateDetails>, String> {
+    let pool = get_or_create_pool(&index_path).await?;
+
+    let folder = VaultConfig::load(&vault_path)
+        .and_then(|config| config.settings.get("templateFolder").and_then(|v| v.as_str()).map(|f| f.to_string()))
+        .map(|f| f.trim().trim_matches('/').to_string())
+        .unwrap_or_else(|| DEFAULT_TEMPLATE_FOLDER.to_string());
+
+    let rows = sqlx::query(r#"
+        SELECT path, title, content FROM notes
+        WHERE is_template = 1 OR (? != '' AND substr(path, 1, length(?) + 1) = ? || '/')
+        ORDER BY title COLLATE NOCASE, path
+    "#)
+    .bind(&folder)
+    .bind(&folder)
+    .bind(&folder)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to list templates: {}", e))?;
+
+    let mut templates = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let path = row.get::<String, _>("path");
+        let raw = match resolve_in_vault(&vault_path, &path).and_then(|full_path| {
+            std::fs::read_to_string(full_path).map_err(|e| format!("Failed to read template: {}", e))
+        }) {
+            Ok(raw) => raw,
+            Err(e) => {
+                log::warn!("Using indexed content for {}: {}", path, e);
+                row.get::<String, _>("content")
+            }
+        };
+
+        let (_, body) = split_frontmatter(&raw);
+        let preview = body
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .take(PREVIEW_LINES)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        templates.push(TemplateDetails {
+            title: row.get::<String, _>("title"),
+            preview,
+            variables: template_variables(&raw),
+            path,
+        });
+    }
+
+    Ok(templates)
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::command;
+
+use super::indexer::reindex_file;
+use super::search::{get_or_create_pool, remove_note};
+use super::vault::{is_markdown_path, normalize_rel_path, resolve_in_vault, to_rel_path, write_atomic};
+use super::versions::{remove_versions, rename_versions};
+
+pub const TRASH_FOLDER: &str = ".trash";
+
+fn trash_root(vault_path: &str) -> PathBuf {
+    Path::new(vault_path).join(TRASH_FOLDER)
+}
+
+/// Where trashed entries came from, keyed by their path in the trash, so a
+/// restore puts them back without guessing from the name.
+const ORIGINS_FILE: &str = ".origins.json";
+
+fn load_origins(vault_path: &str) -> HashMap<String, String> {
+    std::fs::read_to_string(trash_root(vault_path).join(ORIGINS_FILE))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+fn save_origins(vault_path: &str, origins: &HashMap<String, String>) -> Result<(), String> {
+    let raw = serde_json::to_string_pretty(origins).map_err(|e| e.to_string())?;
+    write_atomic(&trash_root(vault_path).join(ORIGINS_FILE), raw)
+}
+
+/// `path` itself when free, otherwise with ` (YYYYMMDD-HHMMSS)` before the
+/// extension, then ` (YYYYMMDD-HHMMSS-2)` and so on, so two deletions in
+/// the same second never overwrite each other.
+fn free_trash_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let with_suffix = |suffix: &str| {
+        let name = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => format!("{} ({}).{}", stem, suffix, ext),
+            None => format!("{} ({})", stem, suffix),
+        };
+        path.with_file_name(name)
+    };
+
+    let mut candidate = with_suffix(&stamp);
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = with_suffix(&format!("{}-{}", stamp, n));
+        n += 1;
+    }
+    candidate
+}
+
+/// Moves an entry into `.trash/`, keeping its relative location, and moves
+/// its versions along with it. Returns the path relative to the trash folder.
+pub(crate) fn move_to_trash(vault_path: &str, rel_path: &str) -> Result<String, String> {
+    let rel_path = normalize_rel_path(rel_path)?;
+    let source = resolve_in_vault(vault_path, &rel_path)?;
+    if !source.exists() {
+        return Err(format!("Note not found: {}", rel_path));
+    }
+
+    let destination = free_trash_path(&trash_root(vault_path).join(&rel_path));
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create trash folder: {}", e))?;
+    }
+
+    std::fs::rename(&source, &destination)
+        .map_err(|e| format!("Failed to move note to trash: {}", e))?;
+
+    // The modification time records when the entry was trashed, which is
+    // what `markdown_empty_trash` ages against.
+    if let Ok(file) = std::fs::File::options().write(true).open(&destination) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+
+    let trash_rel = to_rel_path(&trash_root(vault_path), &destination);
+    let mut origins = load_origins(vault_path);
+    origins.insert(trash_rel.clone(), rel_path.clone());
+    save_origins(vault_path, &origins)?;
+
+    if let Err(e) = rename_versions(vault_path, &rel_path, &format!("{}/{}", TRASH_FOLDER, trash_rel)) {
+        log::warn!("Failed to move versions of {} to trash: {}", rel_path, e);
+    }
+
+    Ok(trash_rel)
+}
+
+#[command]
+pub async fn markdown_delete_note(
+    vault_path: String,
+    index_path: String,
+    rel_path: String,
+) -> Result<String, String> {
+    let trash_rel = move_to_trash(&vault_path, &rel_path)?;
+
+    let pool = get_or_create_pool(&index_path).await?;
+    remove_note(&pool, &normalize_rel_path(&rel_path)?).await?;
+
+    Ok(trash_rel)
+}
+
+/// Moves a trashed entry back to where it was deleted from, with its
+/// versions, and indexes the notes it holds again. Returns the restored path.
+#[command]
+pub async fn markdown_restore_from_trash(
+    vault_path: String,
+    index_path: String,
+    trash_rel: String,
+) -> Result<String, String> {
+    let trash_rel = normalize_rel_path(&trash_rel)?;
+    let source = trash_root(&vault_path).join(&trash_rel);
+    if trash_rel.is_empty() || trash_rel == ORIGINS_FILE || !source.exists() {
+        return Err(format!("Not found in trash: {}", trash_rel));
+    }
+
+    // Entries trashed before origins were recorded kept their location.
+    let mut origins = load_origins(&vault_path);
+    let restored_rel = origins.get(&trash_rel).cloned().unwrap_or_else(|| trash_rel.clone());
+    let destination = resolve_in_vault(&vault_path, &restored_rel)?;
+    if destination.exists() {
+        return Err(format!("A note already exists at {}", restored_rel));
+    }
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create folder: {}", e))?;
+    }
+
+    std::fs::rename(&source, &destination)
+        .map_err(|e| format!("Failed to restore note: {}", e))?;
+    if origins.remove(&trash_rel).is_some() {
+        save_origins(&vault_path, &origins)?;
+    }
+    if let Err(e) = rename_versions(&vault_path, &format!("{}/{}", TRASH_FOLDER, trash_rel), &restored_rel) {
+        log::warn!("Failed to restore versions of {}: {}", restored_rel, e);
+    }
+
+    let notes: Vec<String> = if destination.is_dir() {
+        walkdir::WalkDir::new(&destination)
+            .into_iter()
+            .flatten()
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| to_rel_path(Path::new(&vault_path), entry.path()))
+            .filter(|rel| is_markdown_path(rel))
+            .collect()
+    } else if is_markdown_path(&restored_rel) {
+        vec![restored_rel.clone()]
+    } else {
+        Vec::new()
+    };
+    let pool = get_or_create_pool(&index_path).await?;
+    for note in &notes {
+        reindex_file(&pool, &vault_path, note).await?;
+    }
+
+    Ok(restored_rel)
+}
+
+/// Permanently deletes trashed files, optionally only those trashed more than
+/// `older_than_days` ago. Returns the number of files removed.
+#[command]
+pub async fn markdown_empty_trash(vault_path: String, older_than_days: Option<u32>) -> Result<usize, String> {
+    let root = trash_root(&vault_path);
+    if !root.exists() {
+        return Ok(0);
+    }
+
+    let cutoff = older_than_days
+        .map(|days| SystemTime::now() - Duration::from_secs(days as u64 * 24 * 60 * 60));
+
+    let mut removed = 0;
+    for entry in walkdir::WalkDir::new(&root).min_depth(1).into_iter().flatten() {
+        if !entry.file_type().is_file() || entry.path() == root.join(ORIGINS_FILE) {
+            continue;
+        }
+        let expired = match cutoff {
+            Some(cutoff) => entry
+                .metadata()
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .map(|modified| modified < cutoff)
+                .unwrap_or(false),
+            None => true,
+        };
+        if expired {
+            std::fs::remove_file(entry.path())
+                .map_err(|e| format!("Failed to delete {}: {}", entry.path().display(), e))?;
+            let trash_rel = to_rel_path(&root, entry.path());
+            if let Err(e) = remove_versions(&vault_path, &format!("{}/{}", TRASH_FOLDER, trash_rel)) {
+                log::warn!("Failed to delete versions of trashed {}: {}", trash_rel, e);
+            }
+            removed += 1;
+        }
+    }
+
+    let mut origins = load_origins(&vault_path);
+    let before = origins.len();
+    origins.retain(|trash_rel, _| root.join(trash_rel).exists());
+    if origins.len() != before {
+        save_origins(&vault_path, &origins)?;
+    }
+
+    // Drop folders left empty, deepest first.
+    for entry in walkdir::WalkDir::new(&root)
+        .min_depth(1)
+        .contents_first(true)
+        .into_iter()
+        .flatten()
+    {
+        if entry.file_type().is_dir() {
+            let _ = std::fs::remove_dir(entry.path());
+        }
+    }
+
+    Ok(removed)
+}
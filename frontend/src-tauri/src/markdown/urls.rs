@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter};
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+use super::links::{markdown_list_external_urls, UrlOccurrence};
+
+const DEFAULT_CHECK_CONCURRENCY: usize = 8;
+const DEFAULT_CHECK_TIMEOUT_SECS: u64 = 10;
+/// Gap between requests to the same host, so a vault full of links to one
+/// site doesn't hammer it.
+const HOST_DELAY: Duration = Duration::from_millis(500);
+/// How often a check waiting for its host slot looks for cancellation.
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+lazy_static::lazy_static! {
+    static ref URL_CHECK_CANCEL_FLAGS: Mutex<HashMap<String, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlStatus {
+    /// A 2xx response.
+    Ok,
+    /// A 3xx response; `location` says where to.
+    Redirect,
+    /// 404 or 410.
+    NotFound,
+    Timeout,
+    /// Any other status, or no response at all.
+    Error,
+    /// Not checked because the check was cancelled first.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlCheckResult {
+    pub url: String,
+    pub status: UrlStatus,
+    pub status_code: Option<u16>,
+    pub location: Option<String>,
+    pub error: Option<String>,
+    pub occurrences: Vec<UrlOccurrence>,
+}
+
+/// Payload of the `url-check` event, sent as each URL finishes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlCheckProgress {
+    pub check_id: String,
+    pub checked: usize,
+    pub total: usize,
+    pub result: UrlCheckResult,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UrlCheckSummary {
+    pub check_id: String,
+    /// In the order `markdown_list_external_urls` returns them.
+    pub results: Vec<UrlCheckResult>,
+    pub cancelled: bool,
+}
+
+/// Books the next slot for `host` and waits for it. Returns false if the
+/// check was cancelled while waiting.
+async fn wait_for_host(
+    next_allowed: &tokio::sync::Mutex<HashMap<String, Instant>>,
+    host: &str,
+    cancel: &AtomicBool,
+) -> bool {
+    let slot = {
+        let mut next_allowed = next_allowed.lock().await;
+        let now = Instant::now();
+        let slot = next_allowed.get(host).copied().filter(|at| *at > now).unwrap_or(now);
+        next_allowed.insert(host.to_string(), slot + HOST_DELAY);
+        slot
+    };
+    while Instant::now() < slot {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        tokio::time::sleep_until(slot.min(Instant::now() + CANCEL_POLL)).await;
+    }
+    !cancel.load(Ordering::Relaxed)
+}
+
+/// Requests `url` with HEAD, retrying with GET for servers that don't
+/// support HEAD. Redirects are reported rather than followed.
+async fn check_url(client: &reqwest::Client, url: &str) -> (UrlStatus, Option<u16>, Option<String>, Option<String>) {
+    let mut response = client.head(url).send().await;
+    if matches!(&response, Ok(r) if matches!(r.status().as_u16(), 403 | 405 | 501)) {
+        response = client.get(url).send().await;
+    }
+
+    match response {
+        Ok(response) => {
+            let status = response.status();
+            let location = status
+                .is_redirection()
+                .then(|| response.headers().get(reqwest::header::LOCATION).and_then(|v| v.to_str().ok()))
+                .flatten()
+                .map(|l| l.to_string());
+            let kind = if status.is_success() {
+                UrlStatus::Ok
+            } else if status.is_redirection() {
+                UrlStatus::Redirect
+            } else if matches!(status.as_u16(), 404 | 410) {
+                UrlStatus::NotFound
+            } else {
+                UrlStatus::Error
+            };
+            (kind, Some(status.as_u16()), location, None)
+        }
+        Err(e) if e.is_timeout() => (UrlStatus::Timeout, None, None, None),
+        Err(e) => (UrlStatus::Error, None, None, Some(e.to_string())),
+    }
+}
+
+/// Checks every external URL in the index for a "broken external links"
+/// report, `concurrency` at a time (default 8) with a `timeout` in seconds
+/// per request (default 10) and a short pause between requests to the same
+/// host. Each result is emitted as a `url-check` event; the full list is
+/// returned once done or cancelled with `markdown_cancel_url_check`, with
+/// URLs not reached by then marked `skipped`.
+#[command]
+pub async fn markdown_check_urls(
+    app: AppHandle,
+    index_path: String,
+    concurrency: Option<usize>,
+    timeout: Option<u64>,
+    check_id: Option<String>,
+) -> Result<UrlCheckSummary, String> {
+    let urls = markdown_list_external_urls(index_path).await?;
+    let check_id = check_id.unwrap_or_else(|| format!("urls_{}", chrono::Utc::now().timestamp_millis()));
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout.unwrap_or(DEFAULT_CHECK_TIMEOUT_SECS).max(1)))
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!("Nenspace/", env!("CARGO_PKG_VERSION"), " (link checker)"))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    URL_CHECK_CANCEL_FLAGS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(check_id.clone(), cancel.clone());
+
+    let total = urls.len();
+    let semaphore = Arc::new(Semaphore::new(concurrency.unwrap_or(DEFAULT_CHECK_CONCURRENCY).max(1)));
+    let next_allowed = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
+    let checked = Arc::new(AtomicUsize::new(0));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, external) in urls.into_iter().enumerate() {
+        let (client, semaphore, next_allowed) = (client.clone(), semaphore.clone(), next_allowed.clone());
+        let (cancel, checked, app, check_id) = (cancel.clone(), checked.clone(), app.clone(), check_id.clone());
+        tasks.spawn(async move {
+            let url = external.url;
+            // The host slot is waited for before taking a permit, so checks
+            // queued behind a busy host don't hold up every other host.
+            let host = reqwest::Url::parse(&url).ok().and_then(|u| u.host_str().map(|h| h.to_lowercase()));
+            let (status, status_code, location, error) = match host {
+                Some(host) if wait_for_host(&next_allowed, &host, &cancel).await => {
+                    match semaphore.acquire().await {
+                        Ok(_permit) if !cancel.load(Ordering::Relaxed) => check_url(&client, &url).await,
+                        _ => (UrlStatus::Skipped, None, None, None),
+                    }
+                }
+                Some(_) => (UrlStatus::Skipped, None, None, None),
+                None => (UrlStatus::Error, None, None, Some("Invalid URL".to_string())),
+            };
+
+            let result = UrlCheckResult {
+                url,
+                status,
+                status_code,
+                location,
+                error,
+                occurrences: external.occurrences,
+            };
+            if status != UrlStatus::Skipped {
+                let progress = UrlCheckProgress {
+                    check_id,
+                    checked: checked.fetch_add(1, Ordering::Relaxed) + 1,
+                    total,
+                    result: result.clone(),
+                };
+                if let Err(e) = app.emit("url-check", progress) {
+                    log::error!("Failed to emit URL check: {}", e);
+                }
+            }
+            (index, result)
+        });
+    }
+
+    let mut results: Vec<Option<UrlCheckResult>> = vec![None; total];
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, result)) => results[index] = Some(result),
+            Err(e) => log::error!("URL check task failed: {}", e),
+        }
+    }
+
+    if let Ok(mut flags) = URL_CHECK_CANCEL_FLAGS.lock() {
+        flags.remove(&check_id);
+    }
+
+    Ok(UrlCheckSummary {
+        check_id,
+        results: results.into_iter().flatten().collect(),
+        cancelled: cancel.load(Ordering::Relaxed),
+    })
+}
+
+/// Stops a running `markdown_check_urls`. Returns false when no check with
+/// that id is running.
+#[command]
+pub async fn markdown_cancel_url_check(check_id: String) -> Result<bool, String> {
+    let flags = URL_CHECK_CANCEL_FLAGS.lock().map_err(|e| e.to_string())?;
+    match flags.get(&check_id) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use tauri::command;
+use std::path::{Component, Path, PathBuf};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct VaultConfig {
@@ -8,3 +9,130 @@ pub struct VaultConfig {
     pub created: String,
 }
 
+/// Normalises a vault-relative path to forward slashes without `.` segments,
+/// refusing anything that would escape the vault (absolute paths, drive
+/// prefixes or `..` components).
+pub fn normalize_rel_path(rel_path: &str) -> Result<String, String> {
+    let normalized = rel_path.replace('\\', "/");
+    let mut parts = Vec::new();
+
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            _ => return Err(format!("Path escapes the vault: {}", rel_path)),
+        }
+    }
+
+    Ok(parts.join("/"))
+}
+
+/// Joins a vault-relative path onto the vault root; see `normalize_rel_path`.
+pub fn resolve_in_vault(vault_path: &str, rel_path: &str) -> Result<PathBuf, String> {
+    let normalized = normalize_rel_path(rel_path)?;
+    let mut resolved = PathBuf::from(vault_path);
+    if !normalized.is_empty() {
+        resolved.push(normalized);
+    }
+    Ok(resolved)
+}
+
+/// The vault an index belongs to, when it sits at the usual
+/// `<vault>/.vault/index.db`.
+pub fn vault_for_index(db_path: &str) -> Option<&str> {
+    let vault_dir = Path::new(db_path).parent()?;
+    if vault_dir.file_name()? != ".vault" {
+        return None;
+    }
+    vault_dir.parent()?.to_str()
+}
+
+impl VaultConfig {
+    /// Reads `.vault/config.json`, the file the frontend writes on vault creation.
+    pub fn load(vault_path: &str) -> Option<VaultConfig> {
+        let config_path = Path::new(vault_path).join(".vault").join("config.json");
+        let text = std::fs::read_to_string(config_path).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+}
+
+/// Decides which vault entries are skipped by scans: dotfiles and dot-folders
+/// always, plus the `excludedFolders` vault setting.
+pub struct VaultFilter {
+    excluded: Vec<String>,
+}
+
+impl VaultFilter {
+    pub fn load(vault_path: &str) -> VaultFilter {
+        let excluded = VaultConfig::load(vault_path)
+            .and_then(|config| {
+                config.settings.get("excludedFolders").and_then(|v| v.as_array()).map(|folders| {
+                    folders
+                        .iter()
+                        .filter_map(|f| f.as_str())
+                        .map(|f| f.trim_matches('/').to_string())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .unwrap_or_else(|| vec![".vault".to_string(), ".trash".to_string(), "node_modules".to_string()]);
+
+        VaultFilter { excluded }
+    }
+
+    pub fn is_ignored(&self, rel_path: &str) -> bool {
+        if rel_path.split('/').any(|part| part.starts_with('.')) {
+            return true;
+        }
+        self.excluded.iter().any(|folder| {
+            !folder.is_empty() && (rel_path == folder || rel_path.starts_with(&format!("{}/", folder)))
+        })
+    }
+}
+
+/// Converts an absolute path under the vault root into the forward-slash
+/// relative form used by the index.
+pub fn to_rel_path(vault_root: &Path, path: &Path) -> String {
+    path.strip_prefix(vault_root)
+        .ok()
+        .and_then(|p| p.to_str())
+        .map(|s| s.replace('\\', "/"))
+        .unwrap_or_default()
+}
+
+/// Walks the vault, skipping ignored entries, and yields `(rel_path, entry)`
+/// for everything below the root.
+pub fn walk_vault(vault_path: &str) -> Vec<(String, walkdir::DirEntry)> {
+    let root = PathBuf::from(vault_path);
+    let filter = VaultFilter::load(vault_path);
+
+    walkdir::WalkDir::new(&root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| !filter.is_ignored(&to_rel_path(&root, entry.path())))
+        .flatten()
+        .map(|entry| (to_rel_path(&root, entry.path()), entry))
+        .collect()
+}
+
+pub fn is_markdown_path(path: &str) -> bool {
+    path.rsplit_once('.')
+        .map(|(_, ext)| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+        .unwrap_or(false)
+}
+
+/// Writes a file by writing a sibling temp file and renaming it over the
+/// original, so a crash mid-write never leaves a truncated note behind. The
+/// temp name starts with `.` so scans ignore it.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), String> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", name));
+
+    std::fs::write(&temp_path, contents).map_err(|e| format!("Failed to write {}: {}", name, e))?;
+    std::fs::rename(&temp_path, path).map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        format!("Failed to replace {}: {}", name, e)
+    })
+}
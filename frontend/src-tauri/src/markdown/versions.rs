@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+use super::diff::{diff_texts, DiffHunk};
+use super::vault::{normalize_rel_path, resolve_in_vault, write_atomic, VaultConfig};
+
+/// Snapshots live under this dot-folder, which scans, the file tree and the
+/// watcher all skip.
+pub const VERSIONS_FOLDER: &str = ".nenspace/versions";
+/// Version id standing for the note as it is on disk now.
+pub const CURRENT_VERSION: &str = "current";
+const DEFAULT_MAX_VERSIONS: usize = 50;
+const VERSION_EXTENSION: &str = "md";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NoteVersion {
+    /// Sorts chronologically, e.g. `20240501T093015123Z`.
+    pub id: String,
+    /// RFC3339, UTC.
+    pub created: String,
+    pub size: u64,
+}
+
+/// `<vault>/.nenspace/versions/<rel_path>/`, one folder per note.
+fn versions_dir(vault_path: &str, rel_path: &str) -> Result<(String, PathBuf), String> {
+    let rel_path = normalize_rel_path(rel_path)?;
+    if rel_path.is_empty() {
+        return Err("Note path is empty".to_string());
+    }
+    let dir = Path::new(vault_path).join(VERSIONS_FOLDER).join(&rel_path);
+    Ok((rel_path, dir))
+}
+
+fn version_path(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid version id: {}", id));
+    }
+    Ok(dir.join(format!("{}.{}", id, VERSION_EXTENSION)))
+}
+
+fn parse_version_id(id: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    let stamp = id.split('-').next()?;
+    chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%dT%H%M%S%3fZ")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+/// Versions of a note, newest first.
+fn list_versions(dir: &Path) -> Vec<NoteVersion> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut versions: Vec<NoteVersion> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let id = name.strip_suffix(&format!(".{}", VERSION_EXTENSION))?.to_string();
+            let created = parse_version_id(&id)?;
+            Some(NoteVersion {
+                created: created.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                id,
+            })
+        })
+        .collect();
+
+    versions.sort_by(|a, b| b.id.cmp(&a.id));
+    versions
+}
+
+/// `maxVersionsPerNote` from the vault settings; 0 keeps every version.
+fn max_versions(vault_path: &str) -> usize {
+    VaultConfig::load(vault_path)
+        .and_then(|config| config.settings.get("maxVersionsPerNote").and_then(|v| v.as_u64()))
+        .map(|max| max as usize)
+        .unwrap_or(DEFAULT_MAX_VERSIONS)
+}
+
+/// Copies the note's current content into a new version, then prunes the
+/// oldest beyond the vault's cap. Returns `None` when the content matches
+/// the newest version, so snapshotting on every save doesn't pile up
+/// duplicates.
+pub(crate) fn snapshot_note(vault_path: &str, rel_path: &str) -> Result<Option<NoteVersion>, String> {
+    let (rel_path, dir) = versions_dir(vault_path, rel_path)?;
+    let content = std::fs::read_to_string(resolve_in_vault(vault_path, &rel_path)?)
+        .map_err(|e| format!("Failed to read note: {}", e))?;
+
+    let existing = list_versions(&dir);
+    if let Some(latest) = existing.first() {
+        let latest_content = std::fs::read_to_string(version_path(&dir, &latest.id)?).ok();
+        if latest_content.as_deref() == Some(content.as_str()) {
+            return Ok(None);
+        }
+    }
+
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create versions folder: {}", e))?;
+    let now = chrono::Utc::now();
+    let stamp = now.format("%Y%m%dT%H%M%S%3fZ").to_string();
+    let mut id = stamp.clone();
+    let mut n = 2;
+    while version_path(&dir, &id)?.exists() {
+        id = format!("{}-{}", stamp, n);
+        n += 1;
+    }
+    write_atomic(&version_path(&dir, &id)?, &content)?;
+
+    let max = max_versions(vault_path);
+    if max > 0 {
+        for old in list_versions(&dir).into_iter().skip(max) {
+            if let Err(e) = std::fs::remove_file(version_path(&dir, &old.id)?) {
+                log::warn!("Failed to prune version {} of {}: {}", old.id, rel_path, e);
+            }
+        }
+    }
+
+    Ok(Some(NoteVersion {
+        id,
+        created: now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        size: content.len() as u64,
+    }))
+}
+
+/// Reads a snapshot's content.
+pub(crate) fn read_version(vault_path: &str, rel_path: &str, version_id: &str) -> Result<String, String> {
+    let (_, dir) = versions_dir(vault_path, rel_path)?;
+    std::fs::read_to_string(version_path(&dir, version_id)?)
+        .map_err(|e| format!("Version {} not found: {}", version_id, e))
+}
+
+/// Deletes every stored version of a note.
+pub(crate) fn remove_versions(vault_path: &str, rel_path: &str) -> Result<(), String> {
+    let (_, dir) = versions_dir(vault_path, rel_path)?;
+    match std::fs::remove_dir_all(&dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove versions: {}", e)),
+        _ => Ok(()),
+    }
+}
+
+/// Moves a note's versions along with it when it is renamed.
+pub(crate) fn rename_versions(vault_path: &str, old_path: &str, new_path: &str) -> Result<(), String> {
+    let (_, from) = versions_dir(vault_path, old_path)?;
+    let (_, to) = versions_dir(vault_path, new_path)?;
+    if !from.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create versions folder: {}", e))?;
+    }
+    std::fs::rename(&from, &to).map_err(|e| format!("Failed to move versions: {}", e))
+}
+
+#[command]
+pub async fn markdown_snapshot_note(vault_path: String, rel_path: String) -> Result<Option<NoteVersion>, String> {
+    snapshot_note(&vault_path, &rel_path)
+}
+
+#[command]
+pub async fn markdown_list_versions(vault_path: String, rel_path: String) -> Result<Vec<NoteVersion>, String> {
+    let (_, dir) = versions_dir(&vault_path, &rel_path)?;
+    Ok(list_versions(&dir))
+}
+
+/// Replaces the note with a stored version, snapshotting the current
+/// content first so the restore can itself be undone. Returns the restored
+/// content for the editor to load.
+#[command]
+pub async fn markdown_restore_version(
+    vault_path: String,
+    rel_path: String,
+    version_id: String,
+) -> Result<String, String> {
+    let content = read_version(&vault_path, &rel_path, &version_id)?;
+    let full_path = resolve_in_vault(&vault_path, &rel_path)?;
+
+    if full_path.exists() {
+        snapshot_note(&vault_path, &rel_path)?;
+    } else if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create folder: {}", e))?;
+    }
+    write_atomic(&full_path, &content)?;
+
+    Ok(content)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionDiff {
+    /// False when that side's snapshot (or the note) no longer exists; it is
+    /// then diffed as empty.
+    pub a_exists: bool,
+    pub b_exists: bool,
+    pub identical: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+fn read_side(vault_path: &str, rel_path: &str, version_id: &str) -> Result<Option<String>, String> {
+    let path = if version_id == CURRENT_VERSION {
+        resolve_in_vault(vault_path, rel_path)?
+    } else {
+        let (_, dir) = versions_dir(vault_path, rel_path)?;
+        version_path(&dir, version_id)?
+    };
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("Failed to read version {}: {}", version_id, e)),
+    }
+}
+
+/// Line diff from `version_a` (old side) to `version_b` (new side). Either
+/// may be `current` to compare against the note on disk.
+#[command]
+pub async fn markdown_diff_versions(
+    vault_path: String,
+    rel_path: String,
+    version_a: String,
+    version_b: String,
+) -> Result<VersionDiff, String> {
+    let a = read_side(&vault_path, &rel_path, &version_a)?;
+    let b = read_side(&vault_path, &rel_path, &version_b)?;
+
+    let hunks = diff_texts(a.as_deref().unwrap_or(""), b.as_deref().unwrap_or(""));
+    Ok(VersionDiff {
+        a_exists: a.is_some(),
+        b_exists: b.is_some(),
+        identical: a.is_some() && b.is_some() && hunks.is_empty(),
+        hunks,
+    })
+}
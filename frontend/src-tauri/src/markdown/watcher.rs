@@ -1,8 +1,47 @@
 use serde::{Deserialize, Serialize};
-use tauri::command;
+use tauri::{command, AppHandle, Emitter};
+use notify::event::{ModifyKind, RenameMode};
 use notify::{Watcher, RecommendedWatcher, RecursiveMode, Event, EventKind};
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+use super::indexer::{compute_checksum, reindex_file_checked, LargeNote};
+use super::links::LinkResolver;
+use super::search::{get_or_create_pool, remove_note, rename_note};
+use super::vault::{is_markdown_path, normalize_rel_path, resolve_in_vault, to_rel_path, VaultFilter};
+
+lazy_static::lazy_static! {
+    /// Live watchers by vault path. A watcher stops when it is dropped, so it
+    /// has to outlive the command that started it.
+    static ref WATCHERS: Mutex<HashMap<String, RecommendedWatcher>> = Mutex::new(HashMap::new());
+    /// Auto-index watchers by vault path, kept apart from `WATCHERS` so
+    /// starting one kind doesn't drop the other.
+    static ref INDEX_WATCHERS: Mutex<HashMap<String, RecommendedWatcher>> = Mutex::new(HashMap::new());
+    /// Single-note watchers by `(vault_path, rel_path)`.
+    static ref FILE_WATCHERS: Mutex<HashMap<(String, String), RecommendedWatcher>> = Mutex::new(HashMap::new());
+}
+
+/// Quiet period before a burst of events (an editor's save, a git checkout)
+/// is applied to the index.
+const REINDEX_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Quiet period before a single-file change is reported; long enough to
+/// cover an editor's delete-and-recreate save.
+const FILE_WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+fn keep_watcher(
+    registry: &Mutex<HashMap<String, RecommendedWatcher>>,
+    vault_path: &str,
+    watcher: RecommendedWatcher,
+) -> Result<(), String> {
+    registry
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(vault_path.to_string(), watcher);
+    Ok(())
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileChangeEvent {
@@ -23,9 +62,12 @@ pub async fn markdown_watch_vault(
         .map_err(|e| format!("Failed to create watcher: {}", e))?;
 
     let watch_path = PathBuf::from(&vault_path);
+    let filter = VaultFilter::load(&vault_path);
     watcher.watch(&watch_path, RecursiveMode::Recursive)
         .map_err(|e| format!("Failed to watch path: {}", e))?;
 
+    keep_watcher(&WATCHERS, &vault_path, watcher)?;
+
     std::thread::spawn(move || {
         for res in rx {
             match res {
@@ -38,6 +80,10 @@ pub async fn markdown_watch_vault(
                                 .map(|s| s.replace('\\', "/"))
                                 .unwrap_or_default();
 
+                            if filter.is_ignored(&rel_path) {
+                                continue;
+                            }
+
                             let event_type = match event.kind {
                                 EventKind::Create(_) => "create",
                                 EventKind::Modify(_) => "modify",
@@ -59,3 +105,352 @@ pub async fn markdown_watch_vault(
     Ok(())
 }
 
+/// Stops a `markdown_watch_vault` watcher. An auto-index watcher on the same
+/// vault keeps running; stop it with `markdown_unwatch_and_index`.
+#[command]
+pub async fn markdown_unwatch_vault(vault_path: String) -> Result<bool, String> {
+    let removed = WATCHERS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&vault_path);
+    Ok(removed.is_some())
+}
+
+/// Payload of the `note-reindexed` event. `action` is `indexed`, `removed`
+/// or `renamed`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteReindexedEvent {
+    pub vault_path: String,
+    pub action: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+}
+
+/// Emitted as `large-note` when the auto-indexer meets a note over the
+/// vault's size limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LargeNoteEvent {
+    pub vault_path: String,
+    #[serde(flatten)]
+    pub note: LargeNote,
+}
+
+/// Changes gathered over one debounce window.
+#[derive(Default)]
+struct PendingChanges {
+    renames: Vec<(PathBuf, PathBuf)>,
+    paths: BTreeSet<PathBuf>,
+}
+
+impl PendingChanges {
+    fn add(&mut self, event: Event) {
+        match event.kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                self.renames.push((event.paths[0].clone(), event.paths[1].clone()));
+            }
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
+                self.paths.extend(event.paths);
+            }
+            _ => {}
+        }
+    }
+}
+
+struct AutoIndexer {
+    app: AppHandle,
+    vault_path: String,
+    root: PathBuf,
+    filter: VaultFilter,
+    pool: sqlx::Pool<sqlx::Sqlite>,
+}
+
+impl AutoIndexer {
+    fn notify(&self, action: &str, path: &str, old_path: Option<String>) {
+        let event = NoteReindexedEvent {
+            vault_path: self.vault_path.clone(),
+            action: action.to_string(),
+            path: path.to_string(),
+            old_path,
+        };
+        if let Err(e) = self.app.emit("note-reindexed", event) {
+            log::error!("Failed to emit reindex event: {}", e);
+        }
+    }
+
+    /// Reindexes one note, warning the frontend with `large-note` when it is
+    /// over the vault's size limit.
+    async fn reindex(&self, rel_path: &str, resolver: &mut LinkResolver) -> Result<(), String> {
+        let (_, large) = reindex_file_checked(&self.pool, &self.vault_path, rel_path, Some(resolver)).await?;
+        if let Some(note) = large {
+            let event = LargeNoteEvent {
+                vault_path: self.vault_path.clone(),
+                note,
+            };
+            if let Err(e) = self.app.emit("large-note", event) {
+                log::error!("Failed to emit large note event: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn rel_path(&self, path: &Path) -> Option<String> {
+        let rel_path = to_rel_path(&self.root, path);
+        (!rel_path.is_empty() && !self.filter.is_ignored(&rel_path)).then_some(rel_path)
+    }
+
+    async fn rename(&self, from: &Path, to: &Path, resolver: &mut LinkResolver) {
+        let (Some(old_rel), Some(new_rel)) = (self.rel_path(from), self.rel_path(to)) else {
+            // Moved into or out of an ignored folder: index the side we can see.
+            self.apply(from, resolver).await;
+            self.apply(to, resolver).await;
+            return;
+        };
+
+        // A folder rename moves every note below it.
+        let moves: Vec<(String, String)> = if to.is_dir() {
+            walkdir::WalkDir::new(to)
+                .into_iter()
+                .flatten()
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| to_rel_path(to, entry.path()))
+                .filter(|suffix| is_markdown_path(suffix))
+                .map(|suffix| (format!("{}/{}", old_rel, suffix), format!("{}/{}", new_rel, suffix)))
+                .collect()
+        } else if is_markdown_path(&old_rel) && is_markdown_path(&new_rel) {
+            vec![(old_rel, new_rel)]
+        } else {
+            self.apply(from, resolver).await;
+            self.apply(to, resolver).await;
+            return;
+        };
+
+        for (old_rel, new_rel) in moves {
+            if let Err(e) = rename_note(&self.pool, &old_rel, &new_rel).await {
+                log::error!("Failed to rename {} in index: {}", old_rel, e);
+                continue;
+            }
+            resolver.remove_note(&old_rel);
+            if let Err(e) = self.reindex(&new_rel, resolver).await {
+                log::error!("Failed to reindex {}: {}", new_rel, e);
+            }
+            self.notify("renamed", &new_rel, Some(old_rel));
+        }
+    }
+
+    /// Reindexes a note that exists on disk, or drops it from the index. A
+    /// new folder is scanned, since files can land in it before it is watched.
+    async fn apply(&self, path: &Path, resolver: &mut LinkResolver) {
+        let Some(rel_path) = self.rel_path(path) else {
+            return;
+        };
+        if path.is_dir() {
+            for entry in walkdir::WalkDir::new(path).min_depth(1).into_iter().flatten() {
+                let Some(child) = self.rel_path(entry.path()) else {
+                    continue;
+                };
+                if entry.file_type().is_file() && is_markdown_path(&child) {
+                    match self.reindex(&child, resolver).await {
+                        Ok(_) => self.notify("indexed", &child, None),
+                        Err(e) => log::error!("Failed to reindex {}: {}", child, e),
+                    }
+                }
+            }
+            return;
+        }
+        if !is_markdown_path(&rel_path) {
+            return;
+        }
+
+        if path.is_file() {
+            match self.reindex(&rel_path, resolver).await {
+                Ok(_) => self.notify("indexed", &rel_path, None),
+                Err(e) => log::error!("Failed to reindex {}: {}", rel_path, e),
+            }
+        } else if !path.exists() {
+            match remove_note(&self.pool, &rel_path).await {
+                Ok(()) => {
+                    resolver.remove_note(&rel_path);
+                    self.notify("removed", &rel_path, None);
+                }
+                Err(e) => log::error!("Failed to remove {} from index: {}", rel_path, e),
+            }
+        }
+    }
+
+    /// Applies one quiet period's changes, resolving links against a single
+    /// `LinkResolver` so a burst of saves doesn't reload every note per file.
+    async fn flush(&self, changes: PendingChanges) {
+        let mut resolver = match LinkResolver::load(&self.pool).await {
+            Ok(resolver) => resolver,
+            Err(e) => {
+                log::error!("Failed to load notes for link resolution: {}", e);
+                return;
+            }
+        };
+        for (from, to) in &changes.renames {
+            self.rename(from, to, &mut resolver).await;
+        }
+        // Backends that report both halves of a rename separately as well
+        // have already been handled above.
+        for path in &changes.paths {
+            if changes.renames.iter().any(|(from, to)| from == path || to == path) {
+                continue;
+            }
+            self.apply(path, &mut resolver).await;
+        }
+    }
+}
+
+/// Watches the vault and keeps the index in step with it: after each quiet
+/// period, changed notes are reindexed, deleted ones removed and renamed ones
+/// moved along with their links. Each change is reported as a
+/// `note-reindexed` event. Replaces any earlier auto-index watcher on the
+/// same vault, but leaves a `markdown_watch_vault` watcher running.
+#[command]
+pub async fn markdown_watch_and_index(
+    app: AppHandle,
+    vault_path: String,
+    index_path: String,
+) -> Result<(), String> {
+    let pool = get_or_create_pool(&index_path).await?;
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
+
+    let mut watcher: RecommendedWatcher = Watcher::new(
+        move |res: notify::Result<Event>| match res {
+            Ok(event) => {
+                let _ = tx.send(event);
+            }
+            Err(e) => log::error!("Watcher error: {}", e),
+        },
+        notify::Config::default(),
+    )
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    watcher.watch(Path::new(&vault_path), RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch path: {}", e))?;
+    keep_watcher(&INDEX_WATCHERS, &vault_path, watcher)?;
+
+    let indexer = AutoIndexer {
+        app,
+        root: PathBuf::from(&vault_path),
+        filter: VaultFilter::load(&vault_path),
+        vault_path,
+        pool,
+    };
+
+    // The channel closes when the watcher is dropped, which ends the task.
+    tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            let mut changes = PendingChanges::default();
+            changes.add(event);
+            while let Ok(Some(event)) = tokio::time::timeout(REINDEX_DEBOUNCE, rx.recv()).await {
+                changes.add(event);
+            }
+            indexer.flush(changes).await;
+        }
+    });
+
+    Ok(())
+}
+
+/// Stops a `markdown_watch_and_index` watcher, leaving any
+/// `markdown_watch_vault` watcher on the vault alone.
+#[command]
+pub async fn markdown_unwatch_and_index(vault_path: String) -> Result<bool, String> {
+    let removed = INDEX_WATCHERS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&vault_path);
+    Ok(removed.is_some())
+}
+
+/// Payload of the `file-changed` and `file-deleted` events.
+/// `checksum` is the new content's, so the frontend can tell its own saves
+/// apart from external edits; it is absent for deletions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileWatchEvent {
+    pub vault_path: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+fn read_checksum(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok().map(|raw| compute_checksum(&raw))
+}
+
+/// Watches one note for external edits, emitting `file-changed` when its
+/// content changes and `file-deleted` when it goes away. The note's folder
+/// is watched without recursing rather than the file itself, so editors
+/// that save by replacing the file keep being followed; events for other
+/// files in the folder are ignored. Replaces any earlier watch on the note.
+#[command]
+pub async fn markdown_watch_file(app: AppHandle, vault_path: String, rel_path: String) -> Result<(), String> {
+    let rel_path = normalize_rel_path(&rel_path)?;
+    let full_path = resolve_in_vault(&vault_path, &rel_path)?;
+    if !full_path.is_file() {
+        return Err(format!("Not a file: {}", rel_path));
+    }
+    let (Some(dir), Some(file_name)) = (full_path.parent(), full_path.file_name()) else {
+        return Err(format!("Not a file: {}", rel_path));
+    };
+    let file_name = file_name.to_os_string();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+
+    let mut watcher: RecommendedWatcher = Watcher::new(
+        move |res: notify::Result<Event>| match res {
+            Ok(event) => {
+                if event.paths.iter().any(|p| p.file_name() == Some(file_name.as_os_str())) {
+                    let _ = tx.send(());
+                }
+            }
+            Err(e) => log::error!("File watcher error: {}", e),
+        },
+        notify::Config::default(),
+    )
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch path: {}", e))?;
+
+    FILE_WATCHERS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert((vault_path.clone(), rel_path.clone()), watcher);
+
+    // The channel closes when the watcher is dropped, which ends the task.
+    tokio::spawn(async move {
+        let mut last = read_checksum(&full_path);
+        while rx.recv().await.is_some() {
+            while let Ok(Some(())) = tokio::time::timeout(FILE_WATCH_DEBOUNCE, rx.recv()).await {}
+
+            let checksum = if full_path.is_file() { read_checksum(&full_path) } else { None };
+            let name = match (&last, &checksum) {
+                (_, Some(new)) if last.as_ref() != Some(new) => "file-changed",
+                (Some(_), None) if !full_path.exists() => "file-deleted",
+                _ => continue,
+            };
+            last = checksum.clone();
+            let event = FileWatchEvent {
+                vault_path: vault_path.clone(),
+                path: rel_path.clone(),
+                checksum,
+            };
+            if let Err(e) = app.emit(name, event) {
+                log::error!("Failed to emit {} event: {}", name, e);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[command]
+pub async fn markdown_unwatch_file(vault_path: String, rel_path: String) -> Result<bool, String> {
+    let rel_path = normalize_rel_path(&rel_path)?;
+    let removed = FILE_WATCHERS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&(vault_path, rel_path));
+    Ok(removed.is_some())
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Manager, Url, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent};
+
+use super::indexer::compute_checksum;
+use super::vault::{normalize_rel_path, resolve_in_vault};
+use crate::window::restore_window_zoom;
+
+lazy_static::lazy_static! {
+    static ref NOTE_WINDOWS: Mutex<HashMap<String, NoteWindow>> = Mutex::new(HashMap::new());
+}
+
+/// A note popped out into its own window, keyed by window label.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteWindow {
+    pub label: String,
+    pub vault_path: String,
+    pub rel_path: String,
+}
+
+/// The vault's path with symlinks, `.` segments and trailing separators
+/// resolved, or the path as given when it can't be resolved. Windows'
+/// `\\?\` prefix is dropped so the path still reads as the user wrote it.
+fn canonical_vault_path(vault_path: &str) -> String {
+    let Ok(canonical) = std::fs::canonicalize(vault_path) else {
+        return vault_path.to_string();
+    };
+    let canonical = canonical.to_string_lossy();
+    match canonical.strip_prefix(r"\\?\") {
+        Some(unc) if unc.starts_with(r"UNC\") => format!(r"\\{}", &unc[4..]),
+        Some(local) => local.to_string(),
+        None => canonical.to_string(),
+    }
+}
+
+/// Window labels only allow a narrow character set, so the label is a hash of
+/// the note's location, normalised first so `./a.md` and `a.md`, or a vault
+/// path with a trailing slash, map to the same window.
+pub fn note_window_label(vault_path: &str, rel_path: &str) -> Result<String, String> {
+    let hash = compute_checksum(&format!("{}\n{}", canonical_vault_path(vault_path), normalize_rel_path(rel_path)?));
+    Ok(format!("note-{}", &hash[..16]))
+}
+
+/// Editor route for a popped-out note, with the vault and note as query
+/// params.
+fn note_window_url(vault_path: &str, rel_path: &str) -> Result<String, String> {
+    let mut url = Url::parse("tauri://localhost/dashboard/notes").map_err(|e| e.to_string())?;
+    url.query_pairs_mut()
+        .append_pair("vault", vault_path)
+        .append_pair("note", rel_path)
+        .append_pair("window", "note");
+    Ok(format!("dashboard/notes?{}", url.query().unwrap_or_default()))
+}
+
+pub fn open_note_windows() -> Vec<NoteWindow> {
+    let mut windows: Vec<NoteWindow> = NOTE_WINDOWS
+        .lock()
+        .map(|windows| windows.values().cloned().collect())
+        .unwrap_or_default();
+    windows.sort_by(|a, b| a.label.cmp(&b.label));
+    windows
+}
+
+/// Opens a note in its own window, or returns the existing window after
+/// bringing it to the front.
+pub fn open_note_window(app: &AppHandle, vault_path: &str, rel_path: &str) -> Result<WebviewWindow, String> {
+    let (vault_path, rel_path) = (canonical_vault_path(vault_path), normalize_rel_path(rel_path)?);
+    let (vault_path, rel_path) = (vault_path.as_str(), rel_path.as_str());
+    let full_path = resolve_in_vault(vault_path, rel_path)?;
+    if !full_path.is_file() {
+        return Err(format!("Note not found: {}", rel_path));
+    }
+
+    let label = note_window_label(vault_path, rel_path)?;
+    if let Some(window) = app.get_webview_window(&label) {
+        window.unminimize().map_err(|e| format!("Failed to restore note window: {}", e))?;
+        window.show().map_err(|e| format!("Failed to show note window: {}", e))?;
+        window.set_focus().map_err(|e| format!("Failed to focus note window: {}", e))?;
+        return Ok(window);
+    }
+
+    let title = rel_path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.strip_suffix(".md"))
+        .unwrap_or(rel_path)
+        .to_string();
+    let url = note_window_url(vault_path, rel_path)?;
+
+    let window = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
+        .title(title)
+        .inner_size(800.0, 600.0)
+        .build()
+        .map_err(|e| format!("Failed to open note window: {}", e))?;
+    restore_window_zoom(&window);
+
+    NOTE_WINDOWS.lock().map_err(|e| e.to_string())?.insert(
+        label.clone(),
+        NoteWindow {
+            label: label.clone(),
+            vault_path: vault_path.to_string(),
+            rel_path: rel_path.to_string(),
+        },
+    );
+
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            if let Ok(mut windows) = NOTE_WINDOWS.lock() {
+                windows.remove(&label);
+            }
+        }
+    });
+
+    Ok(window)
+}
+
+/// Opens a note in its own window, or focuses the window if the note is
+/// already open. Returns the window label. Note windows close normally; only
+/// the main window hides on close.
+#[command]
+pub async fn markdown_open_note_window(app: AppHandle, vault_path: String, rel_path: String) -> Result<String, String> {
+    open_note_window(&app, &vault_path, &rel_path).map(|window| window.label().to_string())
+}
+
+/// Notes currently open in their own windows.
+#[command]
+pub async fn markdown_list_note_windows() -> Result<Vec<NoteWindow>, String> {
+    Ok(open_note_windows())
+}
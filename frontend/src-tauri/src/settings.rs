@@ -0,0 +1,22 @@
+use serde::{de::DeserializeOwned, Serialize};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::StoreExt;
+
+/// App-level preferences that outlive a session, kept apart from per-vault
+/// config in `<vault>/.vault/config.json`.
+pub const SETTINGS_STORE: &str = "settings.json";
+
+pub fn load_setting<R: Runtime, T: DeserializeOwned>(app: &AppHandle<R>, key: &str) -> Option<T> {
+    let store = app.store(SETTINGS_STORE).ok()?;
+    let value = store.get(key)?;
+    serde_json::from_value(value).ok()
+}
+
+pub fn save_setting<R: Runtime, T: Serialize>(app: &AppHandle<R>, key: &str, value: &T) -> Result<(), String> {
+    let store = app
+        .store(SETTINGS_STORE)
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    let value = serde_json::to_value(value).map_err(|e| format!("Failed to serialize setting: {}", e))?;
+    store.set(key, value);
+    store.save().map_err(|e| format!("Failed to save settings: {}", e))
+}
@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Emitter, Manager, Runtime, State, WebviewWindow};
+
+use crate::settings::{load_setting, save_setting};
+
+/// Shows or hides the dock icon. On macOS the app starts as an accessory
+/// (tray-only) app; other platforms have no dock, so this does nothing there.
+#[command]
+pub async fn window_set_dock_visible(app: AppHandle, visible: bool) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        let policy = if visible {
+            tauri::ActivationPolicy::Regular
+        } else {
+            tauri::ActivationPolicy::Accessory
+        };
+        app.set_activation_policy(policy)
+            .map_err(|e| format!("Failed to change dock visibility: {}", e))?;
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    let _ = (app, visible);
+
+    Ok(())
+}
+
+/// What closing the main window does. `Ask` leaves the decision to the
+/// frontend via the `confirm-quit` event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CloseBehavior {
+    #[default]
+    Hide,
+    Quit,
+    Ask,
+}
+
+const CLOSE_BEHAVIOR_KEY: &str = "close_behavior";
+
+/// Window preferences held in memory for the event handlers, which can't
+/// wait on a store read. Commands update both this and the settings store.
+#[derive(Default)]
+pub struct WindowState {
+    pub close_behavior: Mutex<CloseBehavior>,
+}
+
+impl WindowState {
+    pub fn load(app: &AppHandle) -> WindowState {
+        WindowState {
+            close_behavior: Mutex::new(load_setting(app, CLOSE_BEHAVIOR_KEY).unwrap_or_default()),
+        }
+    }
+
+    pub fn close_behavior(&self) -> CloseBehavior {
+        self.close_behavior.lock().map(|b| *b).unwrap_or_default()
+    }
+}
+
+#[command]
+pub async fn window_get_close_behavior(state: State<'_, WindowState>) -> Result<CloseBehavior, String> {
+    Ok(state.close_behavior())
+}
+
+#[command]
+pub async fn window_set_close_behavior(
+    app: AppHandle,
+    state: State<'_, WindowState>,
+    behavior: CloseBehavior,
+) -> Result<(), String> {
+    *state.close_behavior.lock().map_err(|e| e.to_string())? = behavior;
+    save_setting(&app, CLOSE_BEHAVIOR_KEY, &behavior)
+}
+
+/// Quits for real, e.g. after the user confirms a `confirm-quit` prompt.
+#[command]
+pub async fn window_quit_app(app: AppHandle) -> Result<(), String> {
+    app.exit(0);
+    Ok(())
+}
+
+const STATUS_CLICK_THROUGH_KEY: &str = "status_click_through";
+
+/// Lets clicks fall through the status indicator to whatever is beneath it.
+/// While enabled the widget gets no mouse events at all, so dragging and its
+/// context menu only come back once this is turned off again.
+#[command]
+pub async fn set_status_window_click_through(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("status_indicator")
+        .ok_or_else(|| "Status indicator window not found".to_string())?;
+    window
+        .set_ignore_cursor_events(enabled)
+        .map_err(|e| format!("Failed to set click-through: {}", e))?;
+    save_setting(&app, STATUS_CLICK_THROUGH_KEY, &enabled)
+}
+
+/// Reapplies the saved click-through preference to a newly created widget.
+pub fn restore_status_click_through<R: Runtime>(window: &WebviewWindow<R>) {
+    let enabled: bool = load_setting(window.app_handle(), STATUS_CLICK_THROUGH_KEY).unwrap_or(false);
+    if enabled {
+        if let Err(e) = window.set_ignore_cursor_events(true) {
+            log::warn!("Failed to restore status indicator click-through: {}", e);
+        }
+    }
+}
+
+/// Turns click-through off, so "Reset Status Indicator" in the tray always
+/// leaves a widget the user can reach.
+pub fn reset_status_click_through<R: Runtime>(window: &WebviewWindow<R>) {
+    if let Err(e) = window.set_ignore_cursor_events(false) {
+        log::warn!("Failed to make status indicator interactive: {}", e);
+    }
+    if let Err(e) = save_setting(window.app_handle(), STATUS_CLICK_THROUGH_KEY, &false) {
+        log::warn!("{}", e);
+    }
+}
+
+const MAIN_ALWAYS_ON_TOP_KEY: &str = "main_always_on_top";
+
+/// Keeps the main window above other apps, for reading notes alongside
+/// them. Only the flag changes: a hidden window stays hidden and focus stays
+/// where it is.
+#[command]
+pub async fn set_main_always_on_top(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+    window
+        .set_always_on_top(enabled)
+        .map_err(|e| format!("Failed to set always on top: {}", e))?;
+    save_setting(&app, MAIN_ALWAYS_ON_TOP_KEY, &enabled)
+}
+
+#[command]
+pub async fn get_main_always_on_top(app: AppHandle) -> Result<bool, String> {
+    Ok(load_setting(&app, MAIN_ALWAYS_ON_TOP_KEY).unwrap_or(false))
+}
+
+/// Reapplies the saved always-on-top preference to the main window at launch.
+pub fn restore_main_always_on_top<R: Runtime>(window: &WebviewWindow<R>) {
+    let enabled: bool = load_setting(window.app_handle(), MAIN_ALWAYS_ON_TOP_KEY).unwrap_or(false);
+    if enabled {
+        if let Err(e) = window.set_always_on_top(true) {
+            log::warn!("Failed to restore main window always on top: {}", e);
+        }
+    }
+}
+
+const PINNED_NOTES_KEY: &str = "pinned_notes";
+
+#[command]
+pub async fn get_pinned_notes(app: AppHandle) -> Result<Vec<String>, String> {
+    Ok(load_setting(&app, PINNED_NOTES_KEY).unwrap_or_default())
+}
+
+/// Replaces the notes pinned to the status indicator, in display order, and
+/// sends the new list to the widget as `pinned-notes-changed`. Blank and
+/// repeated paths are dropped.
+#[command]
+pub async fn set_pinned_notes(app: AppHandle, paths: Vec<String>) -> Result<Vec<String>, String> {
+    let mut pinned: Vec<String> = Vec::with_capacity(paths.len());
+    for path in paths {
+        let path = path.trim().to_string();
+        if !path.is_empty() && !pinned.contains(&path) {
+            pinned.push(path);
+        }
+    }
+    save_setting(&app, PINNED_NOTES_KEY, &pinned)?;
+    if let Err(e) = app.emit_to("status_indicator", "pinned-notes-changed", &pinned) {
+        log::warn!("Failed to notify status indicator of pins: {}", e);
+    }
+    Ok(pinned)
+}
+
+/// For a click on a pin: brings the main window forward and asks it to open
+/// the note with an `open-pinned-note` event.
+#[command]
+pub async fn open_pinned_note(app: AppHandle, path: String) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "Main window not found".to_string())?;
+    window.show().map_err(|e| format!("Failed to show main window: {}", e))?;
+    if let Err(e) = window.unminimize() {
+        log::warn!("Failed to unminimize main window: {}", e);
+    }
+    window.set_focus().map_err(|e| format!("Failed to focus main window: {}", e))?;
+    window
+        .emit("open-pinned-note", &path)
+        .map_err(|e| format!("Failed to open pinned note: {}", e))
+}
+
+const WINDOW_ZOOM_KEY: &str = "window_zoom";
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 3.0;
+
+/// Scales a window's web content, for larger text. The factor is clamped to
+/// 0.5–3.0 and saved under the window's label, so note windows keep their
+/// zoom when reopened. Returns the factor applied.
+#[command]
+pub async fn set_window_zoom(app: AppHandle, label: String, factor: f64) -> Result<f64, String> {
+    if !factor.is_finite() {
+        return Err("Zoom factor must be a number".to_string());
+    }
+    let factor = factor.clamp(MIN_ZOOM, MAX_ZOOM);
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("Window not found: {}", label))?;
+    window
+        .set_zoom(factor)
+        .map_err(|e| format!("Failed to set zoom: {}", e))?;
+
+    let mut zoom: BTreeMap<String, f64> = load_setting(&app, WINDOW_ZOOM_KEY).unwrap_or_default();
+    if factor == 1.0 {
+        zoom.remove(&label);
+    } else {
+        zoom.insert(label, factor);
+    }
+    save_setting(&app, WINDOW_ZOOM_KEY, &zoom)?;
+    Ok(factor)
+}
+
+/// Reapplies a window's saved zoom after it is created.
+pub fn restore_window_zoom<R: Runtime>(window: &WebviewWindow<R>) {
+    let zoom: BTreeMap<String, f64> = load_setting(window.app_handle(), WINDOW_ZOOM_KEY).unwrap_or_default();
+    if let Some(factor) = zoom.get(window.label()) {
+        if let Err(e) = window.set_zoom(factor.clamp(MIN_ZOOM, MAX_ZOOM)) {
+            log::warn!("Failed to restore zoom for {}: {}", window.label(), e);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{command, AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize};
+
+use crate::markdown::windows::{open_note_window, open_note_windows};
+use crate::settings::{load_setting, save_setting};
+
+const WORKSPACES_KEY: &str = "workspaces";
+
+/// A note window as it was laid out, in physical pixels.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceWindow {
+    pub label: String,
+    pub vault_path: String,
+    pub rel_path: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    pub saved_at: String,
+    pub windows: Vec<WorkspaceWindow>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreWorkspaceResult {
+    /// Labels of the windows that were opened or moved into place.
+    pub restored: Vec<String>,
+    /// Notes that could not be reopened, as `path: error`.
+    pub failed: Vec<String>,
+}
+
+fn load_workspaces(app: &AppHandle) -> BTreeMap<String, Workspace> {
+    load_setting(app, WORKSPACES_KEY).unwrap_or_default()
+}
+
+/// Where to put a saved window given the monitors attached now. A window
+/// whose top-left corner is no longer on any monitor is centred on the
+/// primary one instead, and shrunk to fit if that monitor is smaller.
+fn place_window(saved: &WorkspaceWindow, monitors: &[Monitor], primary: Option<&Monitor>) -> Option<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+    let on_screen = monitors.iter().find(|monitor| {
+        let position = monitor.position();
+        let size = monitor.size();
+        saved.x >= position.x
+            && saved.y >= position.y
+            && saved.x < position.x + size.width as i32
+            && saved.y < position.y + size.height as i32
+    });
+    let monitor = on_screen.or(primary).or(monitors.first())?;
+
+    let position = monitor.position();
+    let bounds = monitor.size();
+    let width = saved.width.min(bounds.width);
+    let height = saved.height.min(bounds.height);
+
+    let (x, y) = if on_screen.is_some() {
+        // Keep the window from running off the right or bottom edge.
+        let max_x = position.x + (bounds.width - width) as i32;
+        let max_y = position.y + (bounds.height - height) as i32;
+        (saved.x.min(max_x), saved.y.min(max_y))
+    } else {
+        (
+            position.x + ((bounds.width - width) / 2) as i32,
+            position.y + ((bounds.height - height) / 2) as i32,
+        )
+    };
+
+    Some((PhysicalPosition::new(x, y), PhysicalSize::new(width, height)))
+}
+
+/// Saves the open note windows with their positions and sizes under `name`,
+/// replacing any workspace of the same name.
+#[command]
+pub async fn save_workspace(app: AppHandle, name: String) -> Result<Workspace, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Workspace name cannot be empty".to_string());
+    }
+
+    let mut windows = Vec::new();
+    for note in open_note_windows() {
+        let Some(window) = app.get_webview_window(&note.label) else {
+            continue;
+        };
+        let position = window
+            .outer_position()
+            .map_err(|e| format!("Failed to read window position: {}", e))?;
+        let size = window
+            .inner_size()
+            .map_err(|e| format!("Failed to read window size: {}", e))?;
+        windows.push(WorkspaceWindow {
+            label: note.label,
+            vault_path: note.vault_path,
+            rel_path: note.rel_path,
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        });
+    }
+
+    let workspace = Workspace {
+        name: name.clone(),
+        saved_at: chrono::Utc::now().to_rfc3339(),
+        windows,
+    };
+
+    let mut workspaces = load_workspaces(&app);
+    workspaces.insert(name, workspace.clone());
+    save_setting(&app, WORKSPACES_KEY, &workspaces)?;
+
+    Ok(workspace)
+}
+
+/// Reopens the note windows saved under `name` and moves them into place.
+/// Windows already open for those notes are reused; other windows are left
+/// alone.
+#[command]
+pub async fn restore_workspace(app: AppHandle, name: String) -> Result<RestoreWorkspaceResult, String> {
+    let workspace = load_workspaces(&app)
+        .remove(name.trim())
+        .ok_or_else(|| format!("Workspace not found: {}", name))?;
+
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?;
+    let primary = app
+        .primary_monitor()
+        .map_err(|e| format!("Failed to find primary monitor: {}", e))?;
+
+    let mut result = RestoreWorkspaceResult {
+        restored: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for saved in &workspace.windows {
+        let window = match open_note_window(&app, &saved.vault_path, &saved.rel_path) {
+            Ok(window) => window,
+            Err(e) => {
+                result.failed.push(format!("{}: {}", saved.rel_path, e));
+                continue;
+            }
+        };
+
+        if let Some((position, size)) = place_window(saved, &monitors, primary.as_ref()) {
+            if let Err(e) = window.set_size(size) {
+                log::warn!("Failed to resize {}: {}", saved.rel_path, e);
+            }
+            if let Err(e) = window.set_position(position) {
+                log::warn!("Failed to move {}: {}", saved.rel_path, e);
+            }
+        }
+        result.restored.push(window.label().to_string());
+    }
+
+    Ok(result)
+}
+
+#[command]
+pub async fn list_workspaces(app: AppHandle) -> Result<Vec<Workspace>, String> {
+    Ok(load_workspaces(&app).into_values().collect())
+}
+
+/// Returns false when no workspace had that name.
+#[command]
+pub async fn delete_workspace(app: AppHandle, name: String) -> Result<bool, String> {
+    let mut workspaces = load_workspaces(&app);
+    if workspaces.remove(name.trim()).is_none() {
+        return Ok(false);
+    }
+    save_setting(&app, WORKSPACES_KEY, &workspaces)?;
+    Ok(true)
+}
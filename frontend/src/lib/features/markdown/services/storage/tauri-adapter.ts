@@ -159,14 +159,17 @@ export class TauriStorageAdapter implements StorageAdapter {
     const fullPath = joinPath(vaultId, normalizePath(path))
     await writeTextFile(fullPath, content)
     await this.indexNote(vaultId, path, content)
+    await this.snapshotNote(vaultId, path)
     return contentToNote(vaultId, path, content, nanoid())
   }
 
   async deleteNote(vaultId: string, path: string): Promise<void> {
-    const { removeFile } = await import('@tauri-apps/plugin-fs')
-    const fullPath = joinPath(vaultId, normalizePath(path))
-    await removeFile(fullPath)
-    await this.removeFromIndex(vaultId, path)
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('markdown_delete_note', {
+      vaultPath: vaultId,
+      indexPath: joinPath(vaultId, '.vault', 'index.db'),
+      relPath: normalizePath(path)
+    })
   }
 
   async moveNote(vaultId: string, from: string, to: string): Promise<Note> {
@@ -186,14 +189,9 @@ export class TauriStorageAdapter implements StorageAdapter {
     const { invoke } = await import('@tauri-apps/api/core')
     const indexPath = joinPath(vaultId, '.vault', 'index.db')
 
-    let filter = ''
-    if (folder) {
-      filter = `WHERE path LIKE '${normalizePath(folder)}%'`
-    }
-
-    const results = await invoke<any[]>('markdown_list_notes', {
+    const { notes: results } = await invoke<{ notes: any[]; total: number }>('markdown_list_notes', {
       indexPath,
-      filter
+      filter: folder ? { folder: normalizePath(folder) } : null
     })
 
     return results.map(r => {
@@ -286,6 +284,16 @@ export class TauriStorageAdapter implements StorageAdapter {
     })
   }
 
+  private async snapshotNote(vaultId: string, path: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    try {
+      await invoke('markdown_snapshot_note', { vaultPath: vaultId, relPath: normalizePath(path) })
+    } catch (error) {
+      // Version history is best-effort; a failed snapshot must not fail the save.
+      console.warn('Failed to snapshot note:', error)
+    }
+  }
+
   private async removeFromIndex(vaultId: string, path: string): Promise<void> {
     const { invoke } = await import('@tauri-apps/api/core')
     const indexPath = joinPath(vaultId, '.vault', 'index.db')
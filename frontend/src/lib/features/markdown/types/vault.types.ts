@@ -10,6 +10,30 @@ export interface VaultSettings {
   useWikiLinks: boolean
   autoSave: boolean
   autoSaveInterval: number
+  indexMaxConnections?: number
+  maxVersionsPerNote?: number
+  namingRule?: NamingRule
+  largeNoteThreshold?: number
+  largeNoteMetadataOnly?: boolean
+  frontmatterSchema?: FrontmatterSchema
+}
+
+export interface NamingRule {
+  case?: 'kebab' | 'snake'
+  datePrefix?: boolean
+  maxLength?: number
+}
+
+export interface FrontmatterFieldRule {
+  key: string
+  required?: boolean
+  type?: 'string' | 'number' | 'boolean' | 'list' | 'date'
+  allowed?: Array<string | number | boolean>
+}
+
+export interface FrontmatterSchema {
+  folder?: string
+  fields: FrontmatterFieldRule[]
 }
 
 export const DEFAULT_VAULT_SETTINGS: VaultSettings = {
@@ -1,8 +1,9 @@
+// Must stay in sync with `compute_checksum` in src-tauri: line endings are
+// normalised to `\n` before hashing with SHA-256.
 export async function calculateChecksum(content: string): Promise<string> {
   const encoder = new TextEncoder()
-  const data = encoder.encode(content)
+  const data = encoder.encode(content.replace(/\r\n?/g, '\n'))
   const hashBuffer = await crypto.subtle.digest('SHA-256', data)
   const hashArray = Array.from(new Uint8Array(hashBuffer))
   return hashArray.map(b => b.toString(16).padStart(2, '0')).join('')
 }
-
@@ -1,5 +1,6 @@
 import { nanoid } from 'nanoid'
 import type { ParsedLink, LinkType, LinkPosition } from '../types'
+import { parseFrontmatter } from './frontmatter'
 
 export function parseLinks(content: string): ParsedLink[] {
   const links: ParsedLink[] = []
@@ -87,12 +88,33 @@ export function getLinkContext(content: string, position: LinkPosition, contextL
 }
 
 export function extractTags(content: string): string[] {
+  const { frontmatter, body } = parseFrontmatter(content)
   const tags = new Set<string>()
+  const add = (tag: string) => {
+    const cleaned = tag.trim().replace(/^#+/, '').replace(/^\/+|\/+$/g, '')
+    if (cleaned && !/^\d+$/.test(cleaned)) tags.add(cleaned)
+  }
+
+  const declared = frontmatter.tags
+  if (Array.isArray(declared)) declared.forEach((tag) => typeof tag === 'string' && add(tag))
+  else if (typeof declared === 'string') add(declared)
+
+  // Inline tags follow whitespace or start a line; code is skipped.
+  const inlineTagRegex = /(^|\s)#([a-zA-Z0-9_\-/]+)/g
+  let inFence = false
+  for (const line of body.split('\n')) {
+    const trimmed = line.trimStart()
+    if (trimmed.startsWith('```') || trimmed.startsWith('~~~')) {
+      inFence = !inFence
+      continue
+    }
+    if (inFence) continue
 
-  const inlineTagRegex = /#([a-zA-Z0-9_-]+)/g
-  let match
-  while ((match = inlineTagRegex.exec(content)) !== null) {
-    tags.add(match[1])
+    const withoutCode = line.replace(/`[^`]*`/g, (code) => ' '.repeat(code.length))
+    let match
+    while ((match = inlineTagRegex.exec(withoutCode)) !== null) {
+      add(match[2])
+    }
   }
 
   return Array.from(tags)